@@ -5,39 +5,37 @@ use std::ops::{Generator, GeneratorState};
 use std::pin::Pin;
 use std::time::Duration;
 
-pub type Num = f64;
-
-pub enum Expr {
-    Num(Num),
-    Add(Box<Self>, Box<Self>),
-    Mul(Box<Self>, Box<Self>),
-}
-
-impl Drop for Expr {
-    fn drop(&mut self) {
-        std::mem::forget(std::mem::take(self));
-    }
+use stack_safe::testdata::calc::{self as examples, Expr, Num, Ops};
+
+pub trait Eval {
+    fn eval_recursive(&self) -> Num;
+    fn eval_stacker(&self) -> Num;
+    fn eval_trampolined(&self) -> Num;
+    fn eval_trampolined_in_place(&self) -> Num;
+    fn eval_trampolined_opt(&self) -> Num;
+    fn eval_iterative_cps(&self) -> Num;
+    fn eval_iterative_rpn(&self) -> Num;
 }
 
-impl Default for Expr {
-    fn default() -> Self {
-        Self::Num(0 as Num)
-    }
-}
-
-impl Expr {
-    pub fn eval_recursive(&self) -> Num {
+impl Eval for Expr {
+    fn eval_recursive(&self) -> Num {
         match self {
             Self::Num(num) => *num,
             Self::Add(expr1, expr2) => expr1.eval_recursive() + expr2.eval_recursive(),
             Self::Mul(expr1, expr2) => expr1.eval_recursive() * expr2.eval_recursive(),
         }
     }
-}
 
-impl Expr {
-    pub fn eval_trampolined<'a>(&'a self) -> Num {
-        trampoline(|e: &'a Self| {
+    fn eval_stacker(&self) -> Num {
+        stacker::maybe_grow(32 * 1024, 1024 * 1024, || match self {
+            Self::Num(num) => *num,
+            Self::Add(expr1, expr2) => expr1.eval_stacker() + expr2.eval_stacker(),
+            Self::Mul(expr1, expr2) => expr1.eval_stacker() * expr2.eval_stacker(),
+        })
+    }
+
+    fn eval_trampolined<'a>(&'a self) -> Num {
+        trampoline_moving(|e: &'a Self| {
             move |_| match e {
                 Self::Num(n) => *n,
                 Self::Add(e1, e2) => (yield e1.as_ref()) + (yield e2.as_ref()),
@@ -46,7 +44,19 @@ impl Expr {
         })(self)
     }
 
-    pub fn eval_trampolined_opt(&self) -> Num {
+    /// Like `eval_trampolined`, but with `stack_safe::trampoline`, which
+    /// resumes the frames in place on the stack instead of moving them.
+    fn eval_trampolined_in_place<'a>(&'a self) -> Num {
+        stack_safe::trampoline(|e: &'a Self| {
+            move |_| match e {
+                Self::Num(n) => *n,
+                Self::Add(e1, e2) => (yield e1.as_ref()) + (yield e2.as_ref()),
+                Self::Mul(e1, e2) => (yield e1.as_ref()) * (yield e2.as_ref()),
+            }
+        })(self)
+    }
+
+    fn eval_trampolined_opt(&self) -> Num {
         pub enum Gen<'a> {
             Init { expr: &'a Expr },
             Add1 { rhs: &'a Expr },
@@ -111,12 +121,10 @@ impl Expr {
             }
         }
 
-        trampoline(Gen::init)(self)
+        trampoline_moving(Gen::init)(self)
     }
-}
 
-impl Expr {
-    pub fn eval_iterative_cps(&self) -> Num {
+    fn eval_iterative_cps(&self) -> Num {
         enum Cont<'a> {
             AddLhs { rhs: &'a Expr },
             AddRhs { lhs: Num },
@@ -166,10 +174,8 @@ impl Expr {
             }
         }
     }
-}
 
-impl Expr {
-    pub fn eval_iterative_rpn(&self) -> Num {
+    fn eval_iterative_rpn(&self) -> Num {
         enum Item<'a> {
             Operand(&'a Expr),
             Add,
@@ -220,137 +226,15 @@ impl Expr {
     }
 }
 
-mod examples {
-    use std::fmt::Display;
-
-    use super::*;
-    use rand::random;
-
-    pub struct Case {
-        pub expr: Expr,
-        pub eval: Num,
-        pub size: usize,
-    }
-
-    #[derive(Clone, Copy)]
-    pub enum Ops {
-        Add,
-        Mul,
-        Rnd,
-    }
-
-    impl Display for Ops {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            match self {
-                Self::Add => write!(f, "add"),
-                Self::Mul => write!(f, "mul"),
-                Self::Rnd => write!(f, "rnd"),
-            }
-        }
-    }
-
-    // impl Ops {
-    //     fn random(self) -> (&'static dyn Fn (Box<Expr>, Box<Expr>) -> Expr, fn (Num, Num) -> Num) {
-    //         use std::ops::Add;
-
-    //         let add = (&Expr::Add, Num::add);
-    //         match self {
-    //             Ops::Add => add,
-    //             Ops::Mul => todo!(),
-    //             Ops::Both => todo!(),
-    //         }
-    //     }
-    // }
-
-    pub fn simple() -> (Expr, Num) {
-        let expr = {
-            Expr::Add(
-                Box::new(Expr::Num(1 as Num)),
-                Box::new(Expr::Mul(
-                    Box::new(Expr::Num(2 as Num)),
-                    Box::new(Expr::Num(3 as Num)),
-                )),
-            )
-        };
-        (expr, 7 as Num)
-    }
-
-    fn random_num() -> Case {
-        let num = random();
-        Case {
-            expr: Expr::Num(num),
-            eval: num,
-            size: 1,
-        }
-    }
-
-    fn random_bin(ops: Ops, lhs: Case, rhs: Case) -> Case {
-        let lhs_expr = Box::new(lhs.expr);
-        let rhs_expr = Box::new(rhs.expr);
-        let add = match ops {
-            Ops::Add => true,
-            Ops::Mul => false,
-            Ops::Rnd => random(),
-        };
-        let (expr, eval) = if add {
-            (Expr::Add(lhs_expr, rhs_expr), lhs.eval + rhs.eval)
-        } else {
-            (Expr::Mul(lhs_expr, rhs_expr), lhs.eval * rhs.eval)
-        };
-        let size = 1 + lhs.size + rhs.size;
-        Case { expr, eval, size }
-    }
-
-    fn tree_with(ops: Ops, n: usize, leaf: &dyn Fn() -> Case) -> Case {
-        if n == 0 {
-            leaf()
-        } else {
-            random_bin(
-                ops,
-                tree_with(ops, n - 1, leaf),
-                tree_with(ops, n - 1, leaf),
-            )
-        }
-    }
-
-    fn branch_with(ops: Ops, n: usize, leaf: &dyn Fn() -> Case) -> Case {
-        let mut expr = leaf();
-        for _ in 0..n {
-            expr = if random::<bool>() {
-                random_bin(ops, expr, leaf())
-            } else {
-                random_bin(ops, leaf(), expr)
-            }
-        }
-        expr
-    }
-
-    pub fn one_branch(ops: Ops) -> Case {
-        branch_with(ops, 512 * 1024 - 1, &random_num)
-    }
-
-    pub fn many_trees(ops: Ops) -> Case {
-        branch_with(ops, 1023, &|| tree_with(ops, 9, &random_num))
-    }
-
-    pub fn one_tree(ops: Ops) -> Case {
-        tree_with(ops, 19, &random_num)
-    }
-
-    pub fn many_branches(ops: Ops) -> Case {
-        tree_with(ops, 10, &|| branch_with(ops, 511, &random_num))
-    }
-}
-
 fn bench_expr_eval(c: &mut Criterion) {
     #![allow(clippy::type_complexity)]
-    use examples::*;
-
     let implementations: &[(&str, fn(&Expr) -> Num)] = &[
         ("recursive", Expr::eval_recursive),
         ("trampolined", Expr::eval_trampolined),
+        ("trampolined_in_place", Expr::eval_trampolined_in_place),
         ("trampolined_opt", Expr::eval_trampolined_opt),
         ("iterative_cps", Expr::eval_iterative_cps),
+        ("stacker", Expr::eval_stacker),
         // ("iterative_rpn", Expr::eval_iterative_rpn),
     ];
 
@@ -418,7 +302,9 @@ criterion_group! {
 }
 criterion_main!(benches);
 
-pub fn trampoline<Arg, Res, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Res
+/// The driver behind `stack_safe::trampoline` before it resumed frames in
+/// place, kept as a baseline. It moves every frame in and out of the stack.
+pub fn trampoline_moving<Arg, Res, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Res
 where
     Res: Default,
     Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
@@ -0,0 +1,77 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use serde_json::Value;
+use stack_safe::json::{clone_value, drop_value, eq_values, parse};
+use std::time::{Duration, Instant};
+
+fn nested(depth: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..depth {
+        input.push_str("[{\"a\":");
+    }
+    input.push_str("null");
+    for _ in 0..depth {
+        input.push_str("}]");
+    }
+    input
+}
+
+// Like `Bencher::iter` but drops the results outside the measurement and
+// without recursion.
+fn timed(iters: u64, f: impl Fn() -> Value) -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..iters {
+        let start = Instant::now();
+        let value = f();
+        elapsed += start.elapsed();
+        drop_value(value);
+    }
+    elapsed
+}
+
+pub fn bench_json(c: &mut Criterion) {
+    let cases: [(&str, usize); 1] = [("D_{size}", 1_000_000)];
+
+    let mut group = c.benchmark_group("json");
+    for (label, size) in cases {
+        let label = label.replace("{size}", &size.to_string());
+        let input = nested(size);
+        let value = parse(&input).unwrap();
+
+        assert!(serde_json::from_str::<Value>(&input).is_err());
+        let input_clone = input.clone();
+        stack_safe::with_stack_size(1024, move || {
+            let value1 = parse(&input_clone).unwrap();
+            let value2 = clone_value(&value1);
+            assert!(eq_values(&value1, &value2));
+            drop_value(value1);
+            drop_value(value2);
+        })
+        .unwrap();
+
+        group.bench_with_input(BenchmarkId::new("parse", &label), &input, |b, input| {
+            b.iter_custom(|iters| timed(iters, || parse(input).unwrap()))
+        });
+        group.bench_with_input(BenchmarkId::new("clone", &label), &value, |b, value| {
+            b.iter_custom(|iters| timed(iters, || clone_value(value)))
+        });
+        group.bench_with_input(BenchmarkId::new("eq", &label), &value, |b, value| {
+            b.iter(|| assert!(eq_values(value, value)))
+        });
+        group.bench_with_input(BenchmarkId::new("drop", &label), &value, |b, value| {
+            b.iter_batched(|| clone_value(value), drop_value, BatchSize::PerIteration)
+        });
+        drop_value(value);
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .measurement_time(Duration::from_secs(10))
+        .warm_up_time(Duration::from_secs(2))
+        .sample_size(20)
+        .configure_from_args();
+    targets = bench_json
+}
+criterion_main!(benches);
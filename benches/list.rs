@@ -1,93 +1,85 @@
-#![feature(generators, generator_trait, step_trait)]
+#![feature(generators, generator_trait)]
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use std::time::Duration;
 
-mod list {
-    use std::ops::Range;
+const RED_ZONE: usize = 32 * 1024;
+const STACK_SIZE: usize = 1024 * 1024;
 
-    pub enum List<T> {
-        Nil,
-        Cons { head: T, tail: Box<List<T>> },
-    }
+mod list {
+    use super::{RED_ZONE, STACK_SIZE};
+    use stack_safe::list::List;
 
-    impl<T> Drop for List<T> {
-        fn drop(&mut self) {
-            if let Self::Cons { .. } = self {
-                let mut list = std::mem::replace(self, List::Nil);
-                while let Self::Cons { head, tail } = &mut list {
-                    let next = std::mem::replace(tail.as_mut(), Self::Nil);
-                    unsafe {
-                        std::ptr::drop_in_place(head as *mut T);
-                        std::ptr::drop_in_place(tail as *mut Box<List<T>>);
-                    }
-                    std::mem::forget::<List<T>>(list);
-                    list = next;
-                }
-                std::mem::forget::<List<T>>(list);
-            }
+    pub fn len_recursive<T>(list: &List<T>) -> usize {
+        match list.split_first() {
+            None => 0,
+            Some((_, tail)) => 1 + len_recursive(tail),
         }
     }
 
-    impl<T> List<T> {
-        pub fn len_recursive(&self) -> usize {
-            match self {
-                Self::Nil => 0,
-                Self::Cons { head: _, tail } => 1 + tail.len_recursive(),
-            }
-        }
+    pub fn len_stacker<T>(list: &List<T>) -> usize {
+        stacker::maybe_grow(RED_ZONE, STACK_SIZE, || match list.split_first() {
+            None => 0,
+            Some((_, tail)) => 1 + len_stacker(tail),
+        })
+    }
 
-        pub fn len_stack_safe(&self) -> usize {
-            stack_safe::trampoline(|list: &List<T>| {
-                move |_: usize| match list {
-                    Self::Nil => 0,
-                    Self::Cons { head: _, tail } => {
-                        let tail_len = yield tail.as_ref();
-                        1 + tail_len
-                    }
-                }
-            })(self)
-        }
+    pub fn len_loop<T>(list: &List<T>) -> usize {
+        list.len()
     }
 
-    impl<T: std::iter::Step> From<Range<T>> for List<T> {
-        fn from(range: Range<T>) -> Self {
-            let mut result = Self::Nil;
-            for value in range.rev() {
-                result = Self::Cons {
-                    head: value,
-                    tail: Box::new(result),
+    pub fn len_stack_safe<T>(list: &List<T>) -> usize {
+        stack_safe::trampoline(|list: &List<T>| {
+            move |_: usize| match list.split_first() {
+                None => 0,
+                Some((_, tail)) => {
+                    let tail_len = yield tail;
+                    1 + tail_len
                 }
             }
-            result
-        }
+        })(list)
     }
 }
 
 pub fn bench_list_len(c: &mut Criterion) {
     use list::*;
+    use stack_safe::list::List;
 
     let cases: [(&str, usize); 1] = [("L_{size}", 1_000_000)];
 
     let mut group = c.benchmark_group("list_len");
     for (label, size) in cases {
         let label = label.replace("{size}", &size.to_string());
-        let list = List::from(0..size);
+        let list: List<usize> = (0..size).collect();
 
-        assert_eq!(list.len_recursive(), size);
+        assert_eq!(len_recursive(&list), size);
         assert_eq!(
-            stack_safe::with_stack_size(1024, move || List::from(0..size).len_stack_safe())
-                .unwrap(),
+            stack_safe::with_stack_size(1024, move || {
+                len_stack_safe(&(0..size).collect::<List<_>>())
+            })
+            .unwrap(),
             size,
         );
+        assert_eq!(len_stacker(&list), size);
+        assert_eq!(len_loop(&list), size);
 
         group.bench_with_input(BenchmarkId::new("recursive", &label), &list, |b, list| {
             b.iter(|| {
-                assert_eq!(list.len_recursive(), size);
+                assert_eq!(len_recursive(list), size);
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("stacker", &label), &list, |b, list| {
+            b.iter(|| {
+                assert_eq!(len_stacker(list), size);
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("loop", &label), &list, |b, list| {
+            b.iter(|| {
+                assert_eq!(len_loop(list), size);
             })
         });
         group.bench_with_input(BenchmarkId::new("stack_safe", &label), &list, |b, list| {
             b.iter(|| {
-                assert_eq!(list.len_stack_safe(), size);
+                assert_eq!(len_stack_safe(list), size);
             })
         });
     }
@@ -2,37 +2,31 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use std::time::Duration;
 
+const RED_ZONE: usize = 32 * 1024;
+const STACK_SIZE: usize = 1024 * 1024;
+
 mod tree {
+    use super::{RED_ZONE, STACK_SIZE};
     use std::cmp::max;
 
-    #[derive(Clone, Debug)]
-    pub struct Tree {
-        pub value: i64,
-        pub children: Vec<Tree>,
-    }
-
-    impl Tree {
-        pub fn new(id: i64) -> Self {
-            Self {
-                value: id,
-                children: Vec::new(),
-            }
-        }
+    pub use stack_safe::testdata::tree::{self as examples, Tree};
+
+    pub trait Algos {
+        fn depth_recursive(&self) -> usize;
+        fn depth_stack_safe(&self) -> usize;
+        fn depth_visit(&self) -> usize;
+        fn depth_manual(&self) -> usize;
+        fn depth_stacker(&self) -> usize;
+        fn depth_loop(&self) -> usize;
+        fn sum_recursive(&self) -> i64;
+        fn sum_stack_safe(&self) -> i64;
+        fn sum_manual(&self) -> i64;
+        fn sum_stacker(&self) -> i64;
+        fn sum_loop(&self) -> i64;
     }
 
-    impl Drop for Tree {
-        fn drop(&mut self) {
-            if !self.children.is_empty() {
-                let mut stack = std::mem::take(&mut self.children);
-                while let Some(mut node) = stack.pop() {
-                    stack.append(&mut node.children);
-                }
-            }
-        }
-    }
-
-    impl Tree {
-        pub fn depth_recursive(&self) -> usize {
+    impl Algos for Tree {
+        fn depth_recursive(&self) -> usize {
             let mut max_child_depth = 0;
             for child in &self.children {
                 max_child_depth = max(max_child_depth, child.depth_recursive());
@@ -40,7 +34,7 @@ mod tree {
             max_child_depth + 1
         }
 
-        pub fn depth_stack_safe(&self) -> usize {
+        fn depth_stack_safe(&self) -> usize {
             stack_safe::trampoline(|tree: &Self| {
                 move |_: usize| {
                     let mut max_child_depth = 0;
@@ -53,11 +47,53 @@ mod tree {
             })(self)
         }
 
-        pub fn depth_manual(&self) -> usize {
+        fn depth_visit(&self) -> usize {
+            let max_depth = std::cell::Cell::new(0);
+            stack_safe::visit(|(tree, depth): (&Self, usize)| {
+                let max_depth = &max_depth;
+                move |_: ()| {
+                    max_depth.set(max(max_depth.get(), depth));
+                    for child in &tree.children {
+                        yield (child, depth + 1);
+                    }
+                }
+            })((self, 1));
+            max_depth.get()
+        }
+
+        fn depth_manual(&self) -> usize {
             stack_safe::trampoline(manual::DepthGen::init)(self)
         }
 
-        pub fn sum_recursive(&self) -> i64 {
+        fn depth_stacker(&self) -> usize {
+            stacker::maybe_grow(RED_ZONE, STACK_SIZE, || {
+                let mut max_child_depth = 0;
+                for child in &self.children {
+                    max_child_depth = max(max_child_depth, child.depth_stacker());
+                }
+                max_child_depth + 1
+            })
+        }
+
+        fn depth_loop(&self) -> usize {
+            let mut max_depth = 1;
+            let mut forest = self.children.iter();
+            let mut stack = Vec::new();
+
+            loop {
+                if let Some(tree) = forest.next() {
+                    stack.push(forest);
+                    max_depth = max(max_depth, stack.len() + 1);
+                    forest = tree.children.iter();
+                } else if let Some(top) = stack.pop() {
+                    forest = top;
+                } else {
+                    break max_depth;
+                }
+            }
+        }
+
+        fn sum_recursive(&self) -> i64 {
             let mut result = self.value;
             for child in &self.children {
                 result += child.sum_recursive();
@@ -65,7 +101,7 @@ mod tree {
             result
         }
 
-        pub fn sum_stack_safe(&self) -> i64 {
+        fn sum_stack_safe(&self) -> i64 {
             stack_safe::trampoline(|tree: &Self| {
                 move |_: i64| {
                     let mut result = tree.value;
@@ -77,11 +113,21 @@ mod tree {
             })(self)
         }
 
-        pub fn sum_manual(&self) -> i64 {
+        fn sum_manual(&self) -> i64 {
             stack_safe::trampoline(manual::SumGen::init)(self)
         }
 
-        pub fn sum_loop(&self) -> i64 {
+        fn sum_stacker(&self) -> i64 {
+            stacker::maybe_grow(RED_ZONE, STACK_SIZE, || {
+                let mut result = self.value;
+                for child in &self.children {
+                    result += child.sum_stacker();
+                }
+                result
+            })
+        }
+
+        fn sum_loop(&self) -> i64 {
             let mut sum = self.value;
             let mut forest = self.children.iter();
             let mut stack = Vec::new();
@@ -224,40 +270,6 @@ mod tree {
             }
         }
     }
-
-    pub mod examples {
-        use super::*;
-
-        pub fn simple() -> (Tree, usize, i64) {
-            let mut v0 = Tree::new(0);
-            let v1 = Tree::new(1);
-            let mut v2 = Tree::new(2);
-            let v3 = Tree::new(3);
-            v2.children = vec![v3];
-            v0.children = vec![v1, v2];
-            (v0, 3, 6)
-        }
-
-        pub fn path(n: usize) -> (Tree, usize, i64) {
-            let mut tree = Tree::new(n as i64 - 1);
-            for k in (0..(n - 1)).rev() {
-                let mut parent = Tree::new(k as i64);
-                parent.children.push(tree);
-                tree = parent;
-            }
-            (tree, n, (n * (n - 1) / 2) as i64)
-        }
-
-        pub fn binary(n: usize) -> (Tree, usize, i64) {
-            if n <= 1 {
-                (Tree::new(1), 1, 1)
-            } else {
-                let mut tree = Tree::new(1);
-                tree.children = vec![binary(n - 1).0, binary(n - 1).0];
-                (tree, n, 2i64.pow(n as u32) - 1)
-            }
-        }
-    }
 }
 
 fn bench_tree_depth(c: &mut Criterion) {
@@ -283,10 +295,19 @@ fn bench_tree_depth(c: &mut Criterion) {
             stack_safe::with_stack_size(1024, move || tree_f(size).0.depth_stack_safe()).unwrap(),
             tree_depth,
         );
+        assert_eq!(
+            stack_safe::with_stack_size(1024, move || tree_f(size).0.depth_visit()).unwrap(),
+            tree_depth,
+        );
         assert_eq!(
             stack_safe::with_stack_size(1024, move || tree_f(size).0.depth_manual()).unwrap(),
             tree_depth,
         );
+        assert_eq!(
+            stack_safe::with_stack_size(1024, move || tree_f(size).0.depth_loop()).unwrap(),
+            tree_depth,
+        );
+        assert_eq!(tree.depth_stacker(), tree_depth);
 
         group.bench_with_input(BenchmarkId::new("recursive", &label), &tree, |b, tree| {
             b.iter(|| {
@@ -298,11 +319,26 @@ fn bench_tree_depth(c: &mut Criterion) {
                 assert_eq!(tree.depth_stack_safe(), tree_depth);
             })
         });
+        group.bench_with_input(BenchmarkId::new("visit", &label), &tree, |b, tree| {
+            b.iter(|| {
+                assert_eq!(tree.depth_visit(), tree_depth);
+            })
+        });
         group.bench_with_input(BenchmarkId::new("manual", &label), &tree, |b, tree| {
             b.iter(|| {
                 assert_eq!(tree.depth_manual(), tree_depth);
             })
         });
+        group.bench_with_input(BenchmarkId::new("stacker", &label), &tree, |b, tree| {
+            b.iter(|| {
+                assert_eq!(tree.depth_stacker(), tree_depth);
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("loop", &label), &tree, |b, tree| {
+            b.iter(|| {
+                assert_eq!(tree.depth_loop(), tree_depth);
+            })
+        });
     }
     group.finish();
 }
@@ -338,6 +374,7 @@ fn bench_tree_sum(c: &mut Criterion) {
             stack_safe::with_stack_size(1024, move || tree_f(size).0.sum_loop()).unwrap(),
             tree_sum,
         );
+        assert_eq!(tree.sum_stacker(), tree_sum);
 
         group.bench_with_input(BenchmarkId::new("recursive", &label), &tree, |b, tree| {
             b.iter(|| {
@@ -354,6 +391,11 @@ fn bench_tree_sum(c: &mut Criterion) {
                 assert_eq!(tree.sum_manual(), tree_sum);
             })
         });
+        group.bench_with_input(BenchmarkId::new("stacker", &label), &tree, |b, tree| {
+            b.iter(|| {
+                assert_eq!(tree.sum_stacker(), tree_sum);
+            })
+        });
         group.bench_with_input(BenchmarkId::new("loop", &label), &tree, |b, tree| {
             b.iter(|| {
                 assert_eq!(tree.sum_loop(), tree_sum);
@@ -0,0 +1,182 @@
+#![feature(destructuring_assignment, generators, generator_trait)]
+// Walks a directory tree with `trampoline_mut`, following symlinks. Every
+// directory is walked once. Revisiting a directory on the current path is
+// reported as a symlink cycle, revisiting a finished one is not.
+//
+// Usage: `cargo run --example fs_walk -- [DIR]`. Without a directory, a
+// deeply nested tree with a symlink cycle and a shared directory is created
+// in the temp directory.
+use stack_safe::{trampoline_mut, with_stack_size};
+use std::collections::HashSet;
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+type DirId = (u64, u64);
+
+#[cfg(unix)]
+fn dir_id(_path: &Path, meta: &Metadata) -> io::Result<DirId> {
+    use std::os::unix::fs::MetadataExt;
+    Ok((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+#[cfg(not(unix))]
+fn dir_id(path: &Path, _meta: &Metadata) -> io::Result<DirId> {
+    fs::canonicalize(path)
+}
+
+#[derive(Default)]
+struct Stats {
+    /// The directories that have been walked completely.
+    visited: HashSet<DirId>,
+    /// The directories from the root to the current one.
+    on_path: HashSet<DirId>,
+    bytes: u64,
+    max_depth: usize,
+    cycles: u64,
+    errors: Vec<(PathBuf, io::Error)>,
+}
+
+/// Returns the number of files below `root`.
+#[allow(clippy::needless_lifetimes)]
+fn walk<'a>(root: PathBuf, stats: &'a mut Stats) -> u64 {
+    let gen = |(path, depth): (PathBuf, usize)| {
+        move |(_, mut stats): (u64, &'a mut Stats)| {
+            stats.max_depth = stats.max_depth.max(depth);
+            let meta = match fs::metadata(&path) {
+                Ok(meta) => meta,
+                Err(err) => {
+                    stats.errors.push((path, err));
+                    return (0, stats);
+                }
+            };
+            if !meta.is_dir() {
+                stats.bytes += meta.len();
+                return (1, stats);
+            }
+            let id = match dir_id(&path, &meta) {
+                Ok(id) => id,
+                Err(err) => {
+                    stats.errors.push((path, err));
+                    return (0, stats);
+                }
+            };
+            if stats.on_path.contains(&id) {
+                stats.cycles += 1;
+                return (0, stats);
+            }
+            if stats.visited.contains(&id) {
+                return (0, stats);
+            }
+            // Collect the entries first, so that suspended frames do not hold
+            // on to a file descriptor each.
+            let entries: Vec<_> = match fs::read_dir(&path) {
+                Ok(entries) => entries
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect(),
+                Err(err) => {
+                    stats.errors.push((path, err));
+                    return (0, stats);
+                }
+            };
+
+            stats.on_path.insert(id.to_owned());
+            let mut files = 0;
+            for entry in entries {
+                match entry {
+                    Ok(child) => {
+                        let child_files;
+                        (child_files, stats) = yield ((child, depth + 1), stats);
+                        files += child_files;
+                    }
+                    Err(err) => stats.errors.push((path.clone(), err)),
+                }
+            }
+            stats.on_path.remove(&id);
+            stats.visited.insert(id);
+            (files, stats)
+        }
+    };
+    trampoline_mut(gen)((root, 0), stats)
+}
+
+#[cfg(unix)]
+const DEMO_DEPTH: usize = 1000;
+
+#[cfg(unix)]
+fn make_demo() -> io::Result<PathBuf> {
+    let root = std::env::temp_dir().join(format!("fs_walk-{}", std::process::id()));
+    let mut dir = root.clone();
+    for _ in 0..DEMO_DEPTH {
+        dir.push("d");
+    }
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("leaf.txt"), "deep")?;
+    std::os::unix::fs::symlink(&root, dir.join("back"))?;
+    // A second way into a directory, which is not a cycle.
+    fs::create_dir(root.join("shared"))?;
+    std::os::unix::fs::symlink(root.join("shared"), root.join("alias"))?;
+    Ok(root)
+}
+
+#[cfg(not(unix))]
+fn make_demo() -> io::Result<PathBuf> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "the demo tree needs symlinks, please pass a directory",
+    ))
+}
+
+/// Removes the demo tree level by level. `fs::remove_dir_all` keeps a file
+/// descriptor open per level.
+#[cfg(unix)]
+fn remove_demo(root: &Path) -> io::Result<()> {
+    fs::remove_file(root.join("alias"))?;
+    fs::remove_dir(root.join("shared"))?;
+    let mut dir = root.to_path_buf();
+    for _ in 0..DEMO_DEPTH {
+        dir.push("d");
+    }
+    fs::remove_file(dir.join("leaf.txt"))?;
+    fs::remove_file(dir.join("back"))?;
+    while dir != root {
+        fs::remove_dir(&dir)?;
+        dir.pop();
+    }
+    fs::remove_dir(root)
+}
+
+#[cfg(not(unix))]
+fn remove_demo(_root: &Path) -> io::Result<()> {
+    unreachable!("there is no demo tree without symlinks")
+}
+
+fn main() -> io::Result<()> {
+    let (root, is_demo) = match std::env::args_os().nth(1) {
+        Some(root) => (PathBuf::from(root), false),
+        None => (make_demo()?, true),
+    };
+
+    let mut stats = Stats::default();
+    let files = with_stack_size(64 * 1024, || walk(root.clone(), &mut stats)).unwrap();
+    println!(
+        "{} files, {} bytes, depth {}, {} symlink cycles, {} errors",
+        files,
+        stats.bytes,
+        stats.max_depth,
+        stats.cycles,
+        stats.errors.len()
+    );
+    for (path, err) in &stats.errors {
+        eprintln!("{}: {}", path.display(), err);
+    }
+
+    if is_demo {
+        remove_demo(&root)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,209 @@
+#![feature(generators, generator_trait, generator_clone)]
+// A backtracking regex matcher on top of `stack_safe::search`. Both the
+// parser and the matcher keep their stacks on the heap, so patterns with
+// thousands of nested groups like `(a(a(a...)))` and long inputs for
+// patterns like `(a|a)*` cannot overflow the native stack. The latter still
+// take exponential time, as with any backtracking matcher.
+//
+// Supported syntax: literals, `.`, `|`, `*`, `+`, `?` and groups.
+//
+// Usage: `cargo run --example regex --features regex-example -- [PATTERN TEXT]`.
+// Without arguments, a few pathological patterns are matched.
+use stack_safe::search::{solutions, Resume, Step};
+use stack_safe::with_stack_size;
+
+#[derive(Clone, Copy, Debug)]
+enum Node {
+    Empty,
+    Char(u8),
+    Any,
+    /// Matches only at the end of the text.
+    End,
+    Concat(usize, usize),
+    Alt(usize, usize),
+    Star(usize),
+    Opt(usize),
+}
+
+/// A parsed pattern. The nodes refer to each other by index, so dropping a
+/// deeply nested pattern does not recurse either.
+struct Regex {
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+/// A group whose closing parenthesis has not been parsed yet.
+#[derive(Default)]
+struct Group {
+    alts: Vec<usize>,
+    seq: Vec<usize>,
+}
+
+impl Regex {
+    fn parse(pattern: &str) -> Result<Self, String> {
+        let mut nodes = Vec::new();
+        let mut add = |node| {
+            nodes.push(node);
+            nodes.len() - 1
+        };
+        let mut groups = vec![Group::default()];
+        for (index, byte) in pattern.bytes().enumerate() {
+            let group = groups.last_mut().unwrap();
+            match byte {
+                b'(' => groups.push(Group::default()),
+                b')' => {
+                    if groups.len() == 1 {
+                        return Err(format!("unmatched `)` at {}", index));
+                    }
+                    let group = groups.pop().unwrap();
+                    let node = finish(group, &mut add);
+                    groups.last_mut().unwrap().seq.push(node);
+                }
+                b'|' => {
+                    let seq = std::mem::take(&mut group.seq);
+                    let node = concat(seq, &mut add);
+                    group.alts.push(node);
+                }
+                b'*' | b'+' | b'?' => {
+                    let inner = group
+                        .seq
+                        .pop()
+                        .ok_or_else(|| format!("nothing to repeat at {}", index))?;
+                    let node = match byte {
+                        b'*' => add(Node::Star(inner)),
+                        b'+' => {
+                            let star = add(Node::Star(inner));
+                            add(Node::Concat(inner, star))
+                        }
+                        _ => add(Node::Opt(inner)),
+                    };
+                    group.seq.push(node);
+                }
+                b'.' => group.seq.push(add(Node::Any)),
+                byte => group.seq.push(add(Node::Char(byte))),
+            }
+        }
+        if groups.len() > 1 {
+            return Err("unmatched `(`".to_string());
+        }
+        let body = finish(groups.pop().unwrap(), &mut add);
+        let end = add(Node::End);
+        let root = add(Node::Concat(body, end));
+        Ok(Self { nodes, root })
+    }
+
+    /// Returns whether the pattern matches all of `text`.
+    fn is_match(&self, text: &[u8]) -> bool {
+        let nodes = &self.nodes;
+        let mut matches = solutions(
+            |(node, pos): (usize, usize)| {
+                move |_: Resume<usize>| match nodes[node] {
+                    Node::Empty => pos,
+                    Node::Char(expected) => {
+                        if text.get(pos) != Some(&expected) {
+                            yield Step::Fail;
+                        }
+                        pos + 1
+                    }
+                    Node::Any => {
+                        if pos >= text.len() {
+                            yield Step::Fail;
+                        }
+                        pos + 1
+                    }
+                    Node::End => {
+                        if pos != text.len() {
+                            yield Step::Fail;
+                        }
+                        pos
+                    }
+                    Node::Concat(first, second) => {
+                        let mid = (yield Step::Call((first, pos))).into_solution();
+                        (yield Step::Call((second, mid))).into_solution()
+                    }
+                    Node::Alt(left, right) => {
+                        let choice = (yield Step::Choose(2)).into_choice();
+                        let alt = if choice == 0 { left } else { right };
+                        (yield Step::Call((alt, pos))).into_solution()
+                    }
+                    Node::Star(inner) => {
+                        // Greedy: try one more repetition first.
+                        if (yield Step::Choose(2)).into_choice() == 1 {
+                            return pos;
+                        }
+                        let mid = (yield Step::Call((inner, pos))).into_solution();
+                        if mid == pos {
+                            // Empty repetitions would loop forever.
+                            yield Step::Fail;
+                        }
+                        (yield Step::Call((node, mid))).into_solution()
+                    }
+                    Node::Opt(inner) => {
+                        if (yield Step::Choose(2)).into_choice() == 1 {
+                            return pos;
+                        }
+                        (yield Step::Call((inner, pos))).into_solution()
+                    }
+                }
+            },
+            (self.root, 0),
+        );
+        matches.next().is_some()
+    }
+}
+
+fn concat(seq: Vec<usize>, add: &mut impl FnMut(Node) -> usize) -> usize {
+    seq.into_iter()
+        .rev()
+        .reduce(|rest, first| add(Node::Concat(first, rest)))
+        .unwrap_or_else(|| add(Node::Empty))
+}
+
+fn finish(group: Group, add: &mut impl FnMut(Node) -> usize) -> usize {
+    let last = concat(group.seq, add);
+    group
+        .alts
+        .into_iter()
+        .rev()
+        .fold(last, |rest, alt| add(Node::Alt(alt, rest)))
+}
+
+fn run(pattern: &str, text: &str) {
+    match Regex::parse(pattern) {
+        Ok(regex) => {
+            let shown: String = pattern.chars().take(40).collect();
+            let ellipsis = if shown.len() < pattern.len() {
+                "..."
+            } else {
+                ""
+            };
+            println!(
+                "{}{} on {} bytes: {}",
+                shown,
+                ellipsis,
+                text.len(),
+                regex.is_match(text.as_bytes())
+            );
+        }
+        Err(err) => eprintln!("invalid pattern: {}", err),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // A small native stack shows that neither parsing nor matching recurse.
+    let result = with_stack_size(64 << 10, move || match args.as_slice() {
+        [pattern, text] => run(pattern, text),
+        [] => {
+            let depth = 10_000;
+            let nested = format!("{}{}", "(a".repeat(depth), ")".repeat(depth));
+            run(&nested, &"a".repeat(depth));
+            run(&nested, &"a".repeat(depth - 1));
+            run("(a|a)*b", &"a".repeat(18));
+            run("(a*)*b", &"a".repeat(18));
+            run("(ab|a)+c?", &"ab".repeat(1000));
+        }
+        _ => eprintln!("usage: regex [PATTERN TEXT]"),
+    });
+    result.unwrap_or_else(|err| err.resume_unwind());
+}
@@ -0,0 +1,644 @@
+//! The expansion of `#[frame]`, which turns a frame function written with
+//! `yield` into an explicit state machine.
+use std::collections::HashSet;
+
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    AttributeArgs, Block, Expr, FnArg, GenericArgument, GenericParam, ItemFn, Meta, NestedMeta,
+    Pat, PatIdent, PathArguments, ReturnType, Stmt, Type,
+};
+
+pub(crate) fn expand(args: AttributeArgs, item: ItemFn) -> syn::Result<TokenStream> {
+    let mut dump = false;
+    for arg in args {
+        match arg {
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("dump") => dump = true,
+            arg => return Err(syn::Error::new_spanned(arg, "unknown argument")),
+        }
+    }
+
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "async functions are not supported",
+        ));
+    }
+    let (arg_ty, res_ty) = frame_types(&sig.output)?;
+
+    let mut params = Vec::new();
+    for input in &sig.inputs {
+        match input {
+            FnArg::Typed(typed) => match &*typed.pat {
+                Pat::Ident(PatIdent {
+                    by_ref: None,
+                    subpat: None,
+                    ident,
+                    ..
+                }) => params.push(Var {
+                    name: ident.clone(),
+                    ty: Some((*typed.ty).clone()),
+                }),
+                pat => {
+                    return Err(syn::Error::new_spanned(
+                        pat,
+                        "only plain identifiers are supported as arguments",
+                    ))
+                }
+            },
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new_spanned(
+                    receiver,
+                    "methods are not supported",
+                ))
+            }
+        }
+    }
+
+    let body = parse_block(*block, &res_ty)?;
+    let mut machine = Machine::default();
+    let start = machine.body(&body.steps, &body.tail, params.clone())?;
+
+    let name = &sig.ident;
+    let enum_name = format_ident!("{}Frame", camel_case(&name.to_string()));
+    let generics = &sig.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut unpin = generics.clone();
+    unpin
+        .make_where_clause()
+        .predicates
+        .push(syn::parse_quote!(Self: ::std::marker::Unpin));
+    let impl_where = &unpin.where_clause;
+
+    let param_names: Vec<_> = params.iter().map(|var| &var.name).collect();
+    let param_tys: Vec<_> = params.iter().map(|var| var.ty.as_ref().unwrap()).collect();
+    let states = machine.states.into_iter().map(Option::unwrap);
+    let (variants, arms): (Vec<_>, Vec<_>) = states
+        .map(|state| {
+            let State { name, fields, body } = state;
+            let names: Vec<_> = fields.iter().map(|(name, _)| name).collect();
+            let tys: Vec<_> = fields.iter().map(|(_, ty)| ty).collect();
+            (
+                quote! { #name { #(#names: #tys),* } },
+                quote! { Self::#name { #(mut #names),* } => #body },
+            )
+        })
+        .unzip();
+
+    let markers: Vec<_> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Lifetime(def) => {
+                let lifetime = &def.lifetime;
+                Some(quote! { &#lifetime () })
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                Some(quote! { #ident })
+            }
+            GenericParam::Const(_) => None,
+        })
+        .collect();
+    let (marker_variant, marker_arm) = if markers.is_empty() {
+        (quote! {}, quote! {})
+    } else {
+        (
+            quote! {
+                #[doc(hidden)]
+                __Marker(::std::marker::PhantomData<(#(#markers,)*)>),
+            },
+            quote! { Self::__Marker(_) => ::std::unreachable!(), },
+        )
+    };
+
+    let doc = format!("The frames of `{}`.", name);
+    let inputs = &sig.inputs;
+    let tokens = quote! {
+        #(#attrs)*
+        #vis fn #name #generics (#inputs) -> #enum_name #ty_generics #where_clause {
+            #enum_name::Start { #(#param_names),* }
+        }
+
+        #[doc = #doc]
+        #vis enum #enum_name #generics #where_clause {
+            Start { #(#param_names: #param_tys),* },
+            #(#variants,)*
+            Done,
+            #marker_variant
+        }
+
+        impl #impl_generics ::std::ops::Generator<#res_ty> for #enum_name #ty_generics #impl_where {
+            type Yield = #arg_ty;
+            type Return = #res_ty;
+
+            #[allow(unused_mut)]
+            fn resume(
+                self: ::std::pin::Pin<&mut Self>,
+                __res: #res_ty,
+            ) -> ::std::ops::GeneratorState<#arg_ty, #res_ty> {
+                let __this = ::std::pin::Pin::get_mut(self);
+                match ::std::mem::replace(__this, Self::Done) {
+                    Self::Start { #(mut #param_names),* } => {
+                        let _ = __res;
+                        #start
+                    }
+                    #(#arms)*
+                    Self::Done => ::std::panic!("resumed a finished frame"),
+                    #marker_arm
+                }
+            }
+        }
+    };
+    if dump {
+        eprintln!("{}", tokens);
+    }
+    Ok(tokens)
+}
+
+/// Extracts `Arg` and `Res` from a return type `Frame<Arg, Res>`.
+fn frame_types(output: &ReturnType) -> syn::Result<(Type, Type)> {
+    let error = || syn::Error::new_spanned(output, "the return type must be `Frame<Arg, Res>`");
+    let path = match output {
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(path) if path.qself.is_none() => &path.path,
+            _ => return Err(error()),
+        },
+        ReturnType::Default => return Err(error()),
+    };
+    let segment = path.segments.last().filter(|_| path.segments.len() == 1);
+    let args = match segment {
+        Some(segment) if segment.ident == "Frame" => match &segment.arguments {
+            PathArguments::AngleBracketed(args) => &args.args,
+            _ => return Err(error()),
+        },
+        _ => return Err(error()),
+    };
+    let mut tys = args.iter().map(|arg| match arg {
+        GenericArgument::Type(ty) => Ok(ty.clone()),
+        _ => Err(error()),
+    });
+    match (tys.next(), tys.next(), tys.next()) {
+        (Some(arg), Some(res), None) => Ok((arg?, res?)),
+        _ => Err(error()),
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars))
+                .into_iter()
+                .flatten()
+        })
+        .collect()
+}
+
+/// A frame body: statements that either run to the next `yield` or are
+/// plain Rust, followed by the tail that produces the result.
+struct Body {
+    steps: Vec<Step>,
+    tail: Tail,
+}
+
+enum Step {
+    Plain(Stmt),
+    /// `let pat: ty = yield arg;` or, without a binding, `yield arg;`.
+    Yield {
+        binding: Option<Box<(PatIdent, Type)>>,
+        arg: Expr,
+    },
+}
+
+enum Tail {
+    Expr(Option<Expr>),
+    If {
+        cond: Expr,
+        then: Box<Body>,
+        otherwise: Box<Body>,
+    },
+    Match {
+        scrutinee: Expr,
+        arms: Vec<Arm>,
+    },
+}
+
+struct Arm {
+    pat: Pat,
+    guard: Option<Expr>,
+    body: Body,
+}
+
+const UNSUPPORTED_YIELD: &str =
+    "`yield` is only supported as `let x: T = yield e;`, as `yield e;` or in tail position";
+
+fn parse_block(block: Block, res_ty: &Type) -> syn::Result<Body> {
+    let mut stmts = block.stmts;
+    let tail = match stmts.last() {
+        Some(Stmt::Expr(_)) => match stmts.pop() {
+            Some(Stmt::Expr(expr)) => Some(expr),
+            _ => unreachable!(),
+        },
+        _ => None,
+    };
+    let mut steps = Vec::new();
+    for stmt in stmts {
+        steps.push(parse_stmt(stmt)?);
+    }
+    let tail = match tail {
+        None => Tail::Expr(None),
+        Some(expr) => parse_tail(expr, &mut steps, res_ty)?,
+    };
+    Ok(Body { steps, tail })
+}
+
+fn parse_stmt(stmt: Stmt) -> syn::Result<Step> {
+    match stmt {
+        Stmt::Local(local) => match local.init {
+            Some((_, init)) if matches!(*init, Expr::Yield(_)) => {
+                let arg = yield_arg(*init)?;
+                let binding = match local.pat {
+                    Pat::Wild(_) => None,
+                    Pat::Type(typed) => match *typed.pat {
+                        Pat::Wild(_) => None,
+                        Pat::Ident(ident) if ident.subpat.is_none() => {
+                            Some(Box::new((ident, *typed.ty)))
+                        }
+                        pat => return Err(binding_error(pat)),
+                    },
+                    pat => return Err(binding_error(pat)),
+                };
+                Ok(Step::Yield { binding, arg })
+            }
+            init => plain(Stmt::Local(syn::Local { init, ..local })),
+        },
+        Stmt::Semi(Expr::Yield(expr), _) => Ok(Step::Yield {
+            binding: None,
+            arg: yield_arg(Expr::Yield(expr))?,
+        }),
+        stmt => plain(stmt),
+    }
+}
+
+fn plain(stmt: Stmt) -> syn::Result<Step> {
+    match find_yield(stmt.to_token_stream()) {
+        Some(span) => Err(syn::Error::new(span, UNSUPPORTED_YIELD)),
+        None => Ok(Step::Plain(stmt)),
+    }
+}
+
+fn binding_error(pat: Pat) -> syn::Error {
+    syn::Error::new_spanned(
+        pat,
+        "bind the result of `yield` to a variable with an explicit type, e.g., `let x: T = yield e;`",
+    )
+}
+
+fn yield_arg(expr: Expr) -> syn::Result<Expr> {
+    match expr {
+        Expr::Yield(expr) => match expr.expr {
+            Some(arg) => no_yield(*arg),
+            None => Err(syn::Error::new_spanned(
+                expr.yield_token,
+                "`yield` needs an argument",
+            )),
+        },
+        _ => unreachable!(),
+    }
+}
+
+fn no_yield(expr: Expr) -> syn::Result<Expr> {
+    match find_yield(expr.to_token_stream()) {
+        Some(span) => Err(syn::Error::new(span, UNSUPPORTED_YIELD)),
+        None => Ok(expr),
+    }
+}
+
+fn parse_tail(expr: Expr, steps: &mut Vec<Step>, res_ty: &Type) -> syn::Result<Tail> {
+    if find_yield(expr.to_token_stream()).is_none() {
+        return Ok(Tail::Expr(Some(expr)));
+    }
+    match expr {
+        Expr::Yield(_) => {
+            let ret = format_ident!("__ret");
+            steps.push(Step::Yield {
+                binding: Some(Box::new((
+                    PatIdent {
+                        attrs: Vec::new(),
+                        by_ref: None,
+                        mutability: None,
+                        ident: ret.clone(),
+                        subpat: None,
+                    },
+                    res_ty.clone(),
+                ))),
+                arg: yield_arg(expr)?,
+            });
+            Ok(Tail::Expr(Some(syn::parse_quote!(#ret))))
+        }
+        Expr::Block(expr) if expr.label.is_none() => {
+            let body = parse_block(expr.block, res_ty)?;
+            steps.extend(body.steps);
+            Ok(body.tail)
+        }
+        Expr::If(expr) => {
+            let otherwise = match expr.else_branch {
+                Some((_, otherwise)) => branch(*otherwise, res_ty)?,
+                None => Body {
+                    steps: Vec::new(),
+                    tail: Tail::Expr(None),
+                },
+            };
+            Ok(Tail::If {
+                cond: no_yield(*expr.cond)?,
+                then: Box::new(parse_block(expr.then_branch, res_ty)?),
+                otherwise: Box::new(otherwise),
+            })
+        }
+        Expr::Match(expr) => {
+            let mut arms = Vec::new();
+            for arm in expr.arms {
+                arms.push(Arm {
+                    pat: arm.pat,
+                    guard: arm.guard.map(|(_, guard)| no_yield(*guard)).transpose()?,
+                    body: branch(*arm.body, res_ty)?,
+                });
+            }
+            Ok(Tail::Match {
+                scrutinee: no_yield(*expr.expr)?,
+                arms,
+            })
+        }
+        expr => Err(syn::Error::new(
+            find_yield(expr.to_token_stream()).unwrap(),
+            UNSUPPORTED_YIELD,
+        )),
+    }
+}
+
+/// Parses the body of a match arm or an `else` branch.
+fn branch(expr: Expr, res_ty: &Type) -> syn::Result<Body> {
+    let mut steps = Vec::new();
+    let tail = parse_tail(expr, &mut steps, res_ty)?;
+    Ok(Body { steps, tail })
+}
+
+fn find_yield(tokens: TokenStream) -> Option<Span> {
+    tokens.into_iter().find_map(|token| match token {
+        TokenTree::Ident(ident) if ident == "yield" => Some(ident.span()),
+        TokenTree::Group(group) => find_yield(group.stream()),
+        _ => None,
+    })
+}
+
+fn mentions(tokens: TokenStream, names: &mut HashSet<String>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                names.insert(ident.to_string());
+            }
+            TokenTree::Group(group) => mentions(group.stream(), names),
+            _ => {}
+        }
+    }
+}
+
+fn body_mentions(steps: &[Step], tail: &Tail, names: &mut HashSet<String>) {
+    for step in steps {
+        match step {
+            Step::Plain(stmt) => mentions(stmt.to_token_stream(), names),
+            Step::Yield { arg, .. } => mentions(arg.to_token_stream(), names),
+        }
+    }
+    match tail {
+        Tail::Expr(expr) => mentions(expr.to_token_stream(), names),
+        Tail::If {
+            cond,
+            then,
+            otherwise,
+        } => {
+            mentions(cond.to_token_stream(), names);
+            body_mentions(&then.steps, &then.tail, names);
+            body_mentions(&otherwise.steps, &otherwise.tail, names);
+        }
+        Tail::Match { scrutinee, arms } => {
+            mentions(scrutinee.to_token_stream(), names);
+            for arm in arms {
+                mentions(arm.guard.to_token_stream(), names);
+                body_mentions(&arm.body.steps, &arm.body.tail, names);
+            }
+        }
+    }
+}
+
+/// A variable in scope. Variables bound by patterns have no known type and
+/// hence cannot be stored across a `yield`.
+#[derive(Clone)]
+struct Var {
+    name: Ident,
+    ty: Option<Type>,
+}
+
+fn bind(scope: &mut Vec<Var>, var: Var) {
+    scope.retain(|other| other.name != var.name);
+    scope.push(var);
+}
+
+fn bind_pat(scope: &mut Vec<Var>, pat: &Pat) {
+    match pat {
+        Pat::Type(typed) => match &*typed.pat {
+            Pat::Ident(ident) if ident.subpat.is_none() => bind(
+                scope,
+                Var {
+                    name: ident.ident.clone(),
+                    ty: Some((*typed.ty).clone()),
+                },
+            ),
+            pat => bind_pat(scope, pat),
+        },
+        Pat::Ident(ident) => {
+            // Bare unit variants like `None` parse as identifiers, too.
+            if !ident.ident.to_string().starts_with(char::is_uppercase) {
+                bind(
+                    scope,
+                    Var {
+                        name: ident.ident.clone(),
+                        ty: None,
+                    },
+                );
+            }
+            if let Some((_, pat)) = &ident.subpat {
+                bind_pat(scope, pat);
+            }
+        }
+        Pat::Box(pat) => bind_pat(scope, &pat.pat),
+        Pat::Or(pat) => pat.cases.iter().for_each(|pat| bind_pat(scope, pat)),
+        Pat::Reference(pat) => bind_pat(scope, &pat.pat),
+        Pat::Slice(pat) => pat.elems.iter().for_each(|pat| bind_pat(scope, pat)),
+        Pat::Struct(pat) => pat
+            .fields
+            .iter()
+            .for_each(|field| bind_pat(scope, &field.pat)),
+        Pat::Tuple(pat) => pat.elems.iter().for_each(|pat| bind_pat(scope, pat)),
+        Pat::TupleStruct(pat) => pat.pat.elems.iter().for_each(|pat| bind_pat(scope, pat)),
+        _ => {}
+    }
+}
+
+struct State {
+    name: Ident,
+    fields: Vec<(Ident, Type)>,
+    body: TokenStream,
+}
+
+/// The states after each `yield`, in order of appearance.
+#[derive(Default)]
+struct Machine {
+    states: Vec<Option<State>>,
+}
+
+impl Machine {
+    /// Generates the code that runs `steps` and `tail` up to the next
+    /// `yield` and evaluates to the `GeneratorState`.
+    fn body(
+        &mut self,
+        steps: &[Step],
+        tail: &Tail,
+        mut scope: Vec<Var>,
+    ) -> syn::Result<TokenStream> {
+        let mut code = TokenStream::new();
+        for (index, step) in steps.iter().enumerate() {
+            let (binding, arg) = match step {
+                Step::Plain(stmt) => {
+                    stmt.to_tokens(&mut code);
+                    if let Stmt::Local(local) = stmt {
+                        bind_pat(&mut scope, &local.pat);
+                    }
+                    continue;
+                }
+                Step::Yield { binding, arg } => (binding, arg),
+            };
+            let rest = &steps[index + 1..];
+            let mut used = HashSet::new();
+            body_mentions(rest, tail, &mut used);
+            let mut fields = Vec::new();
+            for var in scope
+                .iter()
+                .filter(|var| used.contains(&var.name.to_string()))
+            {
+                match &var.ty {
+                    Some(ty) => fields.push((var.name.clone(), ty.clone())),
+                    None => {
+                        return Err(syn::Error::new(
+                            var.name.span(),
+                            format!(
+                                "`{0}` is used after a `yield`, but its type is unknown; \
+                                 rebind it with `let {0}: T = {0};`",
+                                var.name
+                            ),
+                        ))
+                    }
+                }
+            }
+
+            let state = format_ident!("Resume{}", self.states.len());
+            let slot = self.states.len();
+            self.states.push(None);
+            let mut inner: Vec<_> = fields
+                .iter()
+                .map(|(name, ty)| Var {
+                    name: name.clone(),
+                    ty: Some(ty.clone()),
+                })
+                .collect();
+            let resumed = match binding {
+                Some(binding) => {
+                    let (pat, ty) = &**binding;
+                    bind(
+                        &mut inner,
+                        Var {
+                            name: pat.ident.clone(),
+                            ty: Some(ty.clone()),
+                        },
+                    );
+                    quote! { let #pat: #ty = __res; }
+                }
+                None => quote! { let _ = __res; },
+            };
+            let rest = self.body(rest, tail, inner)?;
+            let names: Vec<_> = fields.iter().map(|(name, _)| name.clone()).collect();
+            self.states[slot] = Some(State {
+                name: state.clone(),
+                fields,
+                body: quote! {{ #resumed #rest }},
+            });
+            return Ok(quote! {{
+                #code
+                let __arg = #arg;
+                *__this = Self::#state { #(#names),* };
+                ::std::ops::GeneratorState::Yielded(__arg)
+            }});
+        }
+
+        let tail = match tail {
+            Tail::Expr(Some(expr)) => quote! { ::std::ops::GeneratorState::Complete(#expr) },
+            Tail::Expr(None) => quote! { ::std::ops::GeneratorState::Complete(()) },
+            Tail::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                let mut then_scope = scope.clone();
+                if let Expr::Let(cond) = cond {
+                    bind_pat(&mut then_scope, &cond.pat);
+                }
+                let then = self.body(&then.steps, &then.tail, then_scope)?;
+                let otherwise = self.body(&otherwise.steps, &otherwise.tail, scope)?;
+                quote! { if #cond #then else #otherwise }
+            }
+            Tail::Match { scrutinee, arms } => {
+                let mut cases = Vec::new();
+                for Arm { pat, guard, body } in arms {
+                    let mut arm_scope = scope.clone();
+                    bind_pat(&mut arm_scope, pat);
+                    let body = self.body(&body.steps, &body.tail, arm_scope)?;
+                    let guard = guard.as_ref().map(|guard| quote! { if #guard });
+                    cases.push(quote! { #pat #guard => #body, });
+                }
+                quote! { match #scrutinee { #(#cases)* } }
+            }
+        };
+        Ok(quote! {{ #code #tail }})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand;
+
+    #[test]
+    fn untyped_variables_are_rejected() {
+        let item = syn::parse_quote! {
+            fn sum(xs: &'static [u64]) -> Frame<&'static [u64], u64> {
+                match xs {
+                    [] => 0,
+                    [x, rest @ ..] => {
+                        let sum: u64 = yield rest;
+                        x + sum
+                    }
+                }
+            }
+        };
+        let err = expand(Vec::new(), item).unwrap_err();
+        assert!(err.to_string().starts_with("`x` is used after a `yield`"));
+    }
+}
@@ -0,0 +1,175 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, AttributeArgs, ItemFn, Lit, Meta, NestedMeta};
+
+mod frame;
+
+/// Runs the annotated function on a thread with a big stack.
+///
+/// ```ignore
+/// #[stack_safe::main(stack_size = "64MiB")]
+/// fn main() -> Result<(), Error> {
+///     ...
+/// }
+/// ```
+///
+/// The function must not take any arguments. Its result is returned from
+/// the wrapper, so exit codes work as usual. Panics are propagated to the
+/// calling thread.
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let item = parse_macro_input!(item as ItemFn);
+    match expand(args, item, quote! {}) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Expands a frame function into an explicit state machine, like the one
+/// `eval_trampolined_opt` in the `calc` benchmark uses.
+///
+/// ```ignore
+/// #[stack_safe::frame]
+/// fn triangular(n: u64) -> Frame<u64, u64> {
+///     if n == 0 {
+///         0
+///     } else {
+///         let res: u64 = yield n - 1;
+///         n + res
+///     }
+/// }
+/// ```
+///
+/// The function then returns a `TriangularFrame`, an enum with a variant per
+/// `yield` that holds the variables still needed after it, together with a
+/// `Generator<u64, Yield = u64, Return = u64>` impl. With `#[frame(dump)]`,
+/// the expansion is also printed during compilation as a starting point for
+/// hand-optimizing the frame.
+///
+/// Only a subset of Rust is supported: `yield` must appear as
+/// `let x: T = yield e;`, as `yield e;` or in tail position, possibly nested
+/// in `if` and `match`. Variables that are used after a `yield` need their
+/// types spelled out, so pattern bindings must be rebound with a typed `let`
+/// first. `return`, `?` and loops around a `yield` are not supported.
+#[proc_macro_attribute]
+pub fn frame(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let item = parse_macro_input!(item as ItemFn);
+    match frame::expand(args, item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Like `#[test]`, but runs the test on a thread with the given stack size.
+///
+/// ```ignore
+/// #[stack_safe::test(stack_size = "1KiB")]
+/// #[should_panic]
+/// fn overflows() {
+///     ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let item = parse_macro_input!(item as ItemFn);
+    match expand(args, item, quote! { #[::core::prelude::v1::test] }) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(
+    args: AttributeArgs,
+    item: ItemFn,
+    extra_attrs: proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut stack_size = None;
+    for arg in args {
+        match arg {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("stack_size") => {
+                stack_size = Some(match &nv.lit {
+                    Lit::Str(lit) => parse_size(&lit.value())
+                        .ok_or_else(|| syn::Error::new_spanned(lit, "invalid stack size"))?,
+                    Lit::Int(lit) => lit.base10_parse()?,
+                    lit => return Err(syn::Error::new_spanned(lit, "invalid stack size")),
+                });
+            }
+            arg => return Err(syn::Error::new_spanned(arg, "unknown argument")),
+        }
+    }
+    let stack_size = stack_size
+        .ok_or_else(|| syn::Error::new(Span::call_site(), "missing `stack_size` argument"))?;
+
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "async functions are not supported",
+        ));
+    }
+    if !sig.inputs.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.inputs,
+            "arguments are not supported",
+        ));
+    }
+    let name = sig.ident.to_string();
+    let output = &sig.output;
+    Ok(quote! {
+        #extra_attrs
+        #(#attrs)*
+        #vis #sig {
+            fn inner() #output #block
+            match ::stack_safe::BigStackThread::new()
+                .stack_size(#stack_size)
+                .name(#name)
+                .run(inner)
+            {
+                ::std::result::Result::Ok(res) => res,
+                ::std::result::Result::Err(err) => err.resume_unwind(),
+            }
+        }
+    })
+}
+
+/// Parses sizes like `1048576`, `512KiB`, `64 MiB` or `1G`. All units are
+/// binary.
+fn parse_size(input: &str) -> Option<usize> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '_')
+        .unwrap_or(input.len());
+    let number: usize = input[..split].replace('_', "").parse().ok()?;
+    let shift = match input[split..].trim() {
+        "" | "B" => 0,
+        "K" | "KiB" => 10,
+        "M" | "MiB" => 20,
+        "G" | "GiB" => 30,
+        _ => return None,
+    };
+    number.checked_mul(1 << shift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_size;
+
+    #[test]
+    fn sizes_are_parsed() {
+        assert_eq!(parse_size("1048576"), Some(1 << 20));
+        assert_eq!(parse_size("512KiB"), Some(512 << 10));
+        assert_eq!(parse_size("64 MiB"), Some(64 << 20));
+        assert_eq!(parse_size("1G"), Some(1 << 30));
+        assert_eq!(parse_size("64MB"), None);
+        assert_eq!(parse_size("MiB"), None);
+    }
+}
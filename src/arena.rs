@@ -0,0 +1,127 @@
+use std::slice;
+
+use crate::dom::Children;
+use crate::FlatNode;
+
+/// The index of a node in an `Arena`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Tree nodes stored in a `Vec` and referring to their children by `NodeId`.
+/// Dropping an arena never recurses, no matter how deep the trees in it are.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Arena<T> {
+    nodes: Vec<FlatNode<T>>,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self { nodes: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Adds a node with the given children, which must already be in the
+    /// arena.
+    pub fn alloc(&mut self, value: T, children: Vec<NodeId>) -> NodeId {
+        debug_assert!(children.iter().all(|child| child.0 < self.nodes.len()));
+        self.nodes.push(FlatNode {
+            value,
+            children: children.into_iter().map(NodeId::index).collect(),
+        });
+        NodeId(self.nodes.len() - 1)
+    }
+
+    /// The node added last, e.g., the root of a flattened tree.
+    pub fn last(&self) -> Option<NodeId> {
+        self.nodes.len().checked_sub(1).map(NodeId)
+    }
+
+    pub fn get(&self, id: NodeId) -> &T {
+        &self.nodes[id.0].value
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> &mut T {
+        &mut self.nodes[id.0].value
+    }
+
+    /// A handle to the node `id`, which implements `Children` and hence
+    /// works with the traversals in `dom`.
+    pub fn node(&self, id: NodeId) -> NodeRef<'_, T> {
+        NodeRef { arena: self, id }
+    }
+}
+
+/// The nodes produced by `flatten` form an arena, whose last node is the
+/// root of the flattened tree.
+impl<T> From<Vec<FlatNode<T>>> for Arena<T> {
+    fn from(nodes: Vec<FlatNode<T>>) -> Self {
+        Self { nodes }
+    }
+}
+
+impl<T> From<Arena<T>> for Vec<FlatNode<T>> {
+    fn from(arena: Arena<T>) -> Self {
+        arena.nodes
+    }
+}
+
+pub struct NodeRef<'a, T> {
+    arena: &'a Arena<T>,
+    id: NodeId,
+}
+
+impl<'a, T> Clone for NodeRef<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for NodeRef<'a, T> {}
+
+impl<'a, T> NodeRef<'a, T> {
+    pub fn id(self) -> NodeId {
+        self.id
+    }
+
+    pub fn value(self) -> &'a T {
+        self.arena.get(self.id)
+    }
+}
+
+pub struct NodeChildren<'a, T> {
+    arena: &'a Arena<T>,
+    ids: slice::Iter<'a, usize>,
+}
+
+impl<'a, T> Iterator for NodeChildren<'a, T> {
+    type Item = NodeRef<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = NodeId(*self.ids.next()?);
+        Some(self.arena.node(id))
+    }
+}
+
+impl<'a, T> Children for NodeRef<'a, T> {
+    type Iter = NodeChildren<'a, T>;
+
+    fn children(&self) -> Self::Iter {
+        NodeChildren {
+            arena: self.arena,
+            ids: self.arena.nodes[self.id.0].children.iter(),
+        }
+    }
+}
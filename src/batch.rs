@@ -0,0 +1,86 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+use crate::{drive, Cache};
+
+/// Evaluates the function `f` describes on all `roots` and returns the
+/// results in the same order. Unlike calling the function returned by
+/// `trampoline` once per root, all evaluations share one frame stack, which
+/// hence only grows once.
+pub fn recurse_batch<Arg, Res, Gen>(
+    f: impl Fn(Arg) -> Gen,
+    roots: impl IntoIterator<Item = Arg>,
+) -> Vec<Res>
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    let mut stack = Vec::new();
+    roots
+        .into_iter()
+        .map(|root| drive(&f, root, &mut stack))
+        .collect()
+}
+
+/// Like `recurse_batch`, but also shares `cache` between the evaluations,
+/// like `recurse_with_cache`, so results computed for one root are reused
+/// for all later ones.
+pub fn recurse_batch_with_cache<Arg, Res, Gen, C>(
+    f: impl Fn(Arg) -> Gen,
+    roots: impl IntoIterator<Item = Arg>,
+    cache: &mut C,
+) -> Vec<Res>
+where
+    Arg: Clone,
+    Res: Clone + Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+    C: Cache<Arg, Res> + ?Sized,
+{
+    let mut stack = Vec::new();
+    roots
+        .into_iter()
+        .map(|root| drive_cached(&mut stack, &f, root, cache))
+        .collect()
+}
+
+fn drive_cached<Arg, Res, Gen, C>(
+    stack: &mut Vec<(Gen, Arg)>,
+    f: &impl Fn(Arg) -> Gen,
+    arg: Arg,
+    cache: &mut C,
+) -> Res
+where
+    Arg: Clone,
+    Res: Clone + Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+    C: Cache<Arg, Res> + ?Sized,
+{
+    if let Some(res) = cache.get(&arg) {
+        return res;
+    }
+    let mut current = (f(arg.clone()), arg);
+    let mut res = Res::default();
+
+    loop {
+        match Pin::new(&mut current.0).resume(res) {
+            GeneratorState::Yielded(arg) => match cache.get(&arg) {
+                Some(cached) => res = cached,
+                None => {
+                    stack.push(current);
+                    current = (f(arg.clone()), arg);
+                    res = Res::default();
+                }
+            },
+            GeneratorState::Complete(real_res) => {
+                cache.insert(current.1, real_res.clone());
+                match stack.pop() {
+                    None => return real_res,
+                    Some(top) => {
+                        current = top;
+                        res = real_res;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+use crate::probe::take_overflow;
+use crate::{PanicPayload, StackSafeError};
+
+/// A builder for running a closure on a fresh thread with a big stack.
+///
+/// ```ignore
+/// let res = BigStackThread::new()
+///     .stack_size(64 * 1024 * 1024)
+///     .name("solver")
+///     .run(|| solve(&problem));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BigStackThread {
+    stack_size: Option<usize>,
+    name: Option<String>,
+    core: Option<usize>,
+    nice: Option<i32>,
+}
+
+impl BigStackThread {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.stack_size = Some(size);
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Pins the thread to the given CPU core. Only supported on Linux and
+    /// ignored elsewhere.
+    pub fn pin_to_core(mut self, core: usize) -> Self {
+        self.core = Some(core);
+        self
+    }
+
+    /// Sets the niceness of the thread. Only supported on Linux and ignored
+    /// elsewhere.
+    pub fn nice(mut self, nice: i32) -> Self {
+        self.nice = Some(nice);
+        self
+    }
+
+    /// Runs `f` on the configured thread and waits for its result. Unlike
+    /// `std::thread::spawn`, `f` may borrow from the current stack frame.
+    ///
+    /// Panics on the new thread, including failures to apply the core
+    /// pinning or niceness, result in `StackSafeError::FramePanicked`, except
+    /// for those raised by `stack_probe!`, which result in
+    /// `StackSafeError::OverflowDetected`.
+    pub fn run<T, F>(self, f: F) -> Result<T, StackSafeError>
+    where
+        T: Send,
+        F: FnOnce() -> T + Send,
+    {
+        let (builder, setup) = self.into_parts();
+        let result = unsafe {
+            builder.spawn_unchecked(move || {
+                setup();
+                // Only report probes that fail while `f` runs.
+                take_overflow();
+                panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| match take_overflow() {
+                    Some((depth, remaining)) => {
+                        StackSafeError::OverflowDetected { depth, remaining }
+                    }
+                    None => StackSafeError::FramePanicked(PanicPayload::new(payload)),
+                })
+            })
+        };
+        match result.map_err(StackSafeError::Spawn)?.join() {
+            Ok(result) => result,
+            Err(payload) => Err(StackSafeError::FramePanicked(PanicPayload::new(payload))),
+        }
+    }
+
+    /// Like `run`, but does not wait for `f` to finish. Since the thread may
+    /// outlive the current stack frame, `f` must be `'static`.
+    pub fn spawn<T, F>(self, f: F) -> Result<thread::JoinHandle<T>, StackSafeError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (builder, setup) = self.into_parts();
+        builder
+            .spawn(move || {
+                setup();
+                f()
+            })
+            .map_err(StackSafeError::Spawn)
+    }
+
+    /// Splits the configuration into the thread builder and the setup to run
+    /// on the new thread.
+    fn into_parts(self) -> (thread::Builder, impl FnOnce()) {
+        let mut builder = thread::Builder::new();
+        if let Some(size) = self.stack_size {
+            builder = builder.stack_size(size);
+        }
+        if let Some(name) = self.name {
+            builder = builder.name(name);
+        }
+        let (core, nice) = (self.core, self.nice);
+        let setup = move || {
+            if let Some(core) = core {
+                sys::pin_to_core(core);
+            }
+            if let Some(nice) = nice {
+                sys::set_nice(nice);
+            }
+        };
+        (builder, setup)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::io;
+
+    pub fn pin_to_core(core: usize) {
+        if core >= libc::CPU_SETSIZE as usize {
+            panic!("failed to pin thread to core {}: no such core", core);
+        }
+        // SAFETY: `set` is a plain bitmask owned by this function.
+        let res = unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            libc::CPU_SET(core, &mut set);
+            libc::sched_setaffinity(0, std::mem::size_of_val(&set), &set)
+        };
+        if res != 0 {
+            panic!(
+                "failed to pin thread to core {}: {}",
+                core,
+                io::Error::last_os_error()
+            );
+        }
+    }
+
+    pub fn set_nice(nice: i32) {
+        // SAFETY: On Linux, `setpriority` with `PRIO_PROCESS` and a thread
+        // id only affects that thread.
+        let res = unsafe {
+            let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+            libc::setpriority(libc::PRIO_PROCESS, tid, nice)
+        };
+        if res != 0 {
+            panic!(
+                "failed to set niceness to {}: {}",
+                nice,
+                io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    pub fn pin_to_core(_core: usize) {}
+
+    pub fn set_nice(_nice: i32) {}
+}
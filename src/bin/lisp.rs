@@ -0,0 +1,398 @@
+// A tiny Lisp interpreter whose `eval` is a single frame function. Function
+// bodies and the branches of `if` are evaluated with `Call::tail`, so loops
+// written as tail recursion run in constant frame-stack space. Non-tail
+// recursion, like `count` below, keeps its frames on the heap instead of
+// overflowing the native stack.
+//
+// Usage: `cargo run --bin lisp -- [FILE]`. Without a file, a few demo
+// definitions are evaluated. Every top-level form is printed with its value
+// and the maximal depth of the frame stack during its evaluation.
+#![feature(generators, generator_trait)]
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Generator;
+use std::rc::Rc;
+
+use stack_safe::{with_stack_size, Call, Observer, Recursion};
+
+const DEMO: &str = "
+(define (loop n acc) (if (= n 0) acc (loop (- n 1) (+ acc 1))))
+(loop 100000 0)
+(define (even? n) (if (= n 0) #t (odd? (- n 1))))
+(define (odd? n) (if (= n 0) #f (even? (- n 1))))
+(even? 100001)
+(define (count n) (if (= n 0) 0 (+ 1 (count (- n 1)))))
+(count 100000)
+(define (range n) (if (= n 0) (list) (cons n (range (- n 1)))))
+(car (cdr (range 10)))
+";
+
+#[derive(Clone, Default)]
+enum Value {
+    #[default]
+    Nil,
+    Num(i64),
+    Bool(bool),
+    Sym(Rc<str>),
+    List(Rc<[Value]>),
+    Lambda(Rc<Lambda>),
+    Builtin(&'static str, Builtin),
+    Error(Rc<str>),
+}
+
+impl Value {
+    fn error(message: impl Into<Rc<str>>) -> Self {
+        Self::Error(message.into())
+    }
+
+    fn is_truthy(&self) -> bool {
+        !matches!(self, Self::Bool(false) | Self::Nil)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nil => write!(f, "nil"),
+            Self::Num(n) => write!(f, "{}", n),
+            Self::Bool(b) => write!(f, "{}", if *b { "#t" } else { "#f" }),
+            Self::Sym(name) => write!(f, "{}", name),
+            Self::List(items) => {
+                write!(f, "(")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+            Self::Lambda(_) => write!(f, "<lambda>"),
+            Self::Builtin(name, _) => write!(f, "<builtin {}>", name),
+            Self::Error(message) => write!(f, "error: {}", message),
+        }
+    }
+}
+
+type Builtin = fn(&[Value]) -> Value;
+
+struct Lambda {
+    params: Vec<Rc<str>>,
+    body: Rc<[Value]>,
+    env: Rc<Env>,
+}
+
+#[derive(Default)]
+struct Env {
+    vars: RefCell<HashMap<Rc<str>, Value>>,
+    parent: Option<Rc<Env>>,
+}
+
+impl Env {
+    fn global() -> Rc<Self> {
+        let env = Self::default();
+        let builtins: [(&'static str, Builtin); 10] = [
+            ("+", |args| arithmetic(0, args, i64::checked_add)),
+            ("*", |args| arithmetic(1, args, i64::checked_mul)),
+            ("-", |args| match args {
+                [Value::Num(_)] => arithmetic(0, args, i64::checked_sub),
+                [Value::Num(first), rest @ ..] => arithmetic(*first, rest, i64::checked_sub),
+                _ => Value::error("`-` needs a number"),
+            }),
+            ("=", |args| compare(args, |a, b| a == b)),
+            ("<", |args| compare(args, |a, b| a < b)),
+            ("list", |args| Value::List(args.into())),
+            ("cons", |args| match args {
+                [head, Value::List(tail)] => {
+                    let mut items = Vec::with_capacity(tail.len() + 1);
+                    items.push(head.clone());
+                    items.extend(tail.iter().cloned());
+                    Value::List(items.into())
+                }
+                _ => Value::error("`cons` needs a value and a list"),
+            }),
+            ("car", |args| match args {
+                [Value::List(items)] if !items.is_empty() => items[0].clone(),
+                _ => Value::error("`car` needs a non-empty list"),
+            }),
+            ("cdr", |args| match args {
+                [Value::List(items)] if !items.is_empty() => Value::List(items[1..].into()),
+                _ => Value::error("`cdr` needs a non-empty list"),
+            }),
+            ("null?", |args| match args {
+                [Value::List(items)] => Value::Bool(items.is_empty()),
+                _ => Value::error("`null?` needs a list"),
+            }),
+        ];
+        for (name, builtin) in builtins {
+            env.define(name.into(), Value::Builtin(name, builtin));
+        }
+        Rc::new(env)
+    }
+
+    fn define(&self, name: Rc<str>, value: Value) {
+        self.vars.borrow_mut().insert(name, value);
+    }
+
+    fn lookup(&self, name: &str) -> Value {
+        let mut env = self;
+        loop {
+            if let Some(value) = env.vars.borrow().get(name) {
+                return value.clone();
+            }
+            match &env.parent {
+                Some(parent) => env = parent,
+                None => return Value::error(format!("unbound variable `{}`", name)),
+            }
+        }
+    }
+}
+
+fn arithmetic(init: i64, args: &[Value], op: fn(i64, i64) -> Option<i64>) -> Value {
+    let mut acc = init;
+    for arg in args {
+        let n = match arg {
+            Value::Num(n) => *n,
+            _ => return Value::error(format!("not a number: {}", arg)),
+        };
+        match op(acc, n) {
+            Some(res) => acc = res,
+            None => return Value::error("arithmetic overflow"),
+        }
+    }
+    Value::Num(acc)
+}
+
+fn compare(args: &[Value], op: fn(i64, i64) -> bool) -> Value {
+    match args {
+        [Value::Num(a), Value::Num(b)] => Value::Bool(op(*a, *b)),
+        _ => Value::error("comparisons need two numbers"),
+    }
+}
+
+fn make_lambda(params: &[Value], body: &[Value], env: &Rc<Env>) -> Value {
+    let params = params
+        .iter()
+        .map(|param| match param {
+            Value::Sym(name) => Ok(name.clone()),
+            _ => Err(Value::error(format!("not a parameter: {}", param))),
+        })
+        .collect();
+    match params {
+        Ok(params) => Value::Lambda(Rc::new(Lambda {
+            params,
+            body: body.into(),
+            env: env.clone(),
+        })),
+        Err(err) => err,
+    }
+}
+
+/// What is left to do for an expression after everything that does not
+/// need a call has been done.
+enum Form {
+    Done(Value),
+    If(Value, Value, Value),
+    Define(Rc<str>, Value),
+    /// Evaluates the expressions in order and returns the value of the last.
+    Body(Vec<Value>, Rc<Env>),
+    Apply(Vec<Value>),
+}
+
+fn analyze(expr: &Value, env: &Rc<Env>) -> Form {
+    let list = match expr {
+        Value::Sym(name) => return Form::Done(env.lookup(name)),
+        Value::List(list) if !list.is_empty() => list,
+        Value::List(_) => return Form::Done(Value::error("cannot evaluate `()`")),
+        _ => return Form::Done(expr.clone()),
+    };
+    if let Value::Sym(head) = &list[0] {
+        let form = match (&**head, &list[1..]) {
+            ("quote", [quoted]) => Form::Done(quoted.clone()),
+            ("if", [cond, then, rest @ ..]) if rest.len() <= 1 => {
+                let otherwise = rest.first().cloned().unwrap_or_default();
+                Form::If(cond.clone(), then.clone(), otherwise)
+            }
+            ("define", [Value::Sym(name), value]) => Form::Define(name.clone(), value.clone()),
+            ("define", [Value::List(signature), body @ ..]) if !body.is_empty() => {
+                match signature.split_first() {
+                    Some((Value::Sym(name), params)) => match make_lambda(params, body, env) {
+                        Value::Error(message) => Form::Done(Value::Error(message)),
+                        lambda => {
+                            env.define(name.clone(), lambda);
+                            Form::Done(Value::Sym(name.clone()))
+                        }
+                    },
+                    _ => Form::Done(Value::error("`define` needs a name")),
+                }
+            }
+            ("lambda", [Value::List(params), body @ ..]) if !body.is_empty() => {
+                Form::Done(make_lambda(params, body, env))
+            }
+            ("begin", body) if !body.is_empty() => Form::Body(body.to_vec(), env.clone()),
+            ("quote" | "if" | "define" | "lambda" | "begin", _) => {
+                Form::Done(Value::error(format!("malformed `{}`", head)))
+            }
+            _ => Form::Apply(list.to_vec()),
+        };
+        return form;
+    }
+    Form::Apply(list.to_vec())
+}
+
+/// Applies the first of `values` to the others.
+fn apply(values: Vec<Value>) -> Form {
+    let (function, args) = values.split_first().unwrap();
+    match function {
+        Value::Builtin(_, builtin) => Form::Done(builtin(args)),
+        Value::Lambda(lambda) if lambda.params.len() != args.len() => {
+            Form::Done(Value::error(format!(
+                "expected {} arguments, got {}",
+                lambda.params.len(),
+                args.len()
+            )))
+        }
+        Value::Lambda(lambda) => {
+            let vars = lambda.params.iter().cloned().zip(args.iter().cloned());
+            let env = Rc::new(Env {
+                vars: RefCell::new(vars.collect()),
+                parent: Some(lambda.env.clone()),
+            });
+            Form::Body(lambda.body.to_vec(), env)
+        }
+        _ => Form::Done(Value::error(format!("not a function: {}", function))),
+    }
+}
+
+type Arg = (Value, Rc<Env>);
+
+/// Evaluates `expr` in `env`. Errors are values that every frame passes on
+/// to its caller.
+fn eval((expr, env): Arg) -> impl Generator<Value, Yield = Call<Arg>, Return = Value> {
+    macro_rules! eval {
+        ($expr:expr, $env:expr) => {
+            match yield Call::normal(($expr, $env)) {
+                Value::Error(message) => return Value::Error(message),
+                value => value,
+            }
+        };
+    }
+
+    move |_: Value| {
+        let form = match analyze(&expr, &env) {
+            Form::Apply(exprs) => {
+                let mut values = Vec::with_capacity(exprs.len());
+                for expr in exprs {
+                    let value = eval!(expr, env.clone());
+                    values.push(value);
+                }
+                apply(values)
+            }
+            form => form,
+        };
+        match form {
+            Form::Done(value) => value,
+            Form::If(cond, then, otherwise) => {
+                let branch = if eval!(cond, env.clone()).is_truthy() {
+                    then
+                } else {
+                    otherwise
+                };
+                yield Call::tail((branch, env))
+            }
+            Form::Define(name, expr) => {
+                let value = eval!(expr, env.clone());
+                env.define(name.clone(), value);
+                Value::Sym(name)
+            }
+            Form::Body(body, env) => {
+                let mut body = body.into_iter();
+                let last = body.next_back().unwrap();
+                for expr in body {
+                    eval!(expr, env.clone());
+                }
+                yield Call::tail((last, env))
+            }
+            Form::Apply(_) => unreachable!(),
+        }
+    }
+}
+
+/// Parses all top-level forms of `source`. Nested lists are parsed with an
+/// explicit stack.
+fn parse(source: &str) -> Result<Vec<Value>, String> {
+    let source = source
+        .lines()
+        .map(|line| line.split(';').next().unwrap())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .replace('(', " ( ")
+        .replace(')', " ) ");
+    let mut stack = vec![Vec::new()];
+    for token in source.split_whitespace() {
+        let value = match token {
+            "(" => {
+                stack.push(Vec::new());
+                continue;
+            }
+            ")" => {
+                if stack.len() == 1 {
+                    return Err("unexpected `)`".to_string());
+                }
+                Value::List(stack.pop().unwrap().into())
+            }
+            "#t" => Value::Bool(true),
+            "#f" => Value::Bool(false),
+            "nil" => Value::Nil,
+            _ => match token.parse() {
+                Ok(n) => Value::Num(n),
+                Err(_) => Value::Sym(token.into()),
+            },
+        };
+        stack.last_mut().unwrap().push(value);
+    }
+    if stack.len() > 1 {
+        return Err("missing `)`".to_string());
+    }
+    Ok(stack.pop().unwrap())
+}
+
+#[derive(Default)]
+struct MaxDepth(usize);
+
+impl Observer<Arg> for MaxDepth {
+    fn enter(&mut self, _arg: &Arg, depth: usize) {
+        self.0 = self.0.max(depth);
+    }
+}
+
+fn run(source: &str) {
+    let forms = match parse(source) {
+        Ok(forms) => forms,
+        Err(err) => return eprintln!("parse error: {}", err),
+    };
+    let env = Env::global();
+    for form in forms {
+        let mut max_depth = MaxDepth::default();
+        let shown = form.to_string();
+        let value = Recursion::new(eval)
+            .tail_calls()
+            .observer(&mut max_depth)
+            .build()((form, env.clone()))
+        .unwrap_or_else(|err| err.resume_unwind());
+        println!("{} => {} [max depth {}]", shown, value, max_depth.0);
+    }
+}
+
+fn main() {
+    let source = match std::env::args().nth(1) {
+        Some(path) => std::fs::read_to_string(&path).unwrap_or_else(|err| {
+            eprintln!("cannot read {}: {}", path, err);
+            std::process::exit(1)
+        }),
+        None => DEMO.to_string(),
+    };
+    // A small native stack shows that the interpreter does not recurse.
+    with_stack_size(64 << 10, move || run(&source)).unwrap_or_else(|err| err.resume_unwind());
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use crate::disjoint_set::DisjointSet;
+use crate::{trampoline_mut, ByPtr};
+
+/// Disjoint sets of node addresses.
+#[derive(Default)]
+struct Classes {
+    ids: HashMap<*const (), usize>,
+    sets: DisjointSet,
+}
+
+impl Classes {
+    fn id(&mut self, node: *const ()) -> usize {
+        let sets = &mut self.sets;
+        *self.ids.entry(node).or_insert_with(|| sets.push())
+    }
+
+    /// Merges the classes of `a` and `b`. Returns `false` if they were
+    /// already the same.
+    fn union(&mut self, a: *const (), b: *const ()) -> bool {
+        let (a, b) = (self.id(a), self.id(b));
+        self.sets.union(a, b)
+    }
+}
+
+struct State {
+    classes: Classes,
+    equal: bool,
+}
+
+fn compare<'a: 's, 's, T, I>(
+    a: &'a T,
+    b: &'a T,
+    children: &'s impl Fn(&'a T) -> I,
+    shallow_eq: &'s impl Fn(&'a T, &'a T) -> bool,
+    state: &'s mut State,
+) where
+    T: ?Sized,
+    I: IntoIterator<Item = &'a T>,
+{
+    trampoline_mut(|(a, b): (&'a T, &'a T)| {
+        move |(_, mut s): ((), &'s mut State)| {
+            if !s.classes.union(ByPtr(a).addr(), ByPtr(b).addr()) {
+                return ((), s);
+            }
+            let children_a: Vec<_> = children(a).into_iter().collect();
+            let children_b: Vec<_> = children(b).into_iter().collect();
+            if !shallow_eq(a, b) || children_a.len() != children_b.len() {
+                s.equal = false;
+                return ((), s);
+            }
+            for (a, b) in children_a.into_iter().zip(children_b) {
+                if !s.equal {
+                    break;
+                }
+                ((), s) = yield ((a, b), s);
+            }
+            ((), s)
+        }
+    })((a, b), state)
+}
+
+/// Like `deep_diff(a, b, children, shallow_eq).is_none()`, but terminates on
+/// cyclic structures, e.g., `Rc` graphs or lazily tied knots. Nodes are
+/// identified by their addresses, and two nodes are equal if they are
+/// bisimilar: their data is `shallow_eq` and their children are pairwise
+/// equal. Following Hopcroft and Karp, the nodes of a pair are merged into
+/// one class as soon as the pair is visited, so every pair is compared at
+/// most once and pairs that are still being compared are assumed to be
+/// equal.
+pub fn bisimilar<'a, T, I>(
+    a: &'a T,
+    b: &'a T,
+    children: impl Fn(&'a T) -> I,
+    shallow_eq: impl Fn(&'a T, &'a T) -> bool,
+) -> bool
+where
+    T: ?Sized,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut state = State {
+        classes: Classes::default(),
+        equal: true,
+    };
+    compare(a, b, &children, &shallow_eq, &mut state);
+    state.equal
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::ops::{Deref, Generator};
+use std::sync::Arc;
+
+use crate::trampoline;
+
+/// A stack-safe function with a nameable type, e.g., for storing an
+/// evaluator in a struct field. It derefs to `dyn Fn(Arg) -> Res`, so it can
+/// be called like a function.
+pub struct StackSafeFn<'a, Arg, Res> {
+    f: Box<dyn Fn(Arg) -> Res + 'a>,
+}
+
+impl<'a, Arg, Res> StackSafeFn<'a, Arg, Res> {
+    /// Boxes `trampoline(f)`.
+    pub fn new<Gen>(f: impl Fn(Arg) -> Gen + 'a) -> Self
+    where
+        Arg: 'a,
+        Res: Default + 'a,
+        Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin + 'a,
+    {
+        Self::from_fn(trampoline(f))
+    }
+
+    /// Boxes the result of any other driver, e.g., `trampoline_tco`.
+    pub fn from_fn(f: impl Fn(Arg) -> Res + 'a) -> Self {
+        Self { f: Box::new(f) }
+    }
+
+    pub fn call(&self, arg: Arg) -> Res {
+        (self.f)(arg)
+    }
+}
+
+impl<'a, Arg, Res> Deref for StackSafeFn<'a, Arg, Res> {
+    type Target = dyn Fn(Arg) -> Res + 'a;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.f
+    }
+}
+
+impl<'a, Arg, Res> fmt::Debug for StackSafeFn<'a, Arg, Res> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StackSafeFn").finish_non_exhaustive()
+    }
+}
+
+/// A stack-safe function that can be cloned and shared across threads, e.g.,
+/// for registries and callbacks. It implements `Fn(Arg) -> Res` itself.
+pub struct RecursiveFn<Arg, Res> {
+    f: Arc<dyn Fn(Arg) -> Res + Send + Sync>,
+}
+
+impl<Arg, Res> RecursiveFn<Arg, Res> {
+    /// Shares `trampoline(f)`.
+    pub fn new<Gen>(f: impl Fn(Arg) -> Gen + Send + Sync + 'static) -> Self
+    where
+        Arg: 'static,
+        Res: Default + 'static,
+        Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin + 'static,
+    {
+        Self::from_fn(trampoline(f))
+    }
+
+    /// Shares the result of any other driver, e.g., `trampoline_tco`.
+    pub fn from_fn(f: impl Fn(Arg) -> Res + Send + Sync + 'static) -> Self {
+        Self { f: Arc::new(f) }
+    }
+}
+
+impl<Arg, Res> Clone for RecursiveFn<Arg, Res> {
+    fn clone(&self) -> Self {
+        Self {
+            f: Arc::clone(&self.f),
+        }
+    }
+}
+
+impl<Arg, Res> FnOnce<(Arg,)> for RecursiveFn<Arg, Res> {
+    type Output = Res;
+
+    extern "rust-call" fn call_once(self, (arg,): (Arg,)) -> Res {
+        (self.f)(arg)
+    }
+}
+
+impl<Arg, Res> FnMut<(Arg,)> for RecursiveFn<Arg, Res> {
+    extern "rust-call" fn call_mut(&mut self, (arg,): (Arg,)) -> Res {
+        (self.f)(arg)
+    }
+}
+
+impl<Arg, Res> Fn<(Arg,)> for RecursiveFn<Arg, Res> {
+    extern "rust-call" fn call(&self, (arg,): (Arg,)) -> Res {
+        (self.f)(arg)
+    }
+}
+
+impl<Arg, Res> fmt::Debug for RecursiveFn<Arg, Res> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecursiveFn").finish_non_exhaustive()
+    }
+}
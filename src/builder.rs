@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+use crate::checked::check_tail;
+use crate::hook::PathRecorder;
+use crate::{Cache, Call, CallKind, Never, Observer, StackSafeError, Stop};
+
+/// How calls without a label show up in logical backtraces.
+const UNLABELED: &str = "<unlabeled>";
+
+/// How `Recursion` remembers results. `()` remembers nothing.
+pub trait Memo<Arg, Res> {
+    type Key;
+
+    fn lookup(&mut self, arg: &Arg) -> Option<Res>;
+
+    /// Returns the key to store the result of the call on `arg` under, if any.
+    fn key(&self, arg: &Arg) -> Option<Self::Key>;
+
+    fn store(&mut self, key: Self::Key, res: &Res);
+}
+
+impl<Arg, Res> Memo<Arg, Res> for () {
+    type Key = ();
+
+    fn lookup(&mut self, _arg: &Arg) -> Option<Res> {
+        None
+    }
+
+    fn key(&self, _arg: &Arg) -> Option<()> {
+        None
+    }
+
+    fn store(&mut self, _key: (), _res: &Res) {}
+}
+
+/// Remembers results in a `Cache`.
+#[derive(Clone, Debug, Default)]
+pub struct Memoized<C>(pub C);
+
+impl<Arg, Res, C> Memo<Arg, Res> for Memoized<C>
+where
+    Arg: Clone,
+    Res: Clone,
+    C: Cache<Arg, Res>,
+{
+    type Key = Arg;
+
+    fn lookup(&mut self, arg: &Arg) -> Option<Res> {
+        self.0.get(arg)
+    }
+
+    fn key(&self, arg: &Arg) -> Option<Arg> {
+        Some(arg.clone())
+    }
+
+    fn store(&mut self, key: Arg, res: &Res) {
+        self.0.insert(key, res.clone());
+    }
+}
+
+/// Configures a stack-safe function from `f`, which creates the frame for
+/// an argument, so that options can be combined without a driver for every
+/// combination:
+///
+/// ```ignore
+/// let fib = Recursion::new(fib_frame).memoize().depth_limit(1000).build();
+/// ```
+///
+/// Frames yield either arguments or `Call`s. The built function reports
+/// hitting the depth limit or being stopped as an error.
+pub struct Recursion<F, M = (), S = Never, O = ()> {
+    f: F,
+    tail_calls: bool,
+    depth_limit: Option<usize>,
+    capacity: usize,
+    memo: M,
+    stop: S,
+    observer: O,
+}
+
+impl<F> Recursion<F> {
+    pub fn new(f: F) -> Self {
+        Self {
+            f,
+            tail_calls: false,
+            depth_limit: None,
+            capacity: 0,
+            memo: (),
+            stop: Never,
+            observer: (),
+        }
+    }
+}
+
+impl<F, M, S, O> Recursion<F, M, S, O> {
+    /// Replaces the caller's frame on `Call::tail`, like `trampoline_tco`.
+    /// Otherwise, tail calls are made like normal calls.
+    pub fn tail_calls(mut self) -> Self {
+        self.tail_calls = true;
+        self
+    }
+
+    /// Fails with `StackSafeError::DepthLimit` instead of suspending more
+    /// than `max_depth` frames.
+    pub fn depth_limit(mut self, max_depth: usize) -> Self {
+        self.depth_limit = Some(max_depth);
+        self
+    }
+
+    /// Preallocates room for `capacity` suspended frames on every call.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Remembers the results of all calls in a `HashMap`, which lives as
+    /// long as the built function.
+    pub fn memoize<Arg, Res>(self) -> Recursion<F, Memoized<HashMap<Arg, Res>>, S, O> {
+        self.cache(HashMap::new())
+    }
+
+    /// Like `memoize`, but with a custom cache, e.g., an LRU cache.
+    pub fn cache<C>(self, cache: C) -> Recursion<F, Memoized<C>, S, O> {
+        Recursion {
+            f: self.f,
+            tail_calls: self.tail_calls,
+            depth_limit: self.depth_limit,
+            capacity: self.capacity,
+            memo: Memoized(cache),
+            stop: self.stop,
+            observer: self.observer,
+        }
+    }
+
+    /// Checks `stop` before every step, e.g., a `Fuel` or a `Deadline`. Fuel
+    /// is shared by all calls of the built function.
+    pub fn stop<S1: Stop>(self, stop: S1) -> Recursion<F, M, S1, O> {
+        Recursion {
+            f: self.f,
+            tail_calls: self.tail_calls,
+            depth_limit: self.depth_limit,
+            capacity: self.capacity,
+            memo: self.memo,
+            stop,
+            observer: self.observer,
+        }
+    }
+
+    /// Reports every call that is made to `observer`, like
+    /// `trampoline_observed`. Tail calls exit the caller's frame first.
+    pub fn observer<O1>(self, observer: O1) -> Recursion<F, M, S, O1> {
+        Recursion {
+            f: self.f,
+            tail_calls: self.tail_calls,
+            depth_limit: self.depth_limit,
+            capacity: self.capacity,
+            memo: self.memo,
+            stop: self.stop,
+            observer,
+        }
+    }
+
+    /// Returns the configured stack-safe function.
+    #[must_use = "`build` returns the stack-safe function without calling it"]
+    pub fn build<Arg, Res, Gen>(self) -> impl FnMut(Arg) -> Result<Res, StackSafeError>
+    where
+        F: Fn(Arg) -> Gen,
+        Res: Default,
+        Gen: Generator<Res, Return = Res> + Unpin,
+        Gen::Yield: Into<Call<Arg>>,
+        M: Memo<Arg, Res>,
+        S: Stop,
+        O: Observer<Arg>,
+    {
+        let Self {
+            f,
+            tail_calls,
+            depth_limit,
+            capacity,
+            mut memo,
+            mut stop,
+            mut observer,
+        } = self;
+        move |arg: Arg| {
+            if let Some(res) = memo.lookup(&arg) {
+                return Ok(res);
+            }
+            // Every frame comes with the keys its result is remembered under,
+            // several ones after tail calls.
+            let mut stack: Vec<(Gen, Vec<M::Key>)> = Vec::with_capacity(capacity);
+            let keys: Vec<_> = memo.key(&arg).into_iter().collect();
+            // Records the labels for the hook of `install_panic_hook`.
+            let recorder = PathRecorder::new();
+            recorder.push(&format_args!("{}", UNLABELED));
+            observer.enter_labeled(&arg, None, 0);
+            let mut current = (f(arg), keys);
+            let mut res = Res::default();
+
+            loop {
+                if let Some(err) = stop.check() {
+                    return Err(err);
+                }
+                let done = match Pin::new(&mut current.0).resume(res) {
+                    GeneratorState::Yielded(call) => {
+                        let call = call.into();
+                        let label = call.label;
+                        let (arg, tail) = match call.kind {
+                            CallKind::Normal(arg) => (arg, false),
+                            CallKind::Tail(arg) => (arg, tail_calls),
+                        };
+                        if tail {
+                            check_tail(&mut current.0);
+                        }
+                        match memo.lookup(&arg) {
+                            Some(cached) if tail => cached,
+                            Some(cached) => {
+                                res = cached;
+                                continue;
+                            }
+                            None => {
+                                let key = memo.key(&arg);
+                                if tail {
+                                    observer.exit(stack.len());
+                                    recorder.pop();
+                                    recorder.push(&format_args!(
+                                        "{}",
+                                        label.as_deref().unwrap_or(UNLABELED)
+                                    ));
+                                    observer.enter_labeled(&arg, label.as_deref(), stack.len());
+                                    current.0 = f(arg);
+                                    current.1.extend(key);
+                                } else {
+                                    if let Some(max_depth) = depth_limit {
+                                        if stack.len() >= max_depth {
+                                            return Err(StackSafeError::DepthLimit { max_depth });
+                                        }
+                                    }
+                                    stack.push(current);
+                                    recorder.push(&format_args!(
+                                        "{}",
+                                        label.as_deref().unwrap_or(UNLABELED)
+                                    ));
+                                    observer.enter_labeled(&arg, label.as_deref(), stack.len());
+                                    current = (f(arg), key.into_iter().collect());
+                                }
+                                res = Res::default();
+                                continue;
+                            }
+                        }
+                    }
+                    GeneratorState::Complete(done) => done,
+                };
+                observer.exit(stack.len());
+                recorder.pop();
+                for key in current.1.drain(..) {
+                    memo.store(key, &done);
+                }
+                match stack.pop() {
+                    None => return Ok(done),
+                    Some(top) => {
+                        current = top;
+                        res = done;
+                    }
+                }
+            }
+        }
+    }
+}
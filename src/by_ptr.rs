@@ -0,0 +1,40 @@
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+/// Compares and hashes a pointer like `Rc` or `Arc` by the address of its
+/// target instead of the target's value. This makes shared nodes usable as
+/// keys of `recurse_dag` or of a `Cache` without requiring `Eq + Hash` on the
+/// node type, and without comparing whole subtrees.
+///
+/// Addresses may be reused once a node has been freed, so all nodes must be
+/// kept alive while they are used as keys.
+#[derive(Clone, Debug)]
+pub struct ByPtr<P>(pub P);
+
+impl<P: Deref> ByPtr<P> {
+    pub fn addr(&self) -> *const () {
+        &*self.0 as *const P::Target as *const ()
+    }
+}
+
+impl<P: Deref> Deref for ByPtr<P> {
+    type Target = P::Target;
+
+    fn deref(&self) -> &P::Target {
+        &self.0
+    }
+}
+
+impl<P: Deref> PartialEq for ByPtr<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+impl<P: Deref> Eq for ByPtr<P> {}
+
+impl<P: Deref> Hash for ByPtr<P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.addr().hash(state)
+    }
+}
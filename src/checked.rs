@@ -0,0 +1,74 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+/// A frame that, with the `debug-invariants` feature, checks that drivers
+/// follow the frame protocol: a frame is never resumed after it completed or
+/// after a resume panicked. Without the feature, it only forwards to the
+/// wrapped frame.
+pub(crate) struct Checked<G> {
+    gen: G,
+    #[cfg(feature = "debug-invariants")]
+    state: State,
+}
+
+#[cfg(feature = "debug-invariants")]
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum State {
+    Suspended,
+    Running,
+    Complete,
+}
+
+impl<G> Checked<G> {
+    pub(crate) fn new(gen: G) -> Self {
+        Self {
+            gen,
+            #[cfg(feature = "debug-invariants")]
+            state: State::Suspended,
+        }
+    }
+}
+
+impl<R, G: Generator<R> + Unpin> Generator<R> for Checked<G> {
+    type Yield = G::Yield;
+    type Return = G::Return;
+
+    #[inline]
+    fn resume(self: Pin<&mut Self>, arg: R) -> GeneratorState<G::Yield, G::Return> {
+        let this = self.get_mut();
+        #[cfg(feature = "debug-invariants")]
+        {
+            invariant!(
+                this.state != State::Complete,
+                "a completed frame was resumed"
+            );
+            invariant!(
+                this.state != State::Running,
+                "a frame was resumed after it panicked"
+            );
+            this.state = State::Running;
+        }
+        let state = Pin::new(&mut this.gen).resume(arg);
+        #[cfg(feature = "debug-invariants")]
+        {
+            this.state = match state {
+                GeneratorState::Yielded(_) => State::Suspended,
+                GeneratorState::Complete(_) => State::Complete,
+            };
+        }
+        state
+    }
+}
+
+/// With the `debug-invariants` feature, checks that a frame that just yielded
+/// `Call::tail` does nothing but return: the frame is resumed once more with
+/// a default result and must not yield again. Its result is discarded.
+pub(crate) fn check_tail<R: Default, G: Generator<R> + Unpin>(gen: &mut G) {
+    invariant!(
+        matches!(
+            Pin::new(gen).resume(R::default()),
+            GeneratorState::Complete(_)
+        ),
+        "a frame yielded again after `Call::tail`"
+    );
+}
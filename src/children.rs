@@ -0,0 +1,83 @@
+use std::cmp::Ordering;
+
+use crate::Order;
+
+pub trait ChildrenMut {
+    type IterMut<'a>: Iterator<Item = &'a mut Self>
+    where
+        Self: 'a;
+
+    fn children_mut(&mut self) -> Self::IterMut<'_>;
+}
+
+/// Applies `f` to every node of `root`, either before (`Order::Pre`) or after
+/// (`Order::Post`) it is applied to the node's children. With `Order::Pre`,
+/// the children visited are those present after `f` has run.
+pub fn map_mut<T: ChildrenMut>(root: &mut T, order: Order, mut f: impl FnMut(&mut T)) {
+    enum Task<T> {
+        Enter(*mut T),
+        Exit(*mut T),
+    }
+
+    // All pointers on the stack are derived from `root`. A node is only
+    // accessed when no reference into its subtree is alive: its children are
+    // computed after `f` has been applied to it in pre-order, and `f` is
+    // applied to it in post-order only after its whole subtree is done.
+    let mut stack = vec![Task::Enter(root as *mut T)];
+    while let Some(task) = stack.pop() {
+        match task {
+            Task::Enter(node) => {
+                if order == Order::Pre {
+                    f(unsafe { &mut *node });
+                } else {
+                    stack.push(Task::Exit(node));
+                }
+                let start = stack.len();
+                let children = unsafe { &mut *node }.children_mut();
+                stack.extend(children.map(|child| Task::Enter(child as *mut T)));
+                stack[start..].reverse();
+            }
+            Task::Exit(node) => f(unsafe { &mut *node }),
+        }
+    }
+}
+
+/// Compares `a` and `b` lexicographically: first the roots with
+/// `shallow_cmp`, then their children pairwise from left to right, where a
+/// node with fewer children comes first if all others are equal. This is the
+/// order `#[derive(Ord)]` gives recursive types, without recursing, e.g., to
+/// sort huge syntax trees or use them as `BTreeMap` keys.
+pub fn deep_cmp<'a, T, I>(
+    a: &'a T,
+    b: &'a T,
+    children: impl Fn(&'a T) -> I,
+    shallow_cmp: impl Fn(&'a T, &'a T) -> Ordering,
+) -> Ordering
+where
+    T: ?Sized,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut stack = Vec::new();
+    let (mut a, mut b) = (a, b);
+    loop {
+        match shallow_cmp(a, b) {
+            Ordering::Equal => {}
+            ordering => return ordering,
+        }
+        stack.push((children(a).into_iter(), children(b).into_iter()));
+        (a, b) = loop {
+            let (children_a, children_b) = match stack.last_mut() {
+                None => return Ordering::Equal,
+                Some(top) => top,
+            };
+            match (children_a.next(), children_b.next()) {
+                (Some(a), Some(b)) => break (a, b),
+                (None, None) => {
+                    stack.pop();
+                }
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+            }
+        };
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{Arena, NodeId};
+
+/// Caller-provided storage for the nodes of a deep copy, e.g., an `Arena`,
+/// a bump allocator or a custom allocator. A node is stored after all of its
+/// children, which are referred to by the handles returned for them.
+pub trait Storage<V> {
+    type Handle;
+
+    fn store(&mut self, value: V, children: Vec<Self::Handle>) -> Self::Handle;
+}
+
+impl<T> Storage<T> for Arena<T> {
+    type Handle = NodeId;
+
+    fn store(&mut self, value: T, children: Vec<NodeId>) -> NodeId {
+        self.alloc(value, children)
+    }
+}
+
+/// Copies the tree below `root` into `storage` without recursing on its
+/// depth. `value` produces the stored value of a node. Returns the handle of
+/// the copied root.
+pub fn deep_clone_into<'a, T, V, S, I>(
+    root: &'a T,
+    storage: &mut S,
+    children: impl Fn(&'a T) -> I,
+    value: impl Fn(&'a T) -> V,
+) -> S::Handle
+where
+    S: Storage<V>,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut stack = Vec::new();
+    let mut current = (value(root), children(root).into_iter(), Vec::new());
+
+    loop {
+        if let Some(child) = current.1.next() {
+            stack.push(current);
+            current = (value(child), children(child).into_iter(), Vec::new());
+        } else {
+            let (value, _, handles) = current;
+            let handle = storage.store(value, handles);
+            match stack.pop() {
+                None => return handle,
+                Some(mut parent) => {
+                    parent.2.push(handle);
+                    current = parent;
+                }
+            }
+        }
+    }
+}
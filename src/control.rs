@@ -0,0 +1,171 @@
+use std::ops::{ControlFlow, Generator, GeneratorState};
+use std::pin::Pin;
+
+/// What a frame of `trampoline_control` yields.
+pub enum Control<Arg, Res> {
+    /// Calls the function on `Arg`, like yielding `Arg` to `trampoline`.
+    Call(Arg),
+    /// Like `Call`, but marks the new frame as a prompt.
+    Prompt(Arg),
+    /// Drops all frames up to and including the nearest enclosing prompt and
+    /// resumes the caller of the prompt with `Res`. Without an enclosing
+    /// prompt, the whole computation returns `Res`.
+    Abort(Res),
+}
+
+/// Like `trampoline`, but frames can yield `Control::Abort` for non-local
+/// exits to the nearest `Control::Prompt`. Aborting truncates the frame
+/// stack without resuming any of the dropped frames.
+pub fn trampoline_control<Arg, Res, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Res
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Control<Arg, Res>, Return = Res> + Unpin,
+{
+    move |arg: Arg| {
+        let mut stack = Vec::new();
+        let mut current = (f(arg), false);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current.0).resume(res) {
+                GeneratorState::Yielded(Control::Call(arg)) => {
+                    stack.push(current);
+                    current = (f(arg), false);
+                    res = Res::default();
+                }
+                GeneratorState::Yielded(Control::Prompt(arg)) => {
+                    stack.push(current);
+                    current = (f(arg), true);
+                    res = Res::default();
+                }
+                GeneratorState::Yielded(Control::Abort(value)) => {
+                    while !current.1 {
+                        match stack.pop() {
+                            None => return value,
+                            Some(top) => current = top,
+                        }
+                    }
+                    match stack.pop() {
+                        None => return value,
+                        Some(top) => {
+                            current = top;
+                            res = value;
+                        }
+                    }
+                }
+                GeneratorState::Complete(real_res) => match stack.pop() {
+                    None => return real_res,
+                    Some(top) => {
+                        current = top;
+                        res = real_res;
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// What a frame of `trampoline_labeled` yields.
+pub enum Labeled<Arg, Res, L> {
+    /// Calls the function on `Arg`, like yielding `Arg` to `trampoline`.
+    Call(Arg),
+    /// Like `Call`, but marks the new frame with the label `L`.
+    Label(L, Arg),
+    /// Drops all frames up to and including the nearest one marked with `L`
+    /// and resumes the frame that made the labeled call with `Res`. Without
+    /// such a frame, the whole computation returns `Res`.
+    ReturnTo(L, Res),
+}
+
+/// Like `trampoline_control`, but returns to the nearest frame with a
+/// matching label instead of the nearest prompt, e.g., to model exception
+/// handlers or returning from a function out of nested loops in an
+/// interpreter.
+pub fn trampoline_labeled<Arg, Res, L, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Res
+where
+    Res: Default,
+    L: PartialEq,
+    Gen: Generator<Res, Yield = Labeled<Arg, Res, L>, Return = Res> + Unpin,
+{
+    move |arg: Arg| {
+        let mut stack = Vec::new();
+        let mut current = (f(arg), None);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current.0).resume(res) {
+                GeneratorState::Yielded(Labeled::Call(arg)) => {
+                    stack.push(current);
+                    current = (f(arg), None);
+                    res = Res::default();
+                }
+                GeneratorState::Yielded(Labeled::Label(label, arg)) => {
+                    stack.push(current);
+                    current = (f(arg), Some(label));
+                    res = Res::default();
+                }
+                GeneratorState::Yielded(Labeled::ReturnTo(label, value)) => {
+                    while current.1.as_ref() != Some(&label) {
+                        match stack.pop() {
+                            None => return value,
+                            Some(top) => current = top,
+                        }
+                    }
+                    match stack.pop() {
+                        None => return value,
+                        Some(top) => {
+                            current = top;
+                            res = value;
+                        }
+                    }
+                }
+                GeneratorState::Complete(real_res) => match stack.pop() {
+                    None => return real_res,
+                    Some(top) => {
+                        current = top;
+                        res = real_res;
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Like `trampoline`, but frames return a `ControlFlow`. A `Continue` result
+/// resumes the caller as usual, whereas a `Break` drops all pending frames
+/// and is returned to the top-level caller immediately, e.g., once a search
+/// has found the first match.
+pub fn trampoline_break<Arg, Res, B, Gen>(
+    f: impl Fn(Arg) -> Gen,
+) -> impl Fn(Arg) -> ControlFlow<B, Res>
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = ControlFlow<B, Res>> + Unpin,
+{
+    move |arg: Arg| {
+        let mut stack = Vec::new();
+        let mut current = f(arg);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current).resume(res) {
+                GeneratorState::Yielded(arg) => {
+                    stack.push(current);
+                    current = f(arg);
+                    res = Res::default();
+                }
+                GeneratorState::Complete(ControlFlow::Break(value)) => {
+                    while stack.pop().is_some() {}
+                    return ControlFlow::Break(value);
+                }
+                GeneratorState::Complete(ControlFlow::Continue(real_res)) => match stack.pop() {
+                    None => return ControlFlow::Continue(real_res),
+                    Some(top) => {
+                        current = top;
+                        res = real_res;
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Deref;
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+use crate::ByPtr;
+
+/// Like `trampoline`, but calls the function at most once for every distinct
+/// argument during a top-level call. Repeated calls are answered from a table
+/// of completed calls, which makes walks over shared substructure linear in
+/// the number of distinct nodes.
+///
+/// # Panics
+///
+/// Panics if a call depends on itself.
+pub fn recurse_dag<Arg, Res, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Res
+where
+    Arg: Clone + Eq + Hash,
+    Res: Clone + Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    recurse_dag_by(Arg::clone, f)
+}
+
+/// Like `recurse_dag`, but identifies arguments by `key`, e.g., by pointer
+/// identity for `Rc` or `Arc` nodes via `Rc::as_ptr`.
+pub fn recurse_dag_by<Arg, Key, Res, Gen>(
+    key: impl Fn(&Arg) -> Key,
+    f: impl Fn(Arg) -> Gen,
+) -> impl Fn(Arg) -> Res
+where
+    Key: Clone + Eq + Hash,
+    Res: Clone + Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    move |arg: Arg| {
+        // `None` marks calls that are still in progress.
+        let mut table: HashMap<Key, Option<Res>> = HashMap::new();
+        let mut stack = Vec::new();
+        let arg_key = key(&arg);
+        table.insert(arg_key.clone(), None);
+        let mut current = (f(arg), arg_key);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current.0).resume(res) {
+                GeneratorState::Yielded(arg) => {
+                    let arg_key = key(&arg);
+                    match table.get(&arg_key) {
+                        Some(Some(done)) => res = done.clone(),
+                        Some(None) => panic!("cycle detected in `recurse_dag`"),
+                        None => {
+                            table.insert(arg_key.clone(), None);
+                            stack.push(current);
+                            current = (f(arg), arg_key);
+                            res = Res::default();
+                        }
+                    }
+                }
+                GeneratorState::Complete(real_res) => {
+                    table.insert(current.1, Some(real_res.clone()));
+                    match stack.pop() {
+                        None => return real_res,
+                        Some(top) => {
+                            current = top;
+                            res = real_res;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Like `recurse_dag`, but identifies arguments by the address they point to,
+/// as `ByPtr` does. All nodes must be kept alive during a top-level call.
+pub fn recurse_dag_by_ptr<P, Res, Gen>(f: impl Fn(P) -> Gen) -> impl Fn(P) -> Res
+where
+    P: Deref,
+    Res: Clone + Default,
+    Gen: Generator<Res, Yield = P, Return = Res> + Unpin,
+{
+    recurse_dag_by(|ptr: &P| ByPtr(&**ptr).addr(), f)
+}
+
+/// How a node that `recurse_graph_by_ptr` has seen before is reached again.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Visit {
+    /// The node is still being visited, i.e., the call closes a cycle.
+    Cycle,
+    /// The node has been visited completely before.
+    Shared,
+}
+
+/// What `recurse_graph_by_ptr` does with a call on a node it has seen before.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Revisit<Res> {
+    /// Calls the function on the node again. Entering a `Visit::Cycle` node
+    /// unrolls the cycle once more, so the hook must stop doing so eventually.
+    Enter,
+    /// Resumes the caller with the given result without entering the node.
+    Answer(Res),
+}
+
+/// Like `trampoline`, but for walks over arbitrary object graphs of `Rc` or
+/// `Arc` nodes, which may share nodes and contain cycles. Nodes are
+/// identified by the address they point to, as `ByPtr` does, and every call
+/// on a node that has been seen before during a top-level call is passed to
+/// `revisit`, which decides whether to enter the node again. All nodes seen
+/// are kept alive until the top-level call returns.
+pub fn recurse_graph_by_ptr<P, Res, Gen>(
+    f: impl Fn(P) -> Gen,
+    revisit: impl Fn(&P, Visit) -> Revisit<Res>,
+) -> impl Fn(P) -> Res
+where
+    P: Clone + Deref,
+    Res: Default,
+    Gen: Generator<Res, Yield = P, Return = Res> + Unpin,
+{
+    move |ptr: P| {
+        // The number of frames on the stack for every node seen so far.
+        let mut table: HashMap<*const (), (P, usize)> = HashMap::new();
+        let enter = |table: &mut HashMap<*const (), (P, usize)>, ptr: P| {
+            let addr = ByPtr(&*ptr).addr();
+            table.entry(addr).or_insert_with(|| (ptr.clone(), 0)).1 += 1;
+            (f(ptr), addr)
+        };
+        let mut stack = Vec::new();
+        let mut current = enter(&mut table, ptr);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current.0).resume(res) {
+                GeneratorState::Yielded(ptr) => {
+                    let visit = match table.get(&ByPtr(&*ptr).addr()) {
+                        None => None,
+                        Some((_, 0)) => Some(Visit::Shared),
+                        Some(_) => Some(Visit::Cycle),
+                    };
+                    match visit.map_or(Revisit::Enter, |visit| revisit(&ptr, visit)) {
+                        Revisit::Enter => {
+                            stack.push(current);
+                            current = enter(&mut table, ptr);
+                            res = Res::default();
+                        }
+                        Revisit::Answer(answer) => res = answer,
+                    }
+                }
+                GeneratorState::Complete(real_res) => {
+                    table.get_mut(&current.1).unwrap().1 -= 1;
+                    match stack.pop() {
+                        None => return real_res,
+                        Some(top) => {
+                            current = top;
+                            res = real_res;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
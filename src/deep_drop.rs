@@ -0,0 +1,36 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Drops `rc` without recursing on the depth of the structure behind it.
+/// `take_children` moves the child pointers out of a node whose last strong
+/// reference is being dropped. Nodes that are still referenced elsewhere are
+/// left alone, i.e., only their strong count is decremented.
+///
+/// A `Drop` impl of `T` can use this on the children it owns, as long as
+/// `take_children` leaves nothing behind that would be dropped recursively.
+pub fn deep_drop_rc<T, I>(rc: Rc<T>, mut take_children: impl FnMut(&mut T) -> I)
+where
+    I: IntoIterator<Item = Rc<T>>,
+{
+    let mut stack = vec![rc];
+    while let Some(rc) = stack.pop() {
+        if let Ok(mut node) = Rc::try_unwrap(rc) {
+            stack.extend(take_children(&mut node));
+        }
+    }
+}
+
+/// Like `deep_drop_rc`, but for `Arc`. If another thread drops a reference
+/// to the same node at the same time, that thread might end up dropping the
+/// node and hence its subtree recursively.
+pub fn deep_drop_arc<T, I>(arc: Arc<T>, mut take_children: impl FnMut(&mut T) -> I)
+where
+    I: IntoIterator<Item = Arc<T>>,
+{
+    let mut stack = vec![arc];
+    while let Some(arc) = stack.pop() {
+        if let Ok(mut node) = Arc::try_unwrap(arc) {
+            stack.extend(take_children(&mut node));
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::trampoline_mut;
+
+struct State {
+    path: Vec<usize>,
+    diffs: Vec<Vec<usize>>,
+    limit: usize,
+}
+
+fn diff<'a: 's, 's, T, I>(
+    a: &'a T,
+    b: &'a T,
+    children: &'s impl Fn(&'a T) -> I,
+    shallow_eq: &'s impl Fn(&'a T, &'a T) -> bool,
+    state: &'s mut State,
+) where
+    T: ?Sized,
+    I: IntoIterator<Item = &'a T>,
+{
+    trampoline_mut(|(a, b): (&'a T, &'a T)| {
+        move |(_, mut s): ((), &'s mut State)| {
+            let children_a: Vec<_> = children(a).into_iter().collect();
+            let children_b: Vec<_> = children(b).into_iter().collect();
+            if !shallow_eq(a, b) || children_a.len() != children_b.len() {
+                s.diffs.push(s.path.clone());
+                return ((), s);
+            }
+            for (index, (a, b)) in children_a.into_iter().zip(children_b).enumerate() {
+                if s.diffs.len() >= s.limit {
+                    break;
+                }
+                s.path.push(index);
+                ((), s) = yield ((a, b), s);
+                s.path.pop();
+            }
+            ((), s)
+        }
+    })((a, b), state)
+}
+
+/// Walks `a` and `b` in lockstep and returns the path to the first pair of
+/// nodes that differ, if any. A path lists the indices of the children to
+/// descend into, starting from the roots. Two nodes differ if `shallow_eq`
+/// says so or if they have a different number of children.
+pub fn deep_diff<'a, T, I>(
+    a: &'a T,
+    b: &'a T,
+    children: impl Fn(&'a T) -> I,
+    shallow_eq: impl Fn(&'a T, &'a T) -> bool,
+) -> Option<Vec<usize>>
+where
+    T: ?Sized,
+    I: IntoIterator<Item = &'a T>,
+{
+    deep_diff_all(a, b, children, shallow_eq, 1).pop()
+}
+
+/// Like `deep_diff`, but returns the paths to up to `limit` pairs of nodes
+/// that differ, in pre-order. The children of differing nodes are not
+/// compared.
+pub fn deep_diff_all<'a, T, I>(
+    a: &'a T,
+    b: &'a T,
+    children: impl Fn(&'a T) -> I,
+    shallow_eq: impl Fn(&'a T, &'a T) -> bool,
+    limit: usize,
+) -> Vec<Vec<usize>>
+where
+    T: ?Sized,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut state = State {
+        path: Vec::new(),
+        diffs: Vec::new(),
+        limit,
+    };
+    if limit > 0 {
+        diff(a, b, &children, &shallow_eq, &mut state);
+    }
+    state.diffs
+}
@@ -0,0 +1,86 @@
+//! Disjoint sets with union by size and path compression.
+//!
+//! `find` compresses paths in a loop instead of recursively, so long chains
+//! of parents, e.g., after pathological sequences of unions, cannot overflow
+//! the stack.
+
+/// A partition of the elements `0..len` into disjoint sets.
+#[derive(Clone, Debug, Default)]
+pub struct DisjointSet {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    count: usize,
+}
+
+impl DisjointSet {
+    /// Puts every element into a set of its own.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+            count: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Adds a new element in a set of its own and returns it.
+    pub fn push(&mut self) -> usize {
+        let elem = self.len();
+        self.parents.push(elem);
+        self.sizes.push(1);
+        self.count += 1;
+        elem
+    }
+
+    /// Returns the number of sets.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the representative of the set containing `elem` and points
+    /// all elements on the way directly to it.
+    pub fn find(&mut self, elem: usize) -> usize {
+        let mut root = elem;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut elem = elem;
+        while elem != root {
+            elem = std::mem::replace(&mut self.parents[elem], root);
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns `false` if they were
+    /// the same set already.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.sizes[a] < self.sizes[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        self.count -= 1;
+        true
+    }
+
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Returns the size of the set containing `elem`.
+    pub fn set_size(&mut self, elem: usize) -> usize {
+        let root = self.find(elem);
+        self.sizes[root]
+    }
+}
@@ -0,0 +1,92 @@
+//! Stack-safe traversals of DOM-like trees whose nodes are cheap handles,
+//! such as `roxmltree::Node`. Pathologically nested markup cannot crash them.
+
+/// A node handle that can enumerate the handles of its children.
+pub trait Children: Sized {
+    type Iter: Iterator<Item = Self>;
+
+    fn children(&self) -> Self::Iter;
+}
+
+#[cfg(feature = "roxmltree")]
+impl<'a, 'input> Children for roxmltree::Node<'a, 'input> {
+    type Iter = roxmltree::Children<'a, 'input>;
+
+    fn children(&self) -> Self::Iter {
+        roxmltree::Node::children(self)
+    }
+}
+
+pub struct Descendants<N: Children> {
+    next: Option<N>,
+    stack: Vec<N::Iter>,
+}
+
+impl<N: Children> Iterator for Descendants<N> {
+    type Item = N;
+
+    fn next(&mut self) -> Option<N> {
+        if let Some(node) = self.next.take() {
+            self.stack.push(node.children());
+            return Some(node);
+        }
+        while let Some(iter) = self.stack.last_mut() {
+            match iter.next() {
+                Some(node) => {
+                    self.stack.push(node.children());
+                    return Some(node);
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Iterates over `root` and all its descendants in document order.
+pub fn descendants<N: Children>(root: N) -> Descendants<N> {
+    Descendants {
+        next: Some(root),
+        stack: Vec::new(),
+    }
+}
+
+/// The number of nodes on the longest path from `root` to a leaf.
+pub fn depth<N: Children>(root: N) -> usize {
+    let mut max_depth = 1;
+    let mut stack = vec![root.children()];
+    while let Some(iter) = stack.last_mut() {
+        match iter.next() {
+            Some(node) => {
+                stack.push(node.children());
+                max_depth = max_depth.max(stack.len());
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+    max_depth
+}
+
+/// Transforms the tree below `root` bottom-up. `f` is called on every node
+/// together with the results for its children.
+pub fn transform<N: Children, R>(root: N, mut f: impl FnMut(N, Vec<R>) -> R) -> R {
+    let mut stack = vec![(root.children(), root, Vec::new())];
+    loop {
+        let (iter, _, _) = stack.last_mut().unwrap();
+        match iter.next() {
+            Some(node) => stack.push((node.children(), node, Vec::new())),
+            None => {
+                let (_, node, results) = stack.pop().unwrap();
+                let res = f(node, results);
+                match stack.last_mut() {
+                    None => return res,
+                    Some((_, _, results)) => results.push(res),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::io;
+
+use crate::Observer;
+
+/// Records the tree of calls made during a run of `trampoline_observed` and
+/// renders it in Graphviz's DOT format. Nodes are labelled by applying `label`
+/// to the arguments of the calls.
+pub struct CallTree<L> {
+    label: L,
+    labels: Vec<String>,
+    edges: Vec<(usize, usize)>,
+    path: Vec<usize>,
+}
+
+impl<L> CallTree<L> {
+    pub fn new(label: L) -> Self {
+        Self {
+            label,
+            labels: Vec::new(),
+            edges: Vec::new(),
+            path: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn write_dot(&self, w: &mut impl io::Write) -> io::Result<()> {
+        writeln!(w, "digraph calls {{")?;
+        for (id, label) in self.labels.iter().enumerate() {
+            let label = label
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r");
+            writeln!(w, "    n{} [label=\"{}\"];", id, label)?;
+        }
+        for (parent, child) in &self.edges {
+            writeln!(w, "    n{} -> n{};", parent, child)?;
+        }
+        writeln!(w, "}}")
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut buf = Vec::new();
+        self.write_dot(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+}
+
+impl<Arg, L: Fn(&Arg) -> String> Observer<Arg> for CallTree<L> {
+    fn enter(&mut self, arg: &Arg, _depth: usize) {
+        let id = self.labels.len();
+        self.labels.push((self.label)(arg));
+        if let Some(&parent) = self.path.last() {
+            self.edges.push((parent, id));
+        }
+        self.path.push(id);
+    }
+
+    fn exit(&mut self, _depth: usize) {
+        self.path.pop();
+    }
+}
@@ -0,0 +1,61 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+/// What a frame of `trampoline_effects` yields.
+pub enum Effect<Arg, E> {
+    /// Calls the function on `Arg`, like yielding `Arg` to `trampoline`.
+    Call(Arg),
+    /// Asks the handler to perform `E`. The frame is resumed with the answer.
+    Perform(E),
+}
+
+/// Handles the effects performed by the frames of `trampoline_effects`.
+pub trait Handler<E, Res> {
+    fn handle(&mut self, effect: E) -> Res;
+}
+
+impl<E, Res, F: FnMut(E) -> Res> Handler<E, Res> for F {
+    fn handle(&mut self, effect: E) -> Res {
+        self(effect)
+    }
+}
+
+/// Like `trampoline`, but frames can also perform effects such as logging,
+/// reading input or asking the environment. The driver routes every effect
+/// to the handler passed along with the argument and resumes the frame with
+/// its answer. Results and answers share the type `Res`, which is typically
+/// an enum if they differ.
+pub fn trampoline_effects<Arg, E, Res, Gen, H>(
+    f: impl Fn(Arg) -> Gen,
+) -> impl Fn(Arg, &mut H) -> Res
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Effect<Arg, E>, Return = Res> + Unpin,
+    H: Handler<E, Res> + ?Sized,
+{
+    move |arg: Arg, handler: &mut H| {
+        let mut stack = Vec::new();
+        let mut current = f(arg);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current).resume(res) {
+                GeneratorState::Yielded(Effect::Call(arg)) => {
+                    stack.push(current);
+                    current = f(arg);
+                    res = Res::default();
+                }
+                GeneratorState::Yielded(Effect::Perform(effect)) => {
+                    res = handler.handle(effect);
+                }
+                GeneratorState::Complete(real_res) => match stack.pop() {
+                    None => return real_res,
+                    Some(top) => {
+                        current = top;
+                        res = real_res;
+                    }
+                },
+            }
+        }
+    }
+}
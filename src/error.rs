@@ -0,0 +1,145 @@
+use std::any::Any;
+use std::fmt;
+use std::io;
+use std::sync::Mutex;
+
+use crate::{BudgetExceeded, DepthExceeded};
+
+/// The ways in which running a computation with this crate can fail.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StackSafeError {
+    /// The frame stack refused to store more than `max_depth` frames.
+    DepthLimit { max_depth: usize },
+    /// The frames took up more than `budget` bytes at `depth`.
+    MemoryBudget { budget: usize, depth: usize },
+    /// The computation ran out of fuel before it finished.
+    FuelExhausted,
+    /// The computation was cancelled before it finished.
+    Cancelled,
+    /// The computation missed its deadline.
+    DeadlineExceeded,
+    /// A frame or the closure run on a big-stack thread panicked.
+    FramePanicked(PanicPayload),
+    /// The operating system failed to spawn a big-stack thread.
+    Spawn(io::Error),
+    /// `stack_probe!` detected that the native stack was about to overflow.
+    OverflowDetected {
+        depth: Option<usize>,
+        remaining: usize,
+    },
+}
+
+impl StackSafeError {
+    /// Turns the error back into a panic. The payload of a panicking frame is
+    /// resumed unchanged, all other errors panic with their message.
+    pub fn resume_unwind(self) -> ! {
+        match self {
+            Self::FramePanicked(payload) => std::panic::resume_unwind(payload.into_inner()),
+            err => panic!("{}", err),
+        }
+    }
+}
+
+impl fmt::Display for StackSafeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DepthLimit { max_depth } => write!(f, "recursion depth exceeds {}", max_depth),
+            Self::MemoryBudget { budget, depth } => write!(
+                f,
+                "frames exceed the budget of {} bytes at depth {}",
+                budget, depth
+            ),
+            Self::FuelExhausted => write!(f, "recursion ran out of fuel"),
+            Self::Cancelled => write!(f, "recursion was cancelled"),
+            Self::DeadlineExceeded => write!(f, "recursion missed its deadline"),
+            Self::FramePanicked(payload) => match payload.message() {
+                Some(message) => write!(f, "frame panicked: {}", message),
+                None => write!(f, "frame panicked"),
+            },
+            Self::Spawn(_) => write!(f, "failed to spawn thread"),
+            Self::OverflowDetected {
+                depth: Some(depth),
+                remaining,
+            } => write!(
+                f,
+                "about to overflow the stack at depth {} ({} bytes left), \
+                 consider using `stack_safe::trampoline`",
+                depth, remaining
+            ),
+            Self::OverflowDetected {
+                depth: None,
+                remaining,
+            } => write!(
+                f,
+                "about to overflow the stack ({} bytes left), \
+                 consider using `stack_safe::trampoline`",
+                remaining
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StackSafeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Spawn(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<DepthExceeded> for StackSafeError {
+    fn from(err: DepthExceeded) -> Self {
+        Self::DepthLimit {
+            max_depth: err.max_depth,
+        }
+    }
+}
+
+impl From<BudgetExceeded> for StackSafeError {
+    fn from(err: BudgetExceeded) -> Self {
+        Self::MemoryBudget {
+            budget: err.budget,
+            depth: err.depth,
+        }
+    }
+}
+
+/// The payload of a panic. Unlike the bare payload, it can be shared between
+/// threads and hence be wrapped by `anyhow` and the like.
+pub struct PanicPayload {
+    message: Option<String>,
+    payload: Mutex<Box<dyn Any + Send>>,
+}
+
+impl PanicPayload {
+    pub fn new(payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast_ref::<&str>() {
+            Some(message) => Some(message.to_string()),
+            None => payload.downcast_ref::<String>().cloned(),
+        };
+        Self {
+            message,
+            payload: Mutex::new(payload),
+        }
+    }
+
+    /// The panic message, if the payload is a string.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub fn into_inner(self) -> Box<dyn Any + Send> {
+        match self.payload.into_inner() {
+            Ok(payload) => payload,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl fmt::Debug for PanicPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PanicPayload").field(&self.message).finish()
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt;
+use std::mem;
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+use crate::checked::check_tail;
+use crate::{Call, CallKind};
+
+/// Like `trampoline`, but frames return a `Result`. The first error aborts the
+/// whole recursion and all suspended frames are dropped.
+pub fn try_recurse<Arg, Res, E, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Result<Res, E>
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Result<Res, E>> + Unpin,
+{
+    move |arg: Arg| {
+        let mut stack = Vec::new();
+        let mut current = f(arg);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current).resume(res) {
+                GeneratorState::Yielded(arg) => {
+                    stack.push(current);
+                    current = f(arg);
+                    res = Res::default();
+                }
+                GeneratorState::Complete(real_res) => {
+                    let real_res = real_res?;
+                    match stack.pop() {
+                        None => return Ok(real_res),
+                        Some(top) => {
+                            current = top;
+                            res = real_res;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Like `try_recurse`, but frames yield `Call`s and `Call::tail` replaces the
+/// caller's frame like in `trampoline_tco`, e.g., for interpreters that need
+/// both proper tail calls and error propagation.
+pub fn try_recurse_tco<Arg, Res, E, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Result<Res, E>
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Call<Arg>, Return = Result<Res, E>> + Unpin,
+{
+    move |arg: Arg| {
+        let mut stack = Vec::new();
+        let mut current = f(arg);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current).resume(res) {
+                GeneratorState::Yielded(call) => {
+                    match call.kind {
+                        CallKind::Normal(arg) => stack.push(mem::replace(&mut current, f(arg))),
+                        CallKind::Tail(arg) => {
+                            check_tail(&mut current);
+                            current = f(arg);
+                        }
+                    }
+                    res = Res::default();
+                }
+                GeneratorState::Complete(real_res) => {
+                    let real_res = real_res?;
+                    match stack.pop() {
+                        None => return Ok(real_res),
+                        Some(top) => {
+                            current = top;
+                            res = real_res;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An error together with the labels of the calls that led to it, starting
+/// from the initial call and ending with the call that failed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Traced<E, L> {
+    pub error: E,
+    pub trace: Vec<L>,
+}
+
+impl<E: fmt::Display, L: fmt::Display> fmt::Display for Traced<E, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        for label in self.trace.iter().rev() {
+            write!(f, "\n    in {}", label)?;
+        }
+        Ok(())
+    }
+}
+
+impl<E, L> std::error::Error for Traced<E, L>
+where
+    E: std::error::Error + 'static,
+    L: fmt::Debug + fmt::Display,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Like `try_recurse`, but records the label of every active call and attaches
+/// the labels of the failing call and its ancestors to the error.
+pub fn try_recurse_traced<Arg, L, Res, E, Gen>(
+    label: impl Fn(&Arg) -> L,
+    f: impl Fn(Arg) -> Gen,
+) -> impl Fn(Arg) -> Result<Res, Traced<E, L>>
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Result<Res, E>> + Unpin,
+{
+    move |arg: Arg| {
+        let mut stack = Vec::new();
+        let mut trace = vec![label(&arg)];
+        let mut current = f(arg);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current).resume(res) {
+                GeneratorState::Yielded(arg) => {
+                    trace.push(label(&arg));
+                    stack.push(current);
+                    current = f(arg);
+                    res = Res::default();
+                }
+                GeneratorState::Complete(Err(error)) => return Err(Traced { error, trace }),
+                GeneratorState::Complete(Ok(real_res)) => {
+                    trace.pop();
+                    match stack.pop() {
+                        None => return Ok(real_res),
+                        Some(top) => {
+                            current = top;
+                            res = real_res;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The result of `try_recurse_recover` together with the errors that were
+/// recovered from, in the order in which they occurred.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Recovered<Arg, Res, E> {
+    pub result: Res,
+    pub errors: Vec<(Arg, E)>,
+}
+
+/// Like `try_recurse`, but a failing call is handed to `recover` together
+/// with its argument. If `recover` substitutes a result, the caller is resumed
+/// with it and the error is recorded; otherwise the error aborts the whole
+/// recursion.
+pub fn try_recurse_recover<Arg, Res, E, Gen>(
+    f: impl Fn(Arg) -> Gen,
+    recover: impl Fn(&Arg, &E) -> Option<Res>,
+) -> impl Fn(Arg) -> Result<Recovered<Arg, Res, E>, E>
+where
+    Arg: Clone,
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Result<Res, E>> + Unpin,
+{
+    move |arg: Arg| {
+        let mut stack = Vec::new();
+        let mut errors = Vec::new();
+        let mut current = (f(arg.clone()), arg);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current.0).resume(res) {
+                GeneratorState::Yielded(arg) => {
+                    stack.push(current);
+                    current = (f(arg.clone()), arg);
+                    res = Res::default();
+                }
+                GeneratorState::Complete(real_res) => {
+                    let real_res = match real_res {
+                        Ok(real_res) => real_res,
+                        Err(error) => match recover(&current.1, &error) {
+                            Some(real_res) => {
+                                errors.push((current.1.clone(), error));
+                                real_res
+                            }
+                            None => return Err(error),
+                        },
+                    };
+                    match stack.pop() {
+                        None => {
+                            return Ok(Recovered {
+                                result: real_res,
+                                errors,
+                            })
+                        }
+                        Some(top) => {
+                            current = top;
+                            res = real_res;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::mem::MaybeUninit;
+use std::ptr;
+
+use crate::FrameStack;
+
+// The helpers below operate on the first `len` slots, which are initialized.
+
+fn push<G>(slots: &mut [MaybeUninit<G>], len: &mut usize, frame: G) -> Result<(), G> {
+    match slots.get_mut(*len) {
+        None => Err(frame),
+        Some(slot) => {
+            *slot = MaybeUninit::new(frame);
+            *len += 1;
+            Ok(())
+        }
+    }
+}
+
+fn pop<G>(slots: &mut [MaybeUninit<G>], len: &mut usize) -> Option<G> {
+    if *len == 0 {
+        return None;
+    }
+    *len -= 1;
+    // SAFETY: The slot was initialized and is now considered uninitialized.
+    Some(unsafe { ptr::read(slots[*len].as_ptr()) })
+}
+
+fn last_mut<G>(slots: &mut [MaybeUninit<G>], len: usize) -> Option<&mut G> {
+    let slot = slots.get_mut(len.checked_sub(1)?)?;
+    // SAFETY: The slot is initialized.
+    Some(unsafe { &mut *slot.as_mut_ptr() })
+}
+
+fn clear<G>(slots: &mut [MaybeUninit<G>], len: &mut usize) {
+    while pop(slots, len).is_some() {}
+}
+
+/// A frame stack in caller-provided memory. It never allocates.
+pub struct SliceStack<'a, G> {
+    slots: &'a mut [MaybeUninit<G>],
+    len: usize,
+}
+
+impl<'a, G> SliceStack<'a, G> {
+    pub fn new(slots: &'a mut [MaybeUninit<G>]) -> Self {
+        Self { slots, len: 0 }
+    }
+}
+
+impl<'a, G> FrameStack<G> for SliceStack<'a, G> {
+    fn push(&mut self, frame: G) -> Result<(), G> {
+        push(self.slots, &mut self.len, frame)
+    }
+
+    fn pop(&mut self) -> Option<G> {
+        pop(self.slots, &mut self.len)
+    }
+
+    fn last_mut(&mut self) -> Option<&mut G> {
+        last_mut(self.slots, self.len)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, G> Drop for SliceStack<'a, G> {
+    fn drop(&mut self) {
+        clear(self.slots, &mut self.len);
+    }
+}
+
+/// A frame stack for at most `N` frames stored inline. It never allocates.
+pub struct ArrayStack<G, const N: usize> {
+    slots: [MaybeUninit<G>; N],
+    len: usize,
+}
+
+impl<G, const N: usize> ArrayStack<G, N> {
+    pub fn new() -> Self {
+        Self {
+            // SAFETY: An array of `MaybeUninit` needs no initialization.
+            slots: unsafe { MaybeUninit::uninit().assume_init() },
+            len: 0,
+        }
+    }
+}
+
+impl<G, const N: usize> Default for ArrayStack<G, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G, const N: usize> FrameStack<G> for ArrayStack<G, N> {
+    fn push(&mut self, frame: G) -> Result<(), G> {
+        push(&mut self.slots, &mut self.len, frame)
+    }
+
+    fn pop(&mut self) -> Option<G> {
+        pop(&mut self.slots, &mut self.len)
+    }
+
+    fn last_mut(&mut self) -> Option<&mut G> {
+        last_mut(&mut self.slots, self.len)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<G, const N: usize> Drop for ArrayStack<G, N> {
+    fn drop(&mut self) {
+        clear(&mut self.slots, &mut self.len);
+    }
+}
@@ -0,0 +1,52 @@
+use std::cell::RefCell;
+
+use crate::recurse_owned;
+
+/// A node of a flattened tree. Its children are referred to by their index
+/// in the containing `Vec`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlatNode<N> {
+    pub value: N,
+    pub children: Vec<usize>,
+}
+
+/// Converts a pointer-based tree into a `Vec` of nodes with index children.
+/// `unfold` splits an argument into a node value and its children. The nodes
+/// are stored in post-order, so every node comes after its children and the
+/// root is the last node.
+pub fn flatten<Arg, N, Children>(
+    root: Arg,
+    unfold: impl Fn(Arg) -> (N, Children),
+) -> Vec<FlatNode<N>>
+where
+    Children: IntoIterator<Item = Arg>,
+{
+    let nodes = RefCell::new(Vec::new());
+    recurse_owned(unfold, |value, children| {
+        let mut nodes = nodes.borrow_mut();
+        nodes.push(FlatNode { value, children });
+        nodes.len() - 1
+    })(root);
+    nodes.into_inner()
+}
+
+/// Reconstructs the tree below `nodes[root]` with `build`, which puts a node
+/// together from its value and its reconstructed children.
+///
+/// # Panics
+///
+/// Panics if a node below `root` is reachable in more than one way, i.e., if
+/// `nodes` does not form a tree.
+pub fn unflatten<N, T>(nodes: Vec<FlatNode<N>>, root: usize, build: impl Fn(N, Vec<T>) -> T) -> T {
+    let nodes = RefCell::new(nodes.into_iter().map(Some).collect::<Vec<_>>());
+    let res = recurse_owned(
+        |index: usize| {
+            let node = nodes.borrow_mut()[index]
+                .take()
+                .expect("flattened nodes do not form a tree");
+            (node.value, node.children)
+        },
+        build,
+    )(root);
+    res
+}
@@ -0,0 +1,122 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+use crate::{Never, Partial, Stop};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Order {
+    Pre,
+    Post,
+}
+
+/// Threads an accumulator through a traversal. Frames only yield their
+/// children; `step` is applied to every argument either before its children
+/// are visited (`Order::Pre`) or after all of them have been (`Order::Post`).
+pub fn recurse_fold<Arg, Acc, Gen>(
+    f: impl Fn(Arg) -> Gen,
+    order: Order,
+    step: impl Fn(Acc, &Arg) -> Acc,
+) -> impl Fn(Arg, Acc) -> Acc
+where
+    Arg: Clone,
+    Gen: Generator<(), Yield = Arg, Return = ()> + Unpin,
+{
+    move |arg: Arg, acc: Acc| match drive_fold(&f, order, &step, arg, acc, &mut Never) {
+        Ok(acc) => acc,
+        Err(_) => unreachable!(),
+    }
+}
+
+/// Like `recurse_fold`, but checks `stop` before every step. If it stops the
+/// traversal, the accumulator is returned as it is at that point, i.e., it
+/// has seen exactly the arguments that were stepped before.
+pub fn recurse_fold_until<Arg, Acc, Gen, S>(
+    f: impl Fn(Arg) -> Gen,
+    order: Order,
+    step: impl Fn(Acc, &Arg) -> Acc,
+) -> impl Fn(Arg, Acc, &mut S) -> Result<Acc, Partial<Acc>>
+where
+    Arg: Clone,
+    Gen: Generator<(), Yield = Arg, Return = ()> + Unpin,
+    S: Stop + ?Sized,
+{
+    move |arg: Arg, acc: Acc, stop: &mut S| drive_fold(&f, order, &step, arg, acc, stop)
+}
+
+fn drive_fold<Arg, Acc, Gen, S>(
+    f: &impl Fn(Arg) -> Gen,
+    order: Order,
+    step: &impl Fn(Acc, &Arg) -> Acc,
+    arg: Arg,
+    mut acc: Acc,
+    stop: &mut S,
+) -> Result<Acc, Partial<Acc>>
+where
+    Arg: Clone,
+    Gen: Generator<(), Yield = Arg, Return = ()> + Unpin,
+    S: Stop + ?Sized,
+{
+    let mut stack = Vec::new();
+    if let Some(reason) = stop.check() {
+        return Err(Partial { value: acc, reason });
+    }
+    if order == Order::Pre {
+        acc = step(acc, &arg);
+    }
+    let mut current = (f(arg.clone()), arg);
+
+    loop {
+        if let Some(reason) = stop.check() {
+            return Err(Partial { value: acc, reason });
+        }
+        match Pin::new(&mut current.0).resume(()) {
+            GeneratorState::Yielded(arg) => {
+                if order == Order::Pre {
+                    acc = step(acc, &arg);
+                }
+                stack.push(current);
+                current = (f(arg.clone()), arg);
+            }
+            GeneratorState::Complete(()) => {
+                if order == Order::Post {
+                    acc = step(acc, &current.1);
+                }
+                match stack.pop() {
+                    None => return Ok(acc),
+                    Some(top) => current = top,
+                }
+            }
+        }
+    }
+}
+
+/// Folds over a recursive structure using an explicit stack instead of
+/// generators. The result of a node is `leaf(node)` combined with the results
+/// of its children from left to right, i.e.
+/// `combine(combine(leaf(node), res(child_1)), res(child_2))` and so on.
+pub fn fold_tree<'a, T, I, R>(
+    root: &'a T,
+    children: impl Fn(&'a T) -> I,
+    leaf: impl Fn(&'a T) -> R,
+    combine: impl Fn(R, R) -> R,
+) -> R
+where
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut stack = Vec::new();
+    let mut acc = leaf(root);
+    let mut iter = children(root).into_iter();
+
+    loop {
+        if let Some(child) = iter.next() {
+            stack.push((acc, iter));
+            acc = leaf(child);
+            iter = children(child).into_iter();
+        } else if let Some((parent_acc, parent_iter)) = stack.pop() {
+            acc = combine(parent_acc, acc);
+            iter = parent_iter;
+        } else {
+            return acc;
+        }
+    }
+}
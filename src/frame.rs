@@ -0,0 +1,50 @@
+use std::ops::Generator;
+
+use crate::trampoline;
+
+/// The generators that can serve as frames of a recursion from `Arg` to
+/// `Res`. Shorthand for `Generator<Res, Yield = Arg, Return = Res> + Unpin`,
+/// e.g., in `impl FrameGen<u64, u64>`.
+pub trait FrameGen<Arg, Res>: Generator<Res, Yield = Arg, Return = Res> + Unpin {}
+
+impl<Arg, Res, Gen> FrameGen<Arg, Res> for Gen where
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin
+{
+}
+
+/// A boxed frame, for factories that return different kinds of frames.
+pub type BoxedFrame<'a, Arg, Res> = Box<dyn FrameGen<Arg, Res> + 'a>;
+
+/// The functions that create the frame for an argument.
+pub trait FrameFactory<Arg, Res> {
+    type Gen: FrameGen<Arg, Res>;
+
+    fn call(&self, arg: Arg) -> Self::Gen;
+}
+
+impl<Arg, Res, F, Gen> FrameFactory<Arg, Res> for F
+where
+    F: Fn(Arg) -> Gen,
+    Gen: FrameGen<Arg, Res>,
+{
+    type Gen = Gen;
+
+    fn call(&self, arg: Arg) -> Gen {
+        self(arg)
+    }
+}
+
+/// Like `trampoline`, but with the argument and result types first, so they
+/// can be given explicitly when inference fails:
+///
+/// ```ignore
+/// let triangular = recurse_fn::<u64, u64, _>(|n| move |_| ...);
+/// ```
+#[must_use = "`recurse_fn` returns the stack-safe function without calling it"]
+pub fn recurse_fn<Arg, Res, F>(f: F) -> impl Fn(Arg) -> Res
+where
+    Res: Default,
+    F: FrameFactory<Arg, Res>,
+{
+    trampoline(move |arg: Arg| f.call(arg))
+}
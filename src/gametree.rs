@@ -0,0 +1,243 @@
+//! Alpha-beta search over game trees.
+//!
+//! Users describe a two-player zero-sum game by implementing `Game` for its
+//! positions. The search is a negamax with alpha-beta pruning: scores are
+//! always from the perspective of the player to move. Every node of the
+//! search is a frame, so the depth of the search is only bounded by memory.
+//!
+//! A transposition table remembers the scores of positions that have been
+//! searched before, e.g., when different move orders lead to the same
+//! position. Any `Cache` from positions to `Entry`s works as one, e.g., a
+//! `HashMap`. Keeping the table between the iterations of
+//! `iterative_deepening` lets later iterations reuse earlier results.
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+use crate::{Cache, Never, StackSafeError, Stop};
+
+pub trait Game: Clone {
+    type Move: Clone;
+
+    /// Returns the legal moves in this position. The game is over if there
+    /// are none.
+    fn moves(&self) -> Vec<Self::Move>;
+
+    fn play(&self, mv: &Self::Move) -> Self;
+
+    /// Scores this position from the perspective of the player to move.
+    fn evaluate(&self) -> i32;
+}
+
+/// How the score of an `Entry` relates to the actual score of its position.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Bound {
+    Exact,
+    /// The actual score is at least the stored one.
+    Lower,
+    /// The actual score is at most the stored one.
+    Upper,
+}
+
+/// An entry of a transposition table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// The depth to which the position has been searched.
+    pub depth: u32,
+    pub score: i32,
+    pub bound: Bound,
+}
+
+/// A transposition table that remembers nothing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoTable;
+
+impl<G> Cache<G, Entry> for NoTable {
+    fn get(&mut self, _position: &G) -> Option<Entry> {
+        None
+    }
+
+    fn insert(&mut self, _position: G, _entry: Entry) {}
+}
+
+/// The result of a search from the perspective of the player to move at
+/// the root.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Outcome<M> {
+    pub score: i32,
+    /// The best move, unless the game is over at the root or the depth is 0.
+    pub best: Option<M>,
+    pub depth: u32,
+}
+
+/// Scores are kept within `-INFINITY..=INFINITY` so they can be negated.
+const INFINITY: i32 = i32::MAX;
+
+#[derive(Clone)]
+struct Node<G> {
+    position: G,
+    depth: u32,
+    alpha: i32,
+    beta: i32,
+}
+
+/// The frame of a node. It is resumed with the scores of its children and
+/// returns its own score together with the index of its best move.
+fn node<G: Game>(
+    node: Node<G>,
+) -> impl Generator<i32, Yield = Node<G>, Return = (i32, Option<usize>)> {
+    move |_: i32| {
+        let Node {
+            position,
+            depth,
+            mut alpha,
+            beta,
+        } = node;
+        let moves = position.moves();
+        if depth == 0 || moves.is_empty() {
+            return (position.evaluate(), None);
+        }
+        let mut best: Option<(i32, usize)> = None;
+        for (index, mv) in moves.into_iter().enumerate() {
+            let child = Node {
+                position: position.play(&mv),
+                depth: depth - 1,
+                alpha: -beta,
+                beta: -alpha,
+            };
+            let score = -(yield child);
+            if !matches!(best, Some((best, _)) if best >= score) {
+                best = Some((score, index));
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        let (score, index) = best.unwrap();
+        (score, Some(index))
+    }
+}
+
+/// Returns the score of `node` if `table` knows it well enough.
+fn probe<G, C>(table: &mut C, node: &Node<G>) -> Option<i32>
+where
+    C: Cache<G, Entry> + ?Sized,
+{
+    let entry = table.get(&node.position)?;
+    if entry.depth < node.depth {
+        return None;
+    }
+    match entry.bound {
+        Bound::Exact => Some(entry.score),
+        Bound::Lower if entry.score >= node.beta => Some(entry.score),
+        Bound::Upper if entry.score <= node.alpha => Some(entry.score),
+        _ => None,
+    }
+}
+
+fn record<G, C>(table: &mut C, node: Node<G>, score: i32)
+where
+    C: Cache<G, Entry> + ?Sized,
+{
+    let bound = if score <= node.alpha {
+        Bound::Upper
+    } else if score >= node.beta {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    };
+    let entry = Entry {
+        depth: node.depth,
+        score,
+        bound,
+    };
+    table.insert(node.position, entry);
+}
+
+/// Searches `root` to `depth` moves.
+pub fn alpha_beta<G: Game>(root: &G, depth: u32) -> Outcome<G::Move> {
+    match alpha_beta_with(root, depth, &mut NoTable, &mut Never) {
+        Ok(outcome) => outcome,
+        Err(_) => unreachable!("the search never stops"),
+    }
+}
+
+/// Like `alpha_beta`, but with a transposition table, and `stop` is checked
+/// before every step of the search.
+pub fn alpha_beta_with<G, C, S>(
+    root: &G,
+    depth: u32,
+    table: &mut C,
+    stop: &mut S,
+) -> Result<Outcome<G::Move>, StackSafeError>
+where
+    G: Game,
+    C: Cache<G, Entry> + ?Sized,
+    S: Stop + ?Sized,
+{
+    let frame = |arg: Node<G>| (node(arg.clone()), arg);
+    let mut stack = Vec::new();
+    let mut current = frame(Node {
+        position: root.clone(),
+        depth,
+        alpha: -INFINITY,
+        beta: INFINITY,
+    });
+    let mut res = 0;
+
+    loop {
+        if let Some(err) = stop.check() {
+            return Err(err);
+        }
+        match Pin::new(&mut current.0).resume(res) {
+            GeneratorState::Yielded(child) => match probe(table, &child) {
+                Some(score) => res = score,
+                None => {
+                    stack.push(current);
+                    current = frame(child);
+                    res = 0;
+                }
+            },
+            GeneratorState::Complete((score, best)) => match stack.pop() {
+                None => {
+                    record(table, current.1, score);
+                    return Ok(Outcome {
+                        score,
+                        best: best.map(|index| root.moves()[index].clone()),
+                        depth,
+                    });
+                }
+                Some(top) => {
+                    let (_, node) = std::mem::replace(&mut current, top);
+                    record(table, node, score);
+                    res = score;
+                }
+            },
+        }
+    }
+}
+
+/// Searches `root` to the depths `1, 2, ..., max_depth` and returns the
+/// outcome of the deepest search that finished before `stop` stopped the
+/// search, if any. The entries `table` gains in one iteration are reused by
+/// the next.
+pub fn iterative_deepening<G, C, S>(
+    root: &G,
+    max_depth: u32,
+    table: &mut C,
+    stop: &mut S,
+) -> Option<Outcome<G::Move>>
+where
+    G: Game,
+    C: Cache<G, Entry> + ?Sized,
+    S: Stop + ?Sized,
+{
+    let mut outcome = None;
+    for depth in 1..=max_depth {
+        match alpha_beta_with(root, depth, table, stop) {
+            Ok(deeper) => outcome = Some(deeper),
+            Err(_) => break,
+        }
+    }
+    outcome
+}
@@ -0,0 +1,354 @@
+//! Stack-safe algorithms on directed graphs in adjacency list form.
+use std::cmp::min;
+use std::collections::{BTreeSet, HashSet};
+use std::hash::{BuildHasher, Hash};
+use std::ops::Range;
+
+use crate::disjoint_set::DisjointSet;
+use crate::trampoline_mut;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Node {
+    pub id: usize,
+}
+
+impl Node {
+    pub const fn new(id: usize) -> Self {
+        Self { id }
+    }
+}
+
+pub type Graph = Vec<Vec<Node>>;
+
+pub type SCCs = Vec<Vec<Node>>;
+
+/// The index of a strongly connected component in the result of `tarjan`.
+pub type ComponentId = usize;
+
+struct State {
+    index: usize,
+    indices: Vec<usize>,
+    lowlinks: Vec<usize>,
+    components: SCCs,
+    stack: Vec<Node>,
+    on_stack: Vec<bool>,
+}
+
+fn dfs<'a>(v: Node, graph: &'a Graph, s: &'a mut State) {
+    trampoline_mut(|(v, graph): (Node, &'a Graph)| {
+        move |(_, mut s): ((), &'a mut State)| {
+            s.indices[v.id] = s.index;
+            s.lowlinks[v.id] = s.index;
+            s.index += 1;
+            s.stack.push(v);
+            s.on_stack[v.id] = true;
+
+            for &w in &graph[v.id] {
+                if s.indices[w.id] == usize::MAX {
+                    ((), s) = yield ((w, graph), s);
+                    s.lowlinks[v.id] = min(s.lowlinks[v.id], s.lowlinks[w.id]);
+                } else if s.on_stack[w.id] {
+                    s.lowlinks[v.id] = min(s.lowlinks[v.id], s.indices[w.id]);
+                }
+            }
+
+            if s.lowlinks[v.id] == s.indices[v.id] {
+                let mut component = Vec::new();
+                let mut w = Node::new(usize::MAX);
+                while w != v {
+                    w = s.stack.pop().unwrap();
+                    s.on_stack[w.id] = false;
+                    component.push(w)
+                }
+                s.components.push(component);
+            }
+            ((), s)
+        }
+    })((v, graph), s)
+}
+
+/// Computes the strongly connected components of `graph` with Tarjan's
+/// algorithm, starting from the nodes in increasing order. The components
+/// are returned in reverse topological order.
+pub fn tarjan(graph: &Graph) -> SCCs {
+    let n = graph.len();
+    let mut s = State {
+        index: 0,
+        indices: vec![usize::MAX; n],
+        lowlinks: vec![usize::MAX; n],
+        components: Vec::new(),
+        stack: Vec::new(),
+        on_stack: vec![false; n],
+    };
+
+    for id in 0..n {
+        if s.indices[id] == usize::MAX {
+            dfs(Node::new(id), graph, &mut s);
+        }
+    }
+
+    s.components
+}
+
+/// The strongly connected components of a graph together with the graph
+/// obtained by contracting every component into a single node.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Condensation {
+    /// The components as returned by `tarjan`.
+    pub sccs: SCCs,
+    /// The component of every node of the original graph.
+    pub components: Vec<ComponentId>,
+    /// The acyclic graph on the components. Node `i` stands for `sccs[i]`.
+    /// Edges only point from components to components with smaller ids and
+    /// are not duplicated.
+    pub dag: Graph,
+}
+
+/// Like `tarjan`, but also computes the condensation of `graph`.
+pub fn tarjan_condensed(graph: &Graph) -> Condensation {
+    let sccs = tarjan(graph);
+    let mut components = vec![0; graph.len()];
+    for (component, scc) in sccs.iter().enumerate() {
+        for v in scc {
+            components[v.id] = component;
+        }
+    }
+
+    // `last_seen[c] == component` means the edge to `c` has been added.
+    let mut last_seen = vec![usize::MAX; sccs.len()];
+    let dag = sccs
+        .iter()
+        .enumerate()
+        .map(|(component, scc)| {
+            let mut edges = Vec::new();
+            for v in scc {
+                for w in &graph[v.id] {
+                    let target = components[w.id];
+                    if target != component && last_seen[target] != component {
+                        last_seen[target] = component;
+                        edges.push(Node::new(target));
+                    }
+                }
+            }
+            edges
+        })
+        .collect();
+
+    Condensation {
+        sccs,
+        components,
+        dag,
+    }
+}
+
+/// Computes the weakly connected components of `graph`, i.e., the connected
+/// components when ignoring the direction of the edges. The components are
+/// ordered by their smallest nodes and list their nodes in increasing order.
+pub fn weak_components(graph: &Graph) -> Vec<Vec<Node>> {
+    let mut sets = DisjointSet::new(graph.len());
+    for (v, edges) in graph.iter().enumerate() {
+        for w in edges {
+            sets.union(v, w.id);
+        }
+    }
+    let mut ids = vec![usize::MAX; graph.len()];
+    let mut components: Vec<Vec<Node>> = Vec::with_capacity(sets.count());
+    for v in 0..graph.len() {
+        let root = sets.find(v);
+        if ids[root] == usize::MAX {
+            ids[root] = components.len();
+            components.push(Vec::new());
+        }
+        components[ids[root]].push(Node::new(v));
+    }
+    components
+}
+
+/// Computes a minimum spanning forest of the undirected graph with the
+/// nodes `0..len` and the weighted `edges` with Kruskal's algorithm. The
+/// chosen edges are returned in order of increasing weight.
+pub fn kruskal<W: Ord + Copy>(len: usize, edges: &[(Node, Node, W)]) -> Vec<(Node, Node, W)> {
+    let mut edges = edges.to_vec();
+    edges.sort_by_key(|&(_, _, weight)| weight);
+    let mut sets = DisjointSet::new(len);
+    edges
+        .into_iter()
+        .filter(|&(v, w, _)| sets.union(v.id, w.id))
+        .collect()
+}
+
+/// The result of `euler_tour`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EulerTour {
+    /// The nodes in the order of the walk: every node is listed when it is
+    /// entered and again after each of its children has been left, i.e.,
+    /// `2n - 1` entries for a tree with `n` nodes.
+    pub tour: Vec<Node>,
+    /// The nodes in pre-order.
+    pub order: Vec<Node>,
+    /// The position of every node in `order`. `usize::MAX` for nodes that are
+    /// not reachable from the root.
+    pub entry: Vec<usize>,
+    /// The position in `order` right after the subtree of every node.
+    pub exit: Vec<usize>,
+    /// The position of the first occurrence of every node in `tour`.
+    pub first: Vec<usize>,
+}
+
+impl EulerTour {
+    /// The positions in `order` of the nodes in the subtree of `v`.
+    pub fn subtree(&self, v: Node) -> Range<usize> {
+        self.entry[v.id]..self.exit[v.id]
+    }
+
+    /// Whether `u` is an ancestor of `v`, including `u == v`.
+    pub fn is_ancestor(&self, u: Node, v: Node) -> bool {
+        self.entry[u.id] <= self.entry[v.id] && self.exit[v.id] <= self.exit[u.id]
+    }
+}
+
+/// Walks the tree `tree` from `root` and records its Euler tour, entry and
+/// exit times. Edges back to a node's parent are ignored, so `tree` can list
+/// the edges in both directions.
+pub fn euler_tour(tree: &Graph, root: Node) -> EulerTour {
+    let n = tree.len();
+    let mut tour = Vec::with_capacity(2 * n);
+    let mut order = Vec::with_capacity(n);
+    let mut entry = vec![usize::MAX; n];
+    let mut exit = vec![usize::MAX; n];
+    let mut first = vec![usize::MAX; n];
+
+    // Every entry is a node, its parent and the index of its next child.
+    let mut stack = vec![(root, None, 0)];
+    entry[root.id] = 0;
+    first[root.id] = 0;
+    order.push(root);
+    tour.push(root);
+    while let Some((v, parent, next)) = stack.last_mut() {
+        match tree[v.id].get(*next) {
+            Some(&w) => {
+                *next += 1;
+                if Some(w) != *parent {
+                    entry[w.id] = order.len();
+                    first[w.id] = tour.len();
+                    order.push(w);
+                    tour.push(w);
+                    let v = *v;
+                    stack.push((w, Some(v), 0));
+                }
+            }
+            None => {
+                exit[v.id] = order.len();
+                stack.pop();
+                if let Some(&(u, _, _)) = stack.last() {
+                    tour.push(u);
+                }
+            }
+        }
+    }
+
+    EulerTour {
+        tour,
+        order,
+        entry,
+        exit,
+        first,
+    }
+}
+
+/// The set of nodes `implicit_dfs` has already discovered.
+pub trait Visited<N> {
+    /// Marks `node` as visited. Returns `false` if it was visited before.
+    fn insert(&mut self, node: &N) -> bool;
+}
+
+impl<N: Clone + Eq + Hash, S: BuildHasher> Visited<N> for HashSet<N, S> {
+    fn insert(&mut self, node: &N) -> bool {
+        HashSet::insert(self, node.clone())
+    }
+}
+
+impl<N: Clone + Ord> Visited<N> for BTreeSet<N> {
+    fn insert(&mut self, node: &N) -> bool {
+        BTreeSet::insert(self, node.clone())
+    }
+}
+
+/// A bitset over dense node ids.
+impl Visited<usize> for Vec<bool> {
+    fn insert(&mut self, node: &usize) -> bool {
+        !std::mem::replace(&mut self[*node], true)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Event<N> {
+    /// A node is discovered, i.e., entered in pre-order.
+    Enter(N),
+    /// All nodes reachable from a node have been discovered, i.e., the node
+    /// is left in post-order.
+    Exit(N),
+}
+
+/// The iterator returned by `implicit_dfs`.
+pub struct ImplicitDfs<'v, N, I, F> {
+    neighbors: F,
+    visited: &'v mut dyn Visited<N>,
+    entering: Option<N>,
+    stack: Vec<(N, I)>,
+}
+
+/// Searches the graph defined by `neighbors` depth-first from `start`,
+/// without ever materializing it. `visited` decides which nodes have been
+/// seen already. It can be any set, including approximate ones like bloom
+/// filters, which make the search skip nodes on false positives.
+pub fn implicit_dfs<'v, N, I, F>(
+    start: N,
+    neighbors: F,
+    visited: &'v mut dyn Visited<N>,
+) -> ImplicitDfs<'v, N, I::IntoIter, F>
+where
+    N: Clone,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+{
+    let entering = if visited.insert(&start) {
+        Some(start)
+    } else {
+        None
+    };
+    ImplicitDfs {
+        neighbors,
+        visited,
+        entering,
+        stack: Vec::new(),
+    }
+}
+
+impl<'v, N, I, F> Iterator for ImplicitDfs<'v, N, I::IntoIter, F>
+where
+    N: Clone,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+{
+    type Item = Event<N>;
+
+    fn next(&mut self) -> Option<Event<N>> {
+        loop {
+            if let Some(node) = self.entering.take() {
+                let neighbors = (self.neighbors)(&node).into_iter();
+                self.stack.push((node.clone(), neighbors));
+                return Some(Event::Enter(node));
+            }
+            let (_, neighbors) = self.stack.last_mut()?;
+            match neighbors.next() {
+                Some(node) => {
+                    if self.visited.insert(&node) {
+                        self.entering = Some(node);
+                    }
+                }
+                None => return self.stack.pop().map(|(node, _)| Event::Exit(node)),
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+
+use crate::{recurse_with_cache, trampoline, ByPtr};
+
+/// Computes Merkle-style hashes of deep structures without recursing on
+/// their depth. The hash of a node combines the hash of its own data with
+/// the hashes of its children, so structurally equal subtrees get equal
+/// hashes. This makes them usable for deduplication, change detection and
+/// as memo keys without comparing whole subtrees.
+///
+/// With `cached`, the hash of every node is remembered by its address. The
+/// nodes stay borrowed for `'a`, so their addresses cannot be reused. When
+/// a persistent structure shares unchanged subtrees between versions,
+/// hashing a new version only visits the nodes that are new.
+pub struct StructuralHasher<'a, T: ?Sized, S = BuildHasherDefault<DefaultHasher>> {
+    build: S,
+    cache: Option<HashMap<ByPtr<&'a T>, u64>>,
+}
+
+impl<'a, T: ?Sized> StructuralHasher<'a, T> {
+    /// Creates a hasher whose hashes are deterministic.
+    pub fn new() -> Self {
+        Self::with_hasher(BuildHasherDefault::default())
+    }
+}
+
+impl<'a, T: ?Sized> Default for StructuralHasher<'a, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T: ?Sized, S: BuildHasher> StructuralHasher<'a, T, S> {
+    pub fn with_hasher(build: S) -> Self {
+        Self { build, cache: None }
+    }
+
+    /// Remembers the hash of every node.
+    pub fn cached(mut self) -> Self {
+        self.cache.get_or_insert_with(HashMap::new);
+        self
+    }
+
+    /// Returns the hash of the tree below `root`. `shallow_hash` feeds the
+    /// data of a single node, but not of its children, into the hasher.
+    pub fn hash<I>(
+        &mut self,
+        root: &'a T,
+        children: impl Fn(&'a T) -> I,
+        shallow_hash: impl Fn(&'a T, &mut S::Hasher),
+    ) -> u64
+    where
+        I: IntoIterator<Item = &'a T>,
+    {
+        let build = &self.build;
+        let children = &children;
+        let shallow_hash = &shallow_hash;
+        let frame = |node: ByPtr<&'a T>| {
+            move |_: u64| {
+                let mut hasher = build.build_hasher();
+                shallow_hash(node.0, &mut hasher);
+                let mut count = 0;
+                for child in children(node.0) {
+                    let hash = yield ByPtr(child);
+                    hasher.write_u64(hash);
+                    count += 1;
+                }
+                hasher.write_usize(count);
+                hasher.finish()
+            }
+        };
+        match &mut self.cache {
+            Some(cache) => recurse_with_cache(frame, cache)(ByPtr(root)),
+            None => trampoline(frame)(ByPtr(root)),
+        }
+    }
+
+    /// Returns the remembered hash of `node`, if any.
+    pub fn get(&self, node: &'a T) -> Option<u64> {
+        self.cache.as_ref()?.get(&ByPtr(node)).copied()
+    }
+
+    /// Forgets all remembered hashes.
+    pub fn clear(&mut self) {
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// The formatted labels of every active `trampoline_with_labels` call on
+    /// this thread, outermost first. Only maintained once the hook is
+    /// installed.
+    static PATHS: RefCell<Vec<Vec<String>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Installs a panic hook that prints the labels of the calls active in all
+/// running `trampoline_with_labels` and `trampoline_with_path` drivers and
+/// functions built with `Recursion` on the panicking thread after the output
+/// of the previously installed hook.
+///
+/// The native backtrace of a panicking frame only shows the driver loop. The
+/// logical backtrace shows which calls led to the panic instead. Recording
+/// it costs formatting every label, so it only happens after this function
+/// has been called.
+pub fn install_panic_hook() {
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+        if let Some(backtrace) = logical_backtrace() {
+            eprint!("{}", backtrace);
+        }
+    }));
+}
+
+/// Formats the labels recorded for the current thread, if there are any.
+pub(crate) fn logical_backtrace() -> Option<String> {
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    let _ = PATHS.try_with(|paths| {
+        if let Ok(paths) = paths.try_borrow() {
+            for path in paths.iter().filter(|path| !path.is_empty()) {
+                out.push_str("logical recursion backtrace (innermost call last):\n");
+                for (depth, label) in path.iter().enumerate() {
+                    let _ = writeln!(out, "{:>6}: {}", depth, label);
+                }
+            }
+        }
+    });
+    if out.is_empty() {
+        None
+    } else {
+        Some(out)
+    }
+}
+
+/// Records the labels of one driver in `PATHS` for as long as it is alive.
+pub(crate) struct PathRecorder {
+    enabled: bool,
+}
+
+impl PathRecorder {
+    pub fn new() -> Self {
+        let enabled = INSTALLED.load(Ordering::Relaxed);
+        if enabled {
+            PATHS.with(|paths| paths.borrow_mut().push(Vec::new()));
+        }
+        Self { enabled }
+    }
+
+    pub fn push(&self, label: &impl fmt::Debug) {
+        if self.enabled {
+            PATHS.with(|paths| {
+                paths
+                    .borrow_mut()
+                    .last_mut()
+                    .unwrap()
+                    .push(format!("{:?}", label))
+            });
+        }
+    }
+
+    pub fn pop(&self) {
+        if self.enabled {
+            PATHS.with(|paths| paths.borrow_mut().last_mut().unwrap().pop());
+        }
+    }
+}
+
+impl Drop for PathRecorder {
+    fn drop(&mut self) {
+        if self.enabled {
+            let _ = PATHS.try_with(|paths| paths.borrow_mut().pop());
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Huffman coding on top of the stack-safe drivers.
+//!
+//! Huffman trees are as deep as the frequencies are skewed. Symbols whose
+//! frequencies grow like the Fibonacci numbers produce a tree with one more
+//! level per symbol, which overflows the stack of recursive implementations
+//! once codes get long. None of the functions here recurse.
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::{trampoline, trampoline_mut};
+
+pub type Symbol = u32;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Tree {
+    Leaf(Symbol),
+    /// The subtrees for the bits `false` and `true`.
+    Node(Box<Tree>, Box<Tree>),
+}
+
+impl Drop for Tree {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        if let Tree::Node(zero, one) = self {
+            stack.push(std::mem::replace(zero.as_mut(), Tree::Leaf(0)));
+            stack.push(std::mem::replace(one.as_mut(), Tree::Leaf(0)));
+        }
+        while let Some(mut tree) = stack.pop() {
+            if let Tree::Node(zero, one) = &mut tree {
+                stack.push(std::mem::replace(zero.as_mut(), Tree::Leaf(0)));
+                stack.push(std::mem::replace(one.as_mut(), Tree::Leaf(0)));
+            }
+        }
+    }
+}
+
+/// Builds the Huffman tree for the given symbol frequencies. Returns `None`
+/// if there are no symbols. Ties are broken in favour of the subtree that
+/// was created first. The frequencies of subtrees are summed up as `u128`,
+/// which cannot overflow.
+pub fn build(frequencies: &[(Symbol, u64)]) -> Option<Tree> {
+    let mut heap = BinaryHeap::new();
+    let mut trees = Vec::new();
+    for &(symbol, frequency) in frequencies {
+        heap.push(Reverse((u128::from(frequency), trees.len())));
+        trees.push(Some(Tree::Leaf(symbol)));
+    }
+    while let Some(Reverse((frequency1, index1))) = heap.pop() {
+        match heap.pop() {
+            None => return trees[index1].take(),
+            Some(Reverse((frequency2, index2))) => {
+                let zero = trees[index1].take().unwrap();
+                let one = trees[index2].take().unwrap();
+                heap.push(Reverse((frequency1 + frequency2, trees.len())));
+                trees.push(Some(Tree::Node(Box::new(zero), Box::new(one))));
+            }
+        }
+    }
+    None
+}
+
+/// The tree Huffman's algorithm builds for `n > 0` symbols whose frequencies
+/// grow like the Fibonacci numbers, without the frequencies, which would
+/// overflow any integer type for large `n`. For `n > 1`, symbol `i > 1` is
+/// encoded by `n - 1 - i` ones followed by a zero, symbols `0` and `1` by
+/// `n - 2` ones followed by a zero and a one, respectively.
+pub fn skewed(n: Symbol) -> Tree {
+    if n == 1 {
+        return Tree::Leaf(0);
+    }
+    let mut tree = Tree::Node(Box::new(Tree::Leaf(0)), Box::new(Tree::Leaf(1)));
+    for symbol in 2..n {
+        tree = Tree::Node(Box::new(Tree::Leaf(symbol)), Box::new(tree));
+    }
+    tree
+}
+
+/// The length of the longest code in `tree`.
+pub fn depth(tree: &Tree) -> usize {
+    trampoline(|tree: &Tree| {
+        move |_: usize| match tree {
+            Tree::Leaf(_) => 0,
+            Tree::Node(zero, one) => {
+                let zero = yield zero.as_ref();
+                let one = yield one.as_ref();
+                1 + zero.max(one)
+            }
+        }
+    })(tree)
+}
+
+pub type CodeTable = HashMap<Symbol, Vec<bool>>;
+
+fn collect_codes<'a>(tree: &'a Tree, state: &'a mut (CodeTable, Vec<bool>)) {
+    trampoline_mut(|tree: &'a Tree| {
+        move |(_, mut state): ((), &'a mut (CodeTable, Vec<bool>))| {
+            match tree {
+                Tree::Leaf(symbol) => {
+                    state.0.insert(*symbol, state.1.clone());
+                }
+                Tree::Node(zero, one) => {
+                    state.1.push(false);
+                    ((), state) = yield (zero.as_ref(), state);
+                    *state.1.last_mut().unwrap() = true;
+                    ((), state) = yield (one.as_ref(), state);
+                    state.1.pop();
+                }
+            }
+            ((), state)
+        }
+    })(tree, state)
+}
+
+/// The code of every symbol in `tree`. A tree consisting of a single leaf
+/// encodes its symbol with a single `false`.
+pub fn code_table(tree: &Tree) -> CodeTable {
+    let mut state = (HashMap::new(), Vec::new());
+    match tree {
+        Tree::Leaf(symbol) => {
+            state.0.insert(*symbol, vec![false]);
+        }
+        Tree::Node(..) => collect_codes(tree, &mut state),
+    }
+    state.0
+}
+
+/// Encodes `symbols` with `table`. Returns `None` if a symbol has no code.
+pub fn encode(table: &CodeTable, symbols: &[Symbol]) -> Option<Vec<bool>> {
+    let mut bits = Vec::new();
+    for symbol in symbols {
+        bits.extend_from_slice(table.get(symbol)?);
+    }
+    Some(bits)
+}
+
+/// Decodes `bits` with `tree`. Returns `None` if `bits` ends in the middle
+/// of a code.
+pub fn decode(tree: &Tree, bits: &[bool]) -> Option<Vec<Symbol>> {
+    let mut symbols = Vec::new();
+    if let Tree::Leaf(symbol) = tree {
+        symbols.resize(bits.len(), *symbol);
+        return Some(symbols);
+    }
+    let mut node = tree;
+    for &bit in bits {
+        if let Tree::Node(zero, one) = node {
+            node = if bit { one } else { zero };
+        }
+        if let Tree::Leaf(symbol) = node {
+            symbols.push(*symbol);
+            node = tree;
+        }
+    }
+    if std::ptr::eq(node, tree) {
+        Some(symbols)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,85 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+use crate::BoxedFrame as Frame;
+
+/// The outcome of `IncrementalEval::advance`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EvalStatus<Res> {
+    /// The budget ran out. `depth` is the number of suspended frames.
+    Pending {
+        depth: usize,
+    },
+    Done(Res),
+}
+
+/// A recursion that is evaluated a slice at a time, e.g., one slice per
+/// frame of a game loop. All state is kept between the calls to `advance`.
+pub struct IncrementalEval<'a, Arg, Res> {
+    f: Box<dyn Fn(Arg) -> Frame<'a, Arg, Res> + 'a>,
+    stack: Vec<Frame<'a, Arg, Res>>,
+    current: Option<Frame<'a, Arg, Res>>,
+    res: Option<Res>,
+    done: bool,
+}
+
+impl<'a, Arg, Res: Default> IncrementalEval<'a, Arg, Res> {
+    pub fn new<Gen>(f: impl Fn(Arg) -> Gen + 'a, arg: Arg) -> Self
+    where
+        Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin + 'a,
+    {
+        let f: Box<dyn Fn(Arg) -> Frame<'a, Arg, Res> + 'a> = Box::new(move |arg| Box::new(f(arg)));
+        let current = f(arg);
+        Self {
+            f,
+            stack: Vec::new(),
+            current: Some(current),
+            res: Some(Res::default()),
+            done: false,
+        }
+    }
+
+    /// Resumes at most `budget` frames.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the evaluation is already done or a frame panicked in an
+    /// earlier call.
+    pub fn advance(&mut self, budget: usize) -> EvalStatus<Res> {
+        let mut current = match self.current.take() {
+            Some(current) => current,
+            None if self.done => panic!("evaluation is already done"),
+            None => panic!("evaluation was aborted by a panicking frame"),
+        };
+        let mut res = self.res.take().unwrap();
+
+        for _ in 0..budget {
+            match Pin::new(&mut current).resume(res) {
+                GeneratorState::Yielded(arg) => {
+                    self.stack.push(current);
+                    current = (self.f)(arg);
+                    res = Res::default();
+                }
+                GeneratorState::Complete(real_res) => match self.stack.pop() {
+                    None => {
+                        self.done = true;
+                        return EvalStatus::Done(real_res);
+                    }
+                    Some(top) => {
+                        current = top;
+                        res = real_res;
+                    }
+                },
+            }
+        }
+        self.current = Some(current);
+        self.res = Some(res);
+        EvalStatus::Pending {
+            depth: self.stack.len(),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
@@ -0,0 +1,73 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::recurse_owned;
+
+/// Hash-conses nodes such that structurally equal nodes are shared.
+///
+/// Nodes refer to their children via `ByPtr<Rc<T>>`, which makes the derived
+/// `Eq` and `Hash` impls of `T` shallow. Since children are interned before
+/// their parents, comparing two nodes shallowly is the same as comparing
+/// them structurally, but neither recurses nor takes time proportional to
+/// the size of the subtrees.
+pub struct Interner<T> {
+    nodes: HashSet<Rc<T>>,
+}
+
+impl<T: Eq + Hash> Interner<T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: HashSet::new(),
+        }
+    }
+
+    /// The number of distinct nodes interned so far.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the shared copy of `node`, which must only refer to interned
+    /// children.
+    pub fn intern(&mut self, node: T) -> Rc<T> {
+        match self.nodes.get(&node) {
+            Some(shared) => Rc::clone(shared),
+            None => {
+                let shared = Rc::new(node);
+                self.nodes.insert(Rc::clone(&shared));
+                shared
+            }
+        }
+    }
+
+    /// Converts an owned tree into a DAG of interned nodes without recursing.
+    /// `unfold` splits an argument into a node and its children, `build`
+    /// puts a node together from its interned children.
+    pub fn intern_owned<Arg, Node, Children>(
+        &mut self,
+        arg: Arg,
+        unfold: impl Fn(Arg) -> (Node, Children),
+        build: impl Fn(Node, Vec<Rc<T>>) -> T,
+    ) -> Rc<T>
+    where
+        Children: IntoIterator<Item = Arg>,
+    {
+        let this = RefCell::new(self);
+        let res = recurse_owned(unfold, |node, children| {
+            let node = build(node, children);
+            this.borrow_mut().intern(node)
+        })(arg);
+        res
+    }
+}
+
+impl<T: Eq + Hash> Default for Interner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
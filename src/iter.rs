@@ -0,0 +1,116 @@
+//! Lazy traversal iterators over any tree-like structure, given a function
+//! producing the children of a node.
+use std::collections::VecDeque;
+
+pub struct Preorder<T, I, F> {
+    next: Option<T>,
+    stack: Vec<I>,
+    children: F,
+}
+
+impl<T, I, F> Iterator for Preorder<T, I::IntoIter, F>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(&T) -> I,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let node = match self.next.take() {
+            Some(node) => node,
+            None => loop {
+                match self.stack.last_mut()?.next() {
+                    Some(node) => break node,
+                    None => {
+                        self.stack.pop();
+                    }
+                }
+            },
+        };
+        self.stack.push((self.children)(&node).into_iter());
+        Some(node)
+    }
+}
+
+/// Visits every node before its children, depth first.
+pub fn preorder<T, I, F>(root: T, children: F) -> Preorder<T, I::IntoIter, F>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(&T) -> I,
+{
+    Preorder {
+        next: Some(root),
+        stack: Vec::new(),
+        children,
+    }
+}
+
+pub struct Postorder<T, I, F> {
+    stack: Vec<(T, I)>,
+    children: F,
+}
+
+impl<T, I, F> Iterator for Postorder<T, I::IntoIter, F>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(&T) -> I,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            let (_, iter) = self.stack.last_mut()?;
+            match iter.next() {
+                Some(child) => {
+                    let iter = (self.children)(&child).into_iter();
+                    self.stack.push((child, iter));
+                }
+                None => return self.stack.pop().map(|(node, _)| node),
+            }
+        }
+    }
+}
+
+/// Visits every node after its children, depth first.
+pub fn postorder<T, I, F>(root: T, mut children: F) -> Postorder<T, I::IntoIter, F>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(&T) -> I,
+{
+    let iter = children(&root).into_iter();
+    Postorder {
+        stack: vec![(root, iter)],
+        children,
+    }
+}
+
+pub struct Levelorder<T, F> {
+    queue: VecDeque<T>,
+    children: F,
+}
+
+impl<T, I, F> Iterator for Levelorder<T, F>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(&T) -> I,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend((self.children)(&node));
+        Some(node)
+    }
+}
+
+/// Visits the nodes breadth first, one level of the tree at a time.
+pub fn levelorder<T, I, F>(root: T, children: F) -> Levelorder<T, F>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(&T) -> I,
+{
+    Levelorder {
+        queue: VecDeque::from(vec![root]),
+        children,
+    }
+}
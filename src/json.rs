@@ -0,0 +1,349 @@
+//! Stack-safe operations on deeply nested JSON documents.
+//!
+//! Parsing, cloning, comparing and dropping a `serde_json::Value` all recurse
+//! on its nesting depth. An attacker who controls the input can hence crash a
+//! process by sending a document like `[[[[...]]]]`. The functions in this
+//! module perform the same operations with a heap-allocated stack instead.
+use serde_json::{Map, Value};
+use std::fmt;
+
+use crate::{recurse_owned, trampoline, MapLike};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub offset: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid JSON at offset {}", self.offset)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+enum Frame {
+    Array(Vec<Value>),
+    Object(Map<String, Value>, String),
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self) -> ParseError {
+        ParseError { offset: self.pos }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.input.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.input.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ParseError> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn scalar(&mut self, len: usize) -> Result<Value, ParseError> {
+        let end = self.pos + len;
+        let res = std::str::from_utf8(&self.input[self.pos..end])
+            .ok()
+            .and_then(|slice| serde_json::from_str(slice).ok())
+            .ok_or_else(|| self.error())?;
+        self.pos = end;
+        Ok(res)
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        if self.peek() != Some(b'"') {
+            return Err(self.error());
+        }
+        let mut len = 1;
+        loop {
+            match self.input.get(self.pos + len) {
+                None => return Err(self.error()),
+                Some(b'"') => match self.scalar(len + 1)? {
+                    Value::String(string) => return Ok(string),
+                    _ => unreachable!(),
+                },
+                Some(b'\\') => len += 2,
+                Some(_) => len += 1,
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<String, ParseError> {
+        let key = self.string()?;
+        self.expect(b':')?;
+        Ok(key)
+    }
+
+    fn literal(&mut self, literal: &str, value: Value) -> Result<Value, ParseError> {
+        if self.input[self.pos..].starts_with(literal.as_bytes()) {
+            self.pos += literal.len();
+            Ok(value)
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, ParseError> {
+        let len = self.input[self.pos..]
+            .iter()
+            .take_while(|byte| matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+            .count();
+        match self.scalar(len)? {
+            value @ Value::Number(_) => Ok(value),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn parse(&mut self, stack: &mut Vec<Frame>) -> Result<Value, ParseError> {
+        loop {
+            let mut value = match self.peek() {
+                Some(b'[') => {
+                    self.pos += 1;
+                    if self.peek() == Some(b']') {
+                        self.pos += 1;
+                        Value::Array(Vec::new())
+                    } else {
+                        stack.push(Frame::Array(Vec::new()));
+                        continue;
+                    }
+                }
+                Some(b'{') => {
+                    self.pos += 1;
+                    if self.peek() == Some(b'}') {
+                        self.pos += 1;
+                        Value::Object(Map::new())
+                    } else {
+                        let key = self.key()?;
+                        stack.push(Frame::Object(Map::new(), key));
+                        continue;
+                    }
+                }
+                Some(b'"') => Value::String(self.string()?),
+                Some(b't') => self.literal("true", Value::Bool(true))?,
+                Some(b'f') => self.literal("false", Value::Bool(false))?,
+                Some(b'n') => self.literal("null", Value::Null)?,
+                Some(_) => self.number()?,
+                None => return Err(self.error()),
+            };
+
+            loop {
+                match stack.last_mut() {
+                    None => {
+                        return match self.peek() {
+                            None => Ok(value),
+                            Some(_) => {
+                                drop_value(value);
+                                Err(self.error())
+                            }
+                        };
+                    }
+                    Some(Frame::Array(items)) => {
+                        items.push(value);
+                        match self.peek() {
+                            Some(b',') => {
+                                self.pos += 1;
+                                break;
+                            }
+                            Some(b']') => {
+                                self.pos += 1;
+                                match stack.pop() {
+                                    Some(Frame::Array(items)) => value = Value::Array(items),
+                                    _ => unreachable!(),
+                                }
+                            }
+                            _ => return Err(self.error()),
+                        }
+                    }
+                    Some(Frame::Object(map, key)) => {
+                        if let Some(old) = map.insert(std::mem::take(key), value) {
+                            drop_value(old);
+                        }
+                        match self.peek() {
+                            Some(b',') => {
+                                self.pos += 1;
+                                *key = self.key()?;
+                                break;
+                            }
+                            Some(b'}') => {
+                                self.pos += 1;
+                                match stack.pop() {
+                                    Some(Frame::Object(map, _)) => value = Value::Object(map),
+                                    _ => unreachable!(),
+                                }
+                            }
+                            _ => return Err(self.error()),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parses a JSON document of arbitrary nesting depth.
+pub fn parse(input: &str) -> Result<Value, ParseError> {
+    let mut parser = Parser {
+        input: input.as_bytes(),
+        pos: 0,
+    };
+    let mut stack = Vec::new();
+    let res = parser.parse(&mut stack);
+    for frame in stack {
+        match frame {
+            Frame::Array(items) => drop_value(Value::Array(items)),
+            Frame::Object(map, _) => drop_value(Value::Object(map)),
+        }
+    }
+    res
+}
+
+pub fn clone_value(value: &Value) -> Value {
+    trampoline(|value: &Value| {
+        move |_: Value| match value {
+            Value::Array(items) => {
+                let mut res = Vec::with_capacity(items.len());
+                for item in items {
+                    let item = yield item;
+                    res.push(item);
+                }
+                Value::Array(res)
+            }
+            Value::Object(map) => {
+                let mut res = Map::new();
+                for (key, item) in map {
+                    let item = yield item;
+                    res.insert(key.clone(), item);
+                }
+                Value::Object(res)
+            }
+            _ => value.clone(),
+        }
+    })(value)
+}
+
+pub fn eq_values(value1: &Value, value2: &Value) -> bool {
+    trampoline(|(value1, value2): (&Value, &Value)| {
+        move |_: bool| match (value1, value2) {
+            (Value::Array(items1), Value::Array(items2)) => {
+                if items1.len() != items2.len() {
+                    return false;
+                }
+                for (item1, item2) in items1.iter().zip(items2) {
+                    if !(yield (item1, item2)) {
+                        return false;
+                    }
+                }
+                true
+            }
+            (Value::Object(map1), Value::Object(map2)) => {
+                if map1.len() != map2.len() {
+                    return false;
+                }
+                for (key, item1) in map1 {
+                    match map2.get(key) {
+                        Some(item2) => {
+                            if !(yield (item1, item2)) {
+                                return false;
+                            }
+                        }
+                        None => return false,
+                    }
+                }
+                true
+            }
+            _ => value1 == value2,
+        }
+    })((value1, value2))
+}
+
+/// Drops a JSON value without recursing on its nesting depth.
+pub fn drop_value(value: Value) {
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        match value {
+            Value::Array(items) => stack.extend(items),
+            Value::Object(map) => stack.extend(map.into_iter().map(|(_, item)| item)),
+            _ => {}
+        }
+    }
+}
+
+/// Objects are maps for `deep_merge`, all other values are leaves.
+impl MapLike for Value {
+    type Key = String;
+
+    fn into_entries(self) -> Result<Vec<(String, Value)>, Value> {
+        match self {
+            Value::Object(map) => Ok(map.into_iter().collect()),
+            value => Err(value),
+        }
+    }
+
+    fn from_entries(entries: Vec<(String, Value)>) -> Value {
+        Value::Object(entries.into_iter().collect())
+    }
+}
+
+enum Patched {
+    Object(Map<String, Value>, Vec<String>),
+    Value(Value),
+}
+
+/// Applies `patch` to `target` as described in RFC 7386 (JSON Merge Patch):
+/// objects are patched key by key, `null` removes a key and every other value
+/// replaces the target.
+pub fn merge_patch(target: Value, patch: Value) -> Value {
+    recurse_owned(
+        |(target, patch): (Value, Value)| match patch {
+            Value::Object(patch) => {
+                let mut map = match target {
+                    Value::Object(map) => map,
+                    target => {
+                        drop_value(target);
+                        Map::new()
+                    }
+                };
+                let mut keys = Vec::new();
+                let mut children = Vec::new();
+                for (key, value) in patch {
+                    let old = map.remove(&key).unwrap_or(Value::Null);
+                    if value.is_null() {
+                        drop_value(old);
+                    } else {
+                        keys.push(key);
+                        children.push((old, value));
+                    }
+                }
+                (Patched::Object(map, keys), children)
+            }
+            patch => {
+                drop_value(target);
+                (Patched::Value(patch), Vec::new())
+            }
+        },
+        |node, values| match node {
+            Patched::Object(mut map, keys) => {
+                map.extend(keys.into_iter().zip(values));
+                Value::Object(map)
+            }
+            Patched::Value(value) => value,
+        },
+    )((target, patch))
+}
@@ -1,88 +1,446 @@
 #![feature(
+    allocator_api,
     destructuring_assignment,
+    fn_traits,
     generators,
+    generator_clone,
     generator_trait,
-    step_trait,
-    thread_spawn_unchecked
+    generic_associated_types,
+    scoped_threads,
+    thread_spawn_unchecked,
+    unboxed_closures
 )]
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
 use std::ops::{Generator, GeneratorState};
 use std::pin::Pin;
-use std::thread;
 
-pub fn trampoline<Arg, Res, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Res
+use checked::{check_tail, Checked};
+
+extern crate self as stack_safe;
+
+#[macro_use]
+mod macros;
+
+mod arena;
+mod batch;
+mod big_stack;
+mod bisim;
+mod boxed;
+mod builder;
+mod by_ptr;
+mod checked;
+mod children;
+mod clone_into;
+mod control;
+mod dag;
+mod deep_drop;
+mod diff;
+pub mod disjoint_set;
+pub mod dom;
+mod dot;
+mod effect;
+mod error;
+mod fallible;
+mod fixed;
+mod flat;
+mod fold;
+mod frame;
+pub mod gametree;
+pub mod graph;
+mod hash;
+mod hook;
+#[cfg(feature = "huffman")]
+pub mod huffman;
+mod incremental;
+mod interner;
+pub mod iter;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod list;
+mod many;
+mod memo;
+mod merge;
+mod observe;
+mod off_thread;
+mod owned;
+#[cfg(feature = "rayon")]
+mod par;
+mod path;
+mod plan;
+mod probe;
+#[cfg(feature = "profiling")]
+mod profile;
+mod progress;
+mod reclaim;
+mod reduce;
+mod replay;
+mod reuse;
+pub mod rewrite;
+mod sched;
+mod scoped;
+pub mod search;
+mod snapshot;
+mod spill;
+mod stack;
+mod stop;
+#[cfg(feature = "stream")]
+mod stream;
+#[cfg(feature = "syn")]
+pub mod syn_expr;
+mod tail;
+#[cfg(feature = "testdata")]
+pub mod testdata;
+mod thunk;
+mod visit;
+mod walk;
+mod zipper;
+
+pub use arena::{Arena, NodeChildren, NodeId, NodeRef};
+pub use batch::{recurse_batch, recurse_batch_with_cache};
+pub use big_stack::BigStackThread;
+pub use bisim::bisimilar;
+pub use boxed::{RecursiveFn, StackSafeFn};
+pub use builder::{Memo, Memoized, Recursion};
+pub use by_ptr::ByPtr;
+pub use children::{deep_cmp, map_mut, ChildrenMut};
+pub use clone_into::{deep_clone_into, Storage};
+pub use control::{trampoline_break, trampoline_control, trampoline_labeled, Control, Labeled};
+pub use dag::{
+    recurse_dag, recurse_dag_by, recurse_dag_by_ptr, recurse_graph_by_ptr, Revisit, Visit,
+};
+pub use deep_drop::{deep_drop_arc, deep_drop_rc};
+pub use diff::{deep_diff, deep_diff_all};
+pub use dot::CallTree;
+pub use effect::{trampoline_effects, Effect, Handler};
+pub use error::{PanicPayload, StackSafeError};
+pub use fallible::{
+    try_recurse, try_recurse_recover, try_recurse_tco, try_recurse_traced, Recovered, Traced,
+};
+pub use fixed::{ArrayStack, SliceStack};
+pub use flat::{flatten, unflatten, FlatNode};
+pub use fold::{fold_tree, recurse_fold, recurse_fold_until, Order};
+pub use frame::{recurse_fn, BoxedFrame, FrameFactory, FrameGen};
+pub use hash::StructuralHasher;
+pub use hook::install_panic_hook;
+pub use incremental::{EvalStatus, IncrementalEval};
+pub use interner::Interner;
+pub use many::{trampoline_many, trampoline_many_scoped, ManyCall};
+pub use memo::{recurse_with_cache, Cache};
+pub use merge::{deep_merge, MapLike};
+pub use observe::{trampoline_observed, Observer};
+pub use off_thread::OffThread;
+pub use owned::{recurse_owned, try_recurse_owned};
+#[cfg(feature = "rayon")]
+pub use par::{par_nodes, par_reachable, trampoline_many_par};
+pub use path::{trampoline_with_labels, trampoline_with_path, Path};
+pub use plan::recurse_planned;
+#[doc(hidden)]
+pub use probe::{__probe, __probe_failed};
+pub use probe::{remaining_stack, DEFAULT_RED_ZONE};
+#[cfg(feature = "profiling")]
+pub use profile::{Profiler, Sample};
+#[cfg(feature = "indicatif")]
+pub use progress::progress_bar;
+pub use progress::{Progress, ProgressUpdate};
+pub use reclaim::{trampoline_reclaiming, ReclaimingStack, Shrink};
+pub use reduce::recurse_reduce;
+pub use replay::{replay, trampoline_recorded, Divergence, Trace, TraceEvent};
+pub use reuse::{release_retained, retained_bytes, trampoline_reusing};
+pub use sched::{trampoline_scheduled, Bfs, Dfs, Priority, Scheduler, Task};
+pub use scoped::recurse_scoped;
+pub use snapshot::Computation;
+pub use spill::{Plain, SpillStack};
+pub use stack::{
+    drive, trampoline_bounded, trampoline_budgeted, trampoline_in, trampoline_on, trampoline_with,
+    try_drive, BoundedStack, BudgetExceeded, BudgetedStack, DepthExceeded, FrameStack,
+    GrowingStack, Growth,
+};
+pub use stack_safe_macros::{frame, main, test};
+pub use stack_safe_stable::{recurse_async, CallFuture, Caller};
+pub use stop::{CancelToken, Deadline, Fuel, Never, Partial, Stop};
+#[cfg(feature = "stream")]
+pub use stream::TraversalStream;
+pub use tail::{trampoline_tail, Tail, TailCall};
+pub use thunk::Thunk;
+pub use visit::visit;
+pub use walk::{walk, Visitor, Walk};
+pub use zipper::Zipper;
+
+/// Turns `f`, which creates the frame for an argument, into a stack-safe
+/// function. Like the functions returned by the other drivers, it is `Send`
+/// and `Sync` whenever `f` is. It is also `Clone` whenever `f` is, so that
+/// copies can be handed out without a `RecursiveFn`. Only `trampoline` and
+/// `trampoline_tco` are `Clone` like this. The other drivers return opaque
+/// functions that never are; `RecursiveFn::from_fn` can share those.
+#[must_use = "`trampoline` returns the stack-safe function without calling it"]
+pub fn trampoline<Arg, Res, Gen, F>(f: F) -> Trampoline<F, Res>
 where
+    F: Fn(Arg) -> Gen,
     Res: Default,
     Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
 {
-    move |arg: Arg| {
-        let mut stack = Vec::new();
-        let mut current = f(arg);
-        let mut res = Res::default();
+    Trampoline {
+        f,
+        res: PhantomData,
+    }
+}
 
-        loop {
-            match Pin::new(&mut current).resume(res) {
-                GeneratorState::Yielded(arg) => {
-                    stack.push(current);
-                    current = f(arg);
-                    res = Res::default();
-                }
-                GeneratorState::Complete(real_res) => match stack.pop() {
-                    None => return real_res,
-                    Some(top) => {
-                        current = top;
-                        res = real_res;
-                    }
-                },
-            }
+/// The stack-safe function returned by `trampoline`.
+pub struct Trampoline<F, Res> {
+    f: F,
+    res: PhantomData<fn() -> Res>,
+}
+
+impl<F: Clone, Res> Clone for Trampoline<F, Res> {
+    fn clone(&self) -> Self {
+        Self {
+            f: self.f.clone(),
+            res: PhantomData,
         }
     }
 }
 
+impl<Arg, Res, Gen, F> FnOnce<(Arg,)> for Trampoline<F, Res>
+where
+    F: Fn(Arg) -> Gen,
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    type Output = Res;
+
+    extern "rust-call" fn call_once(self, args: (Arg,)) -> Res {
+        self.call(args)
+    }
+}
+
+impl<Arg, Res, Gen, F> FnMut<(Arg,)> for Trampoline<F, Res>
+where
+    F: Fn(Arg) -> Gen,
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    extern "rust-call" fn call_mut(&mut self, args: (Arg,)) -> Res {
+        self.call(args)
+    }
+}
+
+impl<Arg, Res, Gen, F> Fn<(Arg,)> for Trampoline<F, Res>
+where
+    F: Fn(Arg) -> Gen,
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    extern "rust-call" fn call(&self, (arg,): (Arg,)) -> Res {
+        drive(|arg| Checked::new((self.f)(arg)), arg, &mut Vec::new())
+    }
+}
+
+impl<F, Res> fmt::Debug for Trampoline<F, Res> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trampoline").finish_non_exhaustive()
+    }
+}
+
 pub struct Call<T> {
-    arg: T,
-    is_tail: bool,
+    kind: CallKind<T>,
+    label: Option<Cow<'static, str>>,
+}
+
+pub(crate) enum CallKind<T> {
+    Normal(T),
+    Tail(T),
 }
 
 impl<T> Call<T> {
     pub fn normal(arg: T) -> Self {
         Self {
-            arg,
-            is_tail: false,
+            kind: CallKind::Normal(arg),
+            label: None,
         }
     }
 
+    /// Replaces the caller's frame in drivers that support tail calls. Any
+    /// work the caller does after the call is lost; `trampoline_tco_checked`
+    /// detects such work in debug builds and `trampoline_tail` rules it out at
+    /// compile time. With the `debug-invariants` feature, the drivers resume
+    /// the caller once more with a default result and panic if it yields
+    /// again.
     pub fn tail(arg: T) -> Self {
-        Self { arg, is_tail: true }
+        Self {
+            kind: CallKind::Tail(arg),
+            label: None,
+        }
+    }
+
+    /// Attaches a label to the call, e.g., "parsing field `foo`", which the
+    /// `Recursion` builder passes to `Observer::enter_labeled` and shows in
+    /// the logical backtrace printed by the hook of `install_panic_hook`.
+    pub fn with_label(mut self, label: impl Into<Cow<'static, str>>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+}
+
+/// Makes a normal call, so that drivers accepting `Into<Call<Arg>>` also
+/// work with frames that yield plain arguments.
+impl<T> From<T> for Call<T> {
+    fn from(arg: T) -> Self {
+        Self::normal(arg)
     }
 }
 
-pub fn trampoline_tco<Arg, Res, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Res
+/// Like `trampoline`, but frames yield `Call`s and `Call::tail` replaces the
+/// caller's frame. The result is `Clone` whenever `f` is.
+pub fn trampoline_tco<Arg, Res, Gen, F>(f: F) -> TrampolineTco<F, Res>
 where
+    F: Fn(Arg) -> Gen,
     Res: Default,
     Gen: Generator<Res, Yield = Call<Arg>, Return = Res> + Unpin,
+{
+    TrampolineTco {
+        f,
+        res: PhantomData,
+    }
+}
+
+/// The stack-safe function returned by `trampoline_tco`.
+pub struct TrampolineTco<F, Res> {
+    f: F,
+    res: PhantomData<fn() -> Res>,
+}
+
+impl<F: Clone, Res> Clone for TrampolineTco<F, Res> {
+    fn clone(&self) -> Self {
+        Self {
+            f: self.f.clone(),
+            res: PhantomData,
+        }
+    }
+}
+
+impl<Arg, Res, Gen, F> FnOnce<(Arg,)> for TrampolineTco<F, Res>
+where
+    F: Fn(Arg) -> Gen,
+    Res: Default,
+    Gen: Generator<Res, Yield = Call<Arg>, Return = Res> + Unpin,
+{
+    type Output = Res;
+
+    extern "rust-call" fn call_once(self, args: (Arg,)) -> Res {
+        self.call(args)
+    }
+}
+
+impl<Arg, Res, Gen, F> FnMut<(Arg,)> for TrampolineTco<F, Res>
+where
+    F: Fn(Arg) -> Gen,
+    Res: Default,
+    Gen: Generator<Res, Yield = Call<Arg>, Return = Res> + Unpin,
+{
+    extern "rust-call" fn call_mut(&mut self, args: (Arg,)) -> Res {
+        self.call(args)
+    }
+}
+
+impl<Arg, Res, Gen, F> Fn<(Arg,)> for TrampolineTco<F, Res>
+where
+    F: Fn(Arg) -> Gen,
+    Res: Default,
+    Gen: Generator<Res, Yield = Call<Arg>, Return = Res> + Unpin,
+{
+    extern "rust-call" fn call(&self, (arg,): (Arg,)) -> Res {
+        drive_tco(&self.f, arg, true)
+    }
+}
+
+impl<F, Res> fmt::Debug for TrampolineTco<F, Res> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrampolineTco").finish_non_exhaustive()
+    }
+}
+
+/// Like `trampoline_tco`, but in debug builds every call is also evaluated
+/// with `Call::tail` treated like `Call::normal` and the two results must be
+/// equal. This catches frames that do more work after a tail call, whose
+/// result `trampoline_tco` silently discards. Since the second evaluation
+/// uses a frame per call, this is only meant for small inputs.
+///
+/// # Panics
+///
+/// Panics in debug builds if the results differ.
+pub fn trampoline_tco_checked<Arg, Res, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Res
+where
+    Arg: Clone + fmt::Debug,
+    Res: Default + PartialEq + fmt::Debug,
+    Gen: Generator<Res, Yield = Call<Arg>, Return = Res> + Unpin,
 {
     move |arg: Arg| {
-        let mut stack = Vec::new();
-        let mut gen = f(arg);
-        let mut res = Res::default();
+        if cfg!(debug_assertions) {
+            let expected = drive_tco(&f, arg.clone(), false);
+            let res = drive_tco(&f, arg.clone(), true);
+            assert!(
+                res == expected,
+                "`Call::tail` changes the result for {:?}: {:?} with tail calls, {:?} without",
+                arg,
+                res,
+                expected
+            );
+            res
+        } else {
+            drive_tco(&f, arg, true)
+        }
+    }
+}
 
-        loop {
-            match Pin::new(&mut gen).resume(res) {
-                GeneratorState::Yielded(call) => {
-                    if !call.is_tail {
-                        stack.push(gen);
+/// The driver behind `trampoline_tco`. Unless `honor_tail` is set, tail
+/// calls are made like normal calls.
+fn drive_tco<Arg, Res, Gen>(f: &impl Fn(Arg) -> Gen, arg: Arg, honor_tail: bool) -> Res
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Call<Arg>, Return = Res> + Unpin,
+{
+    let mut stack = Vec::new();
+    let mut gen = Checked::new(f(arg));
+    let mut res = Res::default();
+    let mut depth = 0;
+
+    loop {
+        invariant!(
+            stack.len() == depth,
+            "{} frames are suspended but {} normal calls are pending",
+            stack.len(),
+            depth
+        );
+        match Pin::new(&mut gen).resume(res) {
+            GeneratorState::Yielded(call) => {
+                let arg = match call.kind {
+                    CallKind::Tail(arg) if honor_tail => {
+                        check_tail(&mut gen);
+                        arg
                     }
-                    gen = f(call.arg);
-                    res = Res::default();
-                }
-                GeneratorState::Complete(res1) => match stack.pop() {
-                    None => return res1,
-                    Some(top) => {
-                        gen = top;
-                        res = res1;
+                    CallKind::Normal(arg) | CallKind::Tail(arg) => {
+                        stack.push(gen);
+                        depth += 1;
+                        arg
                     }
-                },
+                };
+                gen = Checked::new(f(arg));
+                res = Res::default();
             }
+            GeneratorState::Complete(res1) => match stack.pop() {
+                None => return res1,
+                Some(top) => {
+                    depth -= 1;
+                    gen = top;
+                    res = res1;
+                }
+            },
         }
     }
 }
@@ -127,17 +485,12 @@ where
     }
 }
 
-pub fn with_stack_size<T, F>(size: usize, f: F) -> thread::Result<T>
+pub fn with_stack_size<T, F>(size: usize, f: F) -> Result<T, StackSafeError>
 where
     T: Send,
     F: FnOnce() -> T + Send,
 {
-    let result = unsafe {
-        std::thread::Builder::new()
-            .stack_size(size)
-            .spawn_unchecked(f)
-    };
-    result.unwrap().join()
+    BigStackThread::new().stack_size(size).run(f)
 }
 
 #[cfg(test)]
@@ -0,0 +1,229 @@
+//! A singly linked cons list whose operations never recurse.
+//!
+//! Linked lists are the textbook example of a recursive data structure and
+//! the textbook way to overflow the stack: even the `Drop` implementation
+//! the compiler generates for them recurses once per element. All operations
+//! of `List`, including dropping it, run in constant native stack space.
+//! `map` doubles as an example of writing them with `trampoline`.
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+
+use crate::trampoline;
+
+pub struct List<T> {
+    head: Option<Box<Node<T>>>,
+}
+
+struct Node<T> {
+    value: T,
+    next: List<T>,
+}
+
+impl<T> List<T> {
+    pub const fn new() -> Self {
+        Self { head: None }
+    }
+
+    pub fn cons(head: T, tail: Self) -> Self {
+        Self {
+            head: Some(Box::new(Node {
+                value: head,
+                next: tail,
+            })),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    /// The first element and the rest of the list, like `<[T]>::split_first`.
+    pub fn split_first(&self) -> Option<(&T, &Self)> {
+        self.head.as_ref().map(|node| (&node.value, &node.next))
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        let tail = mem::take(self);
+        *self = Self::cons(value, tail);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let node = self.head.take()?;
+        let Node { value, next } = *node;
+        *self = next;
+        Some(value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { list: self }
+    }
+
+    /// Applies `f` to every element. The traversal runs on `trampoline`, so
+    /// the result is built from the back without recursing.
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> List<U> {
+        let f = &f;
+        let mapped = trampoline(|list: &Self| {
+            move |_: List<U>| match list.split_first() {
+                None => List::new(),
+                Some((head, tail)) => {
+                    let head = f(head);
+                    let tail = yield tail;
+                    List::cons(head, tail)
+                }
+            }
+        })(self);
+        mapped
+    }
+
+    /// Moves all elements of `other` to the end of the list.
+    pub fn append(&mut self, mut other: Self) {
+        let mut link = &mut self.head;
+        while let Some(node) = link {
+            link = &mut node.next.head;
+        }
+        *link = other.head.take();
+    }
+
+    pub fn reverse(&mut self) {
+        let mut rest = mem::take(self);
+        while let Some(mut node) = rest.head.take() {
+            rest = mem::replace(&mut node.next, mem::take(self));
+            self.head = Some(node);
+        }
+    }
+
+    /// Sorts the list with a bottom-up merge sort. The sort is stable.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        let mut runs: Vec<Self> = Vec::new();
+        while let Some(value) = self.pop_front() {
+            runs.push(Self::cons(value, Self::new()));
+        }
+        while runs.len() > 1 {
+            let mut merged = Vec::with_capacity(runs.len() / 2 + 1);
+            let mut runs_iter = runs.into_iter();
+            while let Some(left) = runs_iter.next() {
+                merged.push(match runs_iter.next() {
+                    Some(right) => merge(left, right),
+                    None => left,
+                });
+            }
+            runs = merged;
+        }
+        *self = runs.pop().unwrap_or_default();
+    }
+}
+
+/// Merges two sorted lists, preferring `left` on ties.
+fn merge<T: Ord>(mut left: List<T>, mut right: List<T>) -> List<T> {
+    let mut merged = List::new();
+    while let (Some(l), Some(r)) = (left.first(), right.first()) {
+        let side = if l <= r { &mut left } else { &mut right };
+        merged.push_front(side.pop_front().unwrap());
+    }
+    merged.reverse();
+    merged.append(if left.is_empty() { right } else { left });
+    merged
+}
+
+/// Unlinks the nodes one by one. The generated `Drop` would recurse.
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+        while let Some(mut node) = head {
+            head = node.next.head.take();
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        for value in iter {
+            list.push_front(value);
+        }
+        list.reverse();
+        list
+    }
+}
+
+pub struct Iter<'a, T> {
+    list: &'a List<T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let (head, tail) = self.list.split_first()?;
+        self.list = tail;
+        Some(head)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+pub struct IntoIter<T> {
+    list: List<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
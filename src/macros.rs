@@ -0,0 +1,103 @@
+/// Yields every argument of an iterator from within a frame and collects
+/// the results into a `Vec`, or folds them into an initial accumulator with
+/// a closure.
+///
+/// ```ignore
+/// let depths = yield_all!(tree.children.iter());
+/// let sum = yield_all!(tree.children.iter(), tree.value, |acc, res| acc + res);
+/// ```
+#[macro_export]
+macro_rules! yield_all {
+    ($args:expr) => {{
+        let mut results = ::std::vec::Vec::new();
+        for arg in $args {
+            let result = yield arg;
+            results.push(result);
+        }
+        results
+    }};
+    ($args:expr, $init:expr, $fold:expr) => {{
+        let mut acc = $init;
+        for arg in $args {
+            let result = yield arg;
+            acc = ($fold)(acc, result);
+        }
+        acc
+    }};
+}
+
+/// Runs a sub-generator to completion from within a frame. Every argument
+/// the sub-generator yields is yielded by the frame and the result is passed
+/// back to it, so helper traversals can be factored out into functions that
+/// return generators. The sub-generator is first resumed with the default
+/// result, just like frames are.
+///
+/// ```ignore
+/// let sum = yield_from!(sum_children(tree)) + tree.value;
+/// ```
+#[macro_export]
+macro_rules! yield_from {
+    ($gen:expr) => {{
+        let mut gen = $gen;
+        let mut res = ::std::default::Default::default();
+        loop {
+            match ::std::ops::Generator::resume(::std::pin::Pin::new(&mut gen), res) {
+                ::std::ops::GeneratorState::Yielded(arg) => res = yield arg,
+                ::std::ops::GeneratorState::Complete(result) => break result,
+            }
+        }
+    }};
+}
+
+/// Ends a frame of `trampoline_tail` with a tail call. Since it returns from
+/// the frame, nothing can happen after the call.
+///
+/// ```ignore
+/// if n == 0 {
+///     tail_call!((m - 1, 1));
+/// }
+/// ```
+#[macro_export]
+macro_rules! tail_call {
+    ($arg:expr) => {
+        return $crate::Tail::Call($crate::TailCall::new($arg))
+    };
+}
+
+/// Panics with a helpful message instead of overflowing the stack when
+/// fewer than `DEFAULT_RED_ZONE` bytes are left on the native stack. Meant
+/// to be placed at the entry of recursive functions. The current recursion
+/// depth can be passed for the message. Compiles to nothing in release
+/// builds and on platforms where the remaining stack is unknown it never
+/// panics.
+///
+/// ```ignore
+/// fn depth(tree: &Tree, level: usize) -> usize {
+///     stack_probe!(depth = level);
+///     ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! stack_probe {
+    () => {
+        #[cfg(debug_assertions)]
+        $crate::__probe($crate::DEFAULT_RED_ZONE, ::std::option::Option::None);
+    };
+    (depth = $depth:expr) => {
+        #[cfg(debug_assertions)]
+        $crate::__probe(
+            $crate::DEFAULT_RED_ZONE,
+            ::std::option::Option::Some($depth),
+        );
+    };
+}
+
+/// Asserts an internal invariant of a driver if the `debug-invariants`
+/// feature is enabled and compiles to nothing otherwise.
+macro_rules! invariant {
+    ($cond:expr, $($arg:tt)+) => {
+        if cfg!(feature = "debug-invariants") {
+            assert!($cond, "invariant violated: {}", format_args!($($arg)+));
+        }
+    };
+}
@@ -0,0 +1,163 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+use std::{panic, thread, vec};
+
+/// What frames of `trampoline_many` and the drivers built on it yield. Unlike
+/// `Call`, it can call the function on a whole batch of arguments.
+pub struct ManyCall<T> {
+    pub(crate) kind: ManyKind<T>,
+}
+
+pub(crate) enum ManyKind<T> {
+    Normal(T),
+    Tail(T),
+    Many(Vec<T>),
+}
+
+impl<T> ManyCall<T> {
+    pub fn normal(arg: T) -> Self {
+        Self {
+            kind: ManyKind::Normal(arg),
+        }
+    }
+
+    /// Replaces the caller's frame, like `Call::tail`.
+    pub fn tail(arg: T) -> Self {
+        Self {
+            kind: ManyKind::Tail(arg),
+        }
+    }
+
+    /// Calls the function on all `args` at once. The caller is resumed with
+    /// all results.
+    pub fn many(args: Vec<T>) -> Self {
+        Self {
+            kind: ManyKind::Many(args),
+        }
+    }
+}
+
+struct Frame<Gen, Arg, Res> {
+    gen: Gen,
+    args: vec::IntoIter<Arg>,
+    results: Vec<Res>,
+}
+
+/// Like `trampoline_tco`, but frames yield `ManyCall`s, which can also call the
+/// function on a whole batch of arguments. Frames are always resumed with the
+/// results of all the calls in their last yield, in the order of the
+/// arguments. For `ManyCall::normal`, this is a vector with a single element.
+pub fn trampoline_many<Arg, Res, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Res
+where
+    Gen: Generator<Vec<Res>, Yield = ManyCall<Arg>, Return = Res> + Unpin,
+{
+    move |arg: Arg| drive_many(&f, arg, Err)
+}
+
+/// Like `trampoline_many_par`, but evaluates the arguments of a `ManyCall::many`
+/// on scoped threads instead of a thread pool. Neither the arguments nor the
+/// results need to be `'static`, so frames can yield references into a
+/// borrowed structure, e.g., the children of a node of an AST. Every argument
+/// but the first is evaluated on a thread of its own, which consumes one
+/// level of `max_par_depth`. Panics in those threads are propagated.
+pub fn trampoline_many_scoped<Arg, Res, Gen, F>(f: F, max_par_depth: usize) -> impl Fn(Arg) -> Res
+where
+    Arg: Send,
+    Res: Send,
+    Gen: Generator<Vec<Res>, Yield = ManyCall<Arg>, Return = Res> + Unpin,
+    F: Fn(Arg) -> Gen + Sync,
+{
+    move |arg: Arg| drive_scoped(&f, arg, max_par_depth)
+}
+
+fn drive_scoped<Arg, Res, Gen, F>(f: &F, arg: Arg, max_par_depth: usize) -> Res
+where
+    Arg: Send,
+    Res: Send,
+    Gen: Generator<Vec<Res>, Yield = ManyCall<Arg>, Return = Res> + Unpin,
+    F: Fn(Arg) -> Gen + Sync,
+{
+    drive_many(f, arg, |args: Vec<Arg>| {
+        if max_par_depth == 0 || args.len() < 2 {
+            return Err(args);
+        }
+        Ok(thread::scope(|scope| {
+            let mut args = args.into_iter();
+            let first = args.next().unwrap();
+            let handles: Vec<_> = args
+                .map(|arg| scope.spawn(move || drive_scoped(f, arg, max_par_depth - 1)))
+                .collect();
+            let mut results = Vec::with_capacity(handles.len() + 1);
+            results.push(drive_scoped(f, first, max_par_depth - 1));
+            for handle in handles {
+                let res = handle
+                    .join()
+                    .unwrap_or_else(|payload| panic::resume_unwind(payload));
+                results.push(res);
+            }
+            results
+        }))
+    })
+}
+
+/// The driver behind `trampoline_many`. `batch` gets the chance to evaluate
+/// the arguments of a `ManyCall::many` by other means. If it hands them back,
+/// they are evaluated one after the other on the frame stack.
+pub(crate) fn drive_many<Arg, Res, Gen>(
+    f: &impl Fn(Arg) -> Gen,
+    arg: Arg,
+    batch: impl Fn(Vec<Arg>) -> Result<Vec<Res>, Vec<Arg>>,
+) -> Res
+where
+    Gen: Generator<Vec<Res>, Yield = ManyCall<Arg>, Return = Res> + Unpin,
+{
+    let mut stack: Vec<Frame<Gen, Arg, Res>> = Vec::new();
+    let mut gen = f(arg);
+    let mut results = Vec::new();
+
+    loop {
+        match Pin::new(&mut gen).resume(results) {
+            GeneratorState::Yielded(call) => {
+                let args = match call.kind {
+                    ManyKind::Normal(arg) => vec![arg],
+                    ManyKind::Tail(arg) => {
+                        gen = f(arg);
+                        results = Vec::new();
+                        continue;
+                    }
+                    ManyKind::Many(args) => match batch(args) {
+                        Ok(batch_results) => {
+                            results = batch_results;
+                            continue;
+                        }
+                        Err(args) => args,
+                    },
+                };
+                let mut args = args.into_iter();
+                if let Some(arg) = args.next() {
+                    let results = Vec::with_capacity(args.len() + 1);
+                    stack.push(Frame { gen, args, results });
+                    gen = f(arg);
+                }
+                results = Vec::new();
+            }
+            GeneratorState::Complete(res) => match stack.last_mut() {
+                None => return res,
+                Some(frame) => {
+                    frame.results.push(res);
+                    match frame.args.next() {
+                        Some(arg) => {
+                            gen = f(arg);
+                            results = Vec::new();
+                        }
+                        None => {
+                            let frame = stack.pop().unwrap();
+                            gen = frame.gen;
+                            results = frame.results;
+                        }
+                    }
+                }
+            },
+        }
+    }
+}
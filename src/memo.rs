@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+/// A store of already computed results, e.g., a `HashMap` or an LRU cache.
+pub trait Cache<Arg, Res> {
+    fn get(&mut self, arg: &Arg) -> Option<Res>;
+
+    fn insert(&mut self, arg: Arg, res: Res);
+}
+
+impl<Arg, Res, S> Cache<Arg, Res> for HashMap<Arg, Res, S>
+where
+    Arg: Eq + Hash,
+    Res: Clone,
+    S: BuildHasher,
+{
+    fn get(&mut self, arg: &Arg) -> Option<Res> {
+        HashMap::get(self, arg).cloned()
+    }
+
+    fn insert(&mut self, arg: Arg, res: Res) {
+        HashMap::insert(self, arg, res);
+    }
+}
+
+/// Like `trampoline`, but looks up every call in `cache` first and stores
+/// the result of every call that is actually made. The cache is owned by the
+/// caller and hence survives across top-level calls.
+pub fn recurse_with_cache<'c, Arg, Res, Gen, C>(
+    f: impl Fn(Arg) -> Gen + 'c,
+    cache: &'c mut C,
+) -> impl FnMut(Arg) -> Res + 'c
+where
+    Arg: Clone,
+    Res: Clone + Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+    C: Cache<Arg, Res>,
+{
+    move |arg: Arg| {
+        if let Some(res) = cache.get(&arg) {
+            return res;
+        }
+        let mut stack = Vec::new();
+        let mut current = (f(arg.clone()), arg);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current.0).resume(res) {
+                GeneratorState::Yielded(arg) => match cache.get(&arg) {
+                    Some(cached) => res = cached,
+                    None => {
+                        stack.push(current);
+                        current = (f(arg.clone()), arg);
+                        res = Res::default();
+                    }
+                },
+                GeneratorState::Complete(real_res) => {
+                    cache.insert(current.1, real_res.clone());
+                    match stack.pop() {
+                        None => return real_res,
+                        Some(top) => {
+                            current = top;
+                            res = real_res;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::recurse_owned;
+
+/// A recursive structure whose inner nodes map keys to substructures.
+pub trait MapLike: Sized {
+    type Key: Eq + Hash;
+
+    /// Takes a map apart into its entries, or hands a leaf back unchanged.
+    fn into_entries(self) -> Result<Vec<(Self::Key, Self)>, Self>;
+
+    fn from_entries(entries: Vec<(Self::Key, Self)>) -> Self;
+}
+
+enum Merged<K, T> {
+    Map(Vec<K>),
+    Leaf(T),
+}
+
+/// Merges `b` into `a` without recursing on the depth of either. Where both
+/// sides are maps, the entries of `a` come first, followed by the entries
+/// that only `b` has, and the values under common keys are merged in turn.
+/// Everywhere else, `combine_leaf` decides what the result is. Keys are
+/// assumed to be unique within each map.
+pub fn deep_merge<T: MapLike>(a: T, b: T, combine_leaf: impl Fn(T, T) -> T) -> T {
+    recurse_owned(
+        |(a, b): (T, Option<T>)| {
+            let b = match b {
+                None => return (Merged::Leaf(a), Vec::new()),
+                Some(b) => b,
+            };
+            let (a_entries, b_entries) = match (a.into_entries(), b.into_entries()) {
+                (Ok(a_entries), Ok(b_entries)) => (a_entries, b_entries),
+                (a, b) => {
+                    let a = a.map_or_else(|a| a, T::from_entries);
+                    let b = b.map_or_else(|b| b, T::from_entries);
+                    return (Merged::Leaf(combine_leaf(a, b)), Vec::new());
+                }
+            };
+            let mut index = HashMap::new();
+            let mut children = Vec::with_capacity(a_entries.len());
+            for (key, a) in a_entries {
+                index.insert(key, children.len());
+                children.push((a, None));
+            }
+            for (key, b) in b_entries {
+                match index.get(&key) {
+                    Some(&i) => children[i].1 = Some(b),
+                    None => {
+                        index.insert(key, children.len());
+                        children.push((b, None));
+                    }
+                }
+            }
+            let mut keys: Vec<_> = index.into_iter().collect();
+            keys.sort_by_key(|(_, i)| *i);
+            let keys = keys.into_iter().map(|(key, _)| key).collect();
+            (Merged::Map(keys), children)
+        },
+        |node, results| match node {
+            Merged::Map(keys) => T::from_entries(keys.into_iter().zip(results).collect()),
+            Merged::Leaf(leaf) => leaf,
+        },
+    )((a, Some(b)))
+}
@@ -0,0 +1,69 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+/// Hooks into the calls made by `trampoline_observed`. The depth of the
+/// initial call is 0.
+pub trait Observer<Arg> {
+    fn enter(&mut self, _arg: &Arg, _depth: usize) {}
+
+    /// Like `enter`, but also receives the label attached with
+    /// `Call::with_label`, if any. Only drivers that take `Call`s, like the
+    /// `Recursion` builder, pass labels. Calls `enter` by default.
+    fn enter_labeled(&mut self, arg: &Arg, _label: Option<&str>, depth: usize) {
+        self.enter(arg, depth)
+    }
+
+    fn exit(&mut self, _depth: usize) {}
+}
+
+/// Observes nothing.
+impl<Arg> Observer<Arg> for () {}
+
+impl<Arg, O: Observer<Arg> + ?Sized> Observer<Arg> for &mut O {
+    fn enter(&mut self, arg: &Arg, depth: usize) {
+        (**self).enter(arg, depth)
+    }
+
+    fn enter_labeled(&mut self, arg: &Arg, label: Option<&str>, depth: usize) {
+        (**self).enter_labeled(arg, label, depth)
+    }
+
+    fn exit(&mut self, depth: usize) {
+        (**self).exit(depth)
+    }
+}
+
+pub fn trampoline_observed<Arg, Res, Gen, O>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg, &mut O) -> Res
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+    O: Observer<Arg> + ?Sized,
+{
+    move |arg: Arg, observer: &mut O| {
+        let mut stack = Vec::new();
+        observer.enter(&arg, 0);
+        let mut current = f(arg);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current).resume(res) {
+                GeneratorState::Yielded(arg) => {
+                    stack.push(current);
+                    observer.enter(&arg, stack.len());
+                    current = f(arg);
+                    res = Res::default();
+                }
+                GeneratorState::Complete(real_res) => {
+                    observer.exit(stack.len());
+                    match stack.pop() {
+                        None => return real_res,
+                        Some(top) => {
+                            current = top;
+                            res = real_res;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::mpsc::{self, Receiver, SendError, SyncSender};
+use std::thread::JoinHandle;
+
+use crate::{BigStackThread, PanicPayload, StackSafeError};
+
+/// Evaluates a function on a dedicated big-stack thread. Arguments are sent
+/// to the thread and results received from it over bounded channels, such
+/// that the caller can keep streaming in new inputs while earlier ones are
+/// still being evaluated. Results arrive in the order of the arguments.
+pub struct OffThread<Arg, Res> {
+    sender: Option<SyncSender<Arg>>,
+    receiver: Receiver<Res>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl<Arg, Res> OffThread<Arg, Res>
+where
+    Arg: Send + 'static,
+    Res: Send + 'static,
+{
+    /// Spawns the worker configured by `thread`. At most `capacity` arguments
+    /// and `capacity` results are buffered, beyond that `send` blocks until
+    /// the worker or the receiver catches up.
+    pub fn spawn<F>(
+        thread: BigStackThread,
+        capacity: usize,
+        mut f: F,
+    ) -> Result<Self, StackSafeError>
+    where
+        F: FnMut(Arg) -> Res + Send + 'static,
+    {
+        let (arg_sender, arg_receiver) = mpsc::sync_channel(capacity);
+        let (res_sender, res_receiver) = mpsc::sync_channel(capacity);
+        let handle = thread.spawn(move || {
+            for arg in arg_receiver {
+                if res_sender.send(f(arg)).is_err() {
+                    break;
+                }
+            }
+        })?;
+        Ok(Self {
+            sender: Some(arg_sender),
+            receiver: res_receiver,
+            handle: Some(handle),
+        })
+    }
+
+    /// Sends an argument to the worker. Fails if the worker has stopped,
+    /// e.g., because `f` panicked.
+    pub fn send(&self, arg: Arg) -> Result<(), SendError<Arg>> {
+        self.sender.as_ref().unwrap().send(arg)
+    }
+
+    /// Waits for the next result. Returns `None` if the worker has stopped
+    /// before producing it.
+    pub fn recv(&self) -> Option<Res> {
+        self.receiver.recv().ok()
+    }
+
+    pub fn try_recv(&self) -> Option<Res> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Stops accepting arguments, waits for the worker to evaluate the pending
+    /// ones and returns their results.
+    pub fn finish(mut self) -> Result<Vec<Res>, StackSafeError> {
+        self.sender = None;
+        let results = self.receiver.iter().collect();
+        match self.handle.take().unwrap().join() {
+            Ok(()) => Ok(results),
+            Err(payload) => Err(StackSafeError::FramePanicked(PanicPayload::new(payload))),
+        }
+    }
+}
+
+impl<Arg, Res> Drop for OffThread<Arg, Res> {
+    fn drop(&mut self) {
+        self.sender = None;
+        if let Some(handle) = self.handle.take() {
+            // Unblock a worker that waits for space in the result channel.
+            while self.receiver.recv().is_ok() {}
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,107 @@
+/// Consumes an owned recursive structure and builds the result without
+/// recursing in either direction. `unfold` splits an argument into a node and
+/// its children. `fold` builds the result of a node from the results of its
+/// children, in the order in which `unfold` produced them.
+///
+/// Since every argument is taken apart by `unfold`, no part of the input is
+/// dropped recursively as long as `unfold` moves the children out.
+pub fn recurse_owned<Arg, Node, Res, Children>(
+    unfold: impl Fn(Arg) -> (Node, Children),
+    fold: impl Fn(Node, Vec<Res>) -> Res,
+) -> impl Fn(Arg) -> Res
+where
+    Children: IntoIterator<Item = Arg>,
+{
+    move |arg: Arg| {
+        let mut stack = Vec::new();
+        let (node, children) = unfold(arg);
+        let mut current = (node, children.into_iter(), Vec::new());
+
+        loop {
+            if let Some(child) = current.1.next() {
+                let (node, children) = unfold(child);
+                stack.push(current);
+                current = (node, children.into_iter(), Vec::new());
+            } else {
+                let (node, _, results) = current;
+                let res = fold(node, results);
+                match stack.pop() {
+                    None => return res,
+                    Some(mut parent) => {
+                        parent.2.push(res);
+                        current = parent;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Fallible version of `recurse_owned` for converting between two recursive
+/// representations. The first error returned by `unfold` or `fold` aborts the
+/// conversion. The parts of the input that have not been unfolded yet are
+/// then taken apart with `unfold` as well, so that they are not dropped
+/// recursively either. Errors during that are ignored.
+pub fn try_recurse_owned<Arg, Node, Res, Children, E>(
+    unfold: impl Fn(Arg) -> Result<(Node, Children), E>,
+    fold: impl Fn(Node, Vec<Res>) -> Result<Res, E>,
+) -> impl Fn(Arg) -> Result<Res, E>
+where
+    Children: IntoIterator<Item = Arg>,
+{
+    move |arg: Arg| {
+        let mut stack = Vec::new();
+        let (node, children) = unfold(arg)?;
+        let mut current = (node, children.into_iter(), Vec::new());
+
+        loop {
+            if let Some(child) = current.1.next() {
+                let (node, children) = match unfold(child) {
+                    Ok(unfolded) => unfolded,
+                    Err(err) => {
+                        stack.push(current);
+                        drain(&unfold, stack);
+                        return Err(err);
+                    }
+                };
+                stack.push(current);
+                current = (node, children.into_iter(), Vec::new());
+            } else {
+                let (node, _, results) = current;
+                let res = match fold(node, results) {
+                    Ok(res) => res,
+                    Err(err) => {
+                        drain(&unfold, stack);
+                        return Err(err);
+                    }
+                };
+                match stack.pop() {
+                    None => return Ok(res),
+                    Some(mut parent) => {
+                        parent.2.push(res);
+                        current = parent;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Takes apart the children that the frames of `try_recurse_owned` have not
+/// reached yet.
+fn drain<Arg, Node, Res, Children, E>(
+    unfold: impl Fn(Arg) -> Result<(Node, Children), E>,
+    frames: Vec<(Node, Children::IntoIter, Vec<Res>)>,
+) where
+    Children: IntoIterator<Item = Arg>,
+{
+    let mut pending: Vec<_> = frames.into_iter().map(|frame| frame.1).collect();
+    while let Some(mut children) = pending.pop() {
+        if let Some(child) = children.next() {
+            pending.push(children);
+            if let Ok((_, grandchildren)) = unfold(child) {
+                pending.push(grandchildren.into_iter());
+            }
+        }
+    }
+}
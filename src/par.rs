@@ -0,0 +1,82 @@
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::ops::Generator;
+
+use crate::iter::preorder;
+use crate::many::drive_many;
+use crate::ManyCall;
+
+/// Like `trampoline_many`, but evaluates the arguments of a `ManyCall::many` in
+/// parallel on the rayon thread pool. Each parallel evaluation runs its own
+/// frame stack and consumes one level of `max_par_depth`. Once that budget is
+/// exhausted, batches are evaluated sequentially.
+///
+/// The native stack usage is not bounded by `max_par_depth` drivers alone.
+/// Rayon splits a batch of `n` arguments in about `log2(n)` nested joins and
+/// each parallel evaluation nests its driver on top of them, on whichever
+/// worker runs it. A worker that waits for a join may also run stolen jobs on
+/// top of its stack. A worker's stack hence holds up to `max_par_depth`
+/// drivers, each above the split depth of its batch, plus stolen jobs, so
+/// `max_par_depth` should stay small, e.g., around `log2` of the number of
+/// threads.
+pub fn trampoline_many_par<Arg, Res, Gen, F>(f: F, max_par_depth: usize) -> impl Fn(Arg) -> Res
+where
+    Arg: Send,
+    Res: Send,
+    Gen: Generator<Vec<Res>, Yield = ManyCall<Arg>, Return = Res> + Unpin,
+    F: Fn(Arg) -> Gen + Sync,
+{
+    move |arg: Arg| drive_par(&f, arg, max_par_depth)
+}
+
+fn drive_par<Arg, Res, Gen, F>(f: &F, arg: Arg, max_par_depth: usize) -> Res
+where
+    Arg: Send,
+    Res: Send,
+    Gen: Generator<Vec<Res>, Yield = ManyCall<Arg>, Return = Res> + Unpin,
+    F: Fn(Arg) -> Gen + Sync,
+{
+    drive_many(f, arg, |args: Vec<Arg>| {
+        if max_par_depth == 0 || args.len() < 2 {
+            Err(args)
+        } else {
+            Ok(args
+                .into_par_iter()
+                .map(|arg| drive_par(f, arg, max_par_depth - 1))
+                .collect())
+        }
+    })
+}
+
+/// Returns a parallel iterator over `root` and all nodes below it. The nodes
+/// are discovered sequentially by `iter::preorder`, so the depth of the tree
+/// does not matter, and then split evenly among rayon's threads regardless
+/// of the shape of the tree. Nodes reachable along several paths are visited
+/// once per path.
+pub fn par_nodes<T, I>(root: T, children: impl FnMut(&T) -> I) -> rayon::vec::IntoIter<T>
+where
+    T: Send,
+    I: IntoIterator<Item = T>,
+{
+    preorder(root, children).collect::<Vec<_>>().into_par_iter()
+}
+
+/// Like `par_nodes`, but for graphs: every node reachable from `root` is
+/// visited exactly once, even if the graph has cycles.
+pub fn par_reachable<T, I>(root: T, mut children: impl FnMut(&T) -> I) -> rayon::vec::IntoIter<T>
+where
+    T: Clone + Eq + Hash + Send,
+    I: IntoIterator<Item = T>,
+{
+    let mut seen = HashSet::new();
+    let mut nodes = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if seen.insert(node.clone()) {
+            stack.extend(children(&node));
+            nodes.push(node);
+        }
+    }
+    nodes.into_par_iter()
+}
@@ -0,0 +1,106 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use crate::hook::PathRecorder;
+
+/// The labels of all calls from the initial one down to the current frame,
+/// as maintained by `trampoline_with_labels`.
+pub struct Path<L> {
+    labels: Rc<RefCell<Vec<L>>>,
+}
+
+impl<L> Clone for Path<L> {
+    fn clone(&self) -> Self {
+        Self {
+            labels: Rc::clone(&self.labels),
+        }
+    }
+}
+
+impl<L> Path<L> {
+    /// The number of calls on the path, including the current one.
+    pub fn len(&self) -> usize {
+        self.labels.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Calls `f` with the labels on the path, starting from the initial call.
+    pub fn with<R>(&self, f: impl FnOnce(&[L]) -> R) -> R {
+        f(&self.labels.borrow())
+    }
+
+    pub fn to_vec(&self) -> Vec<L>
+    where
+        L: Clone,
+    {
+        self.labels.borrow().clone()
+    }
+}
+
+/// Like `trampoline`, but every frame receives a `Path` through which it can
+/// query the labels of its ancestors and itself. The label of a call is
+/// computed by `label` from its argument.
+///
+/// The labels are also printed by the hook set up by `install_panic_hook`
+/// when a frame panics.
+pub fn trampoline_with_labels<Arg, L, Res, Gen>(
+    label: impl Fn(&Arg) -> L,
+    f: impl Fn(Path<L>, Arg) -> Gen,
+) -> impl Fn(Arg) -> Res
+where
+    L: fmt::Debug,
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    move |arg: Arg| {
+        let recorder = PathRecorder::new();
+        let first = label(&arg);
+        recorder.push(&first);
+        let path = Path {
+            labels: Rc::new(RefCell::new(vec![first])),
+        };
+        let mut stack = Vec::new();
+        let mut current = f(path.clone(), arg);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current).resume(res) {
+                GeneratorState::Yielded(arg) => {
+                    let label = label(&arg);
+                    recorder.push(&label);
+                    path.labels.borrow_mut().push(label);
+                    stack.push(current);
+                    current = f(path.clone(), arg);
+                    res = Res::default();
+                }
+                GeneratorState::Complete(real_res) => {
+                    recorder.pop();
+                    path.labels.borrow_mut().pop();
+                    match stack.pop() {
+                        None => return real_res,
+                        Some(top) => {
+                            current = top;
+                            res = real_res;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Like `trampoline_with_labels`, but uses the arguments themselves as labels.
+pub fn trampoline_with_path<Arg, Res, Gen>(f: impl Fn(Path<Arg>, Arg) -> Gen) -> impl Fn(Arg) -> Res
+where
+    Arg: Clone + fmt::Debug,
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    trampoline_with_labels(Arg::clone, f)
+}
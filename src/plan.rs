@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Evaluates a recursion whose calls only depend on their argument in two
+/// phases, which is usually faster than resuming frames for memo-heavy
+/// dynamic programs like edit distance or knapsack. First, the DAG of all
+/// arguments reachable via `dependencies` from the initial one is discovered
+/// and ordered topologically. Then, every argument is evaluated exactly
+/// once, after all of its dependencies, by passing their results, in the
+/// order listed by `dependencies`, to `combine`.
+///
+/// # Panics
+///
+/// Panics if an argument depends on itself.
+pub fn recurse_planned<Arg, Res, D>(
+    dependencies: impl Fn(&Arg) -> D,
+    combine: impl Fn(&Arg, &[Res]) -> Res,
+) -> impl Fn(Arg) -> Res
+where
+    Arg: Clone + Eq + Hash,
+    Res: Clone,
+    D: IntoIterator<Item = Arg>,
+{
+    move |arg: Arg| {
+        // The arguments in topological order with the positions of their
+        // dependencies in that order.
+        let mut order: Vec<(Arg, Vec<usize>)> = Vec::new();
+        // `None` marks arguments whose dependencies are still discovered.
+        let mut positions: HashMap<Arg, Option<usize>> = HashMap::new();
+        positions.insert(arg.clone(), None);
+        let mut stack = vec![(arg.clone(), dependencies(&arg).into_iter(), Vec::new())];
+
+        while let Some((_, pending, deps)) = stack.last_mut() {
+            match pending.next() {
+                Some(dep) => match positions.get(&dep) {
+                    Some(Some(position)) => deps.push(*position),
+                    Some(None) => panic!("cycle detected in `recurse_planned`"),
+                    None => {
+                        positions.insert(dep.clone(), None);
+                        let pending = dependencies(&dep).into_iter();
+                        stack.push((dep, pending, Vec::new()));
+                    }
+                },
+                None => {
+                    let (arg, _, deps) = stack.pop().unwrap();
+                    let position = order.len();
+                    positions.insert(arg.clone(), Some(position));
+                    order.push((arg, deps));
+                    if let Some((_, _, parent_deps)) = stack.last_mut() {
+                        parent_deps.push(position);
+                    }
+                }
+            }
+        }
+
+        let mut results: Vec<Res> = Vec::with_capacity(order.len());
+        let mut inputs = Vec::new();
+        for (arg, deps) in &order {
+            inputs.clear();
+            inputs.extend(deps.iter().map(|&position| results[position].clone()));
+            results.push(combine(arg, &inputs));
+        }
+        results.pop().unwrap()
+    }
+}
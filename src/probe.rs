@@ -0,0 +1,110 @@
+use crate::StackSafeError;
+
+/// The number of bytes `stack_probe!()` requires to be left on the stack.
+pub const DEFAULT_RED_ZONE: usize = 32 * 1024;
+
+#[cfg(target_os = "linux")]
+fn stack_limit() -> Option<usize> {
+    use std::cell::Cell;
+
+    thread_local! {
+        static LIMIT: Cell<Option<Option<usize>>> = const { Cell::new(None) };
+    }
+
+    LIMIT.with(|limit| {
+        if let Some(cached) = limit.get() {
+            return cached;
+        }
+        // SAFETY: `attr` is initialized by `pthread_getattr_np` before it is
+        // read and destroyed afterwards.
+        let computed = unsafe {
+            let mut attr: libc::pthread_attr_t = std::mem::zeroed();
+            if libc::pthread_getattr_np(libc::pthread_self(), &mut attr) != 0 {
+                None
+            } else {
+                let mut addr = std::ptr::null_mut();
+                let mut size = 0;
+                let res = libc::pthread_attr_getstack(&attr, &mut addr, &mut size);
+                libc::pthread_attr_destroy(&mut attr);
+                if res == 0 {
+                    Some(addr as usize)
+                } else {
+                    None
+                }
+            }
+        };
+        limit.set(Some(computed));
+        computed
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn stack_limit() -> Option<usize> {
+    // SAFETY: Both functions only read the attributes of the current thread.
+    unsafe {
+        let thread = libc::pthread_self();
+        let top = libc::pthread_get_stackaddr_np(thread) as usize;
+        let size = libc::pthread_get_stacksize_np(thread);
+        Some(top - size)
+    }
+}
+
+#[cfg(windows)]
+fn stack_limit() -> Option<usize> {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentThreadStackLimits(low_limit: *mut usize, high_limit: *mut usize);
+    }
+
+    let mut low = 0;
+    let mut high = 0;
+    // SAFETY: The function only writes to the two out-parameters.
+    unsafe { GetCurrentThreadStackLimits(&mut low, &mut high) };
+    Some(low)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn stack_limit() -> Option<usize> {
+    None
+}
+
+/// Estimates the number of bytes left on the native stack of the current
+/// thread. Returns `None` on platforms where this is unknown, e.g., wasm.
+///
+/// The estimate ignores guard pages and is only as precise as the stack
+/// bounds reported by the operating system.
+#[inline(never)]
+pub fn remaining_stack() -> Option<usize> {
+    let marker = 0u8;
+    let sp = &marker as *const u8 as usize;
+    stack_limit().map(|limit| sp.saturating_sub(limit))
+}
+
+thread_local! {
+    static OVERFLOW: std::cell::Cell<Option<(Option<usize>, usize)>> = const {
+        std::cell::Cell::new(None)
+    };
+}
+
+/// Returns the depth and remaining stack reported by the last failed probe on
+/// the current thread, if any.
+pub(crate) fn take_overflow() -> Option<(Option<usize>, usize)> {
+    OVERFLOW.with(|overflow| overflow.take())
+}
+
+#[doc(hidden)]
+#[cold]
+pub fn __probe_failed(remaining: usize, depth: Option<usize>) -> ! {
+    OVERFLOW.with(|overflow| overflow.set(Some((depth, remaining))));
+    panic!("{}", StackSafeError::OverflowDetected { depth, remaining })
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn __probe(red_zone: usize, depth: Option<usize>) {
+    if let Some(remaining) = remaining_stack() {
+        if remaining < red_zone {
+            __probe_failed(remaining, depth);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::Observer;
+
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub elapsed: Duration,
+    pub depth: usize,
+    pub label: String,
+}
+
+enum Event {
+    Enter,
+    Exit(Duration),
+}
+
+/// Records a sample whenever a call is entered during a run of
+/// `trampoline_observed`. The samples can be exported as a depth timeline or
+/// in the folded stack format consumed by `inferno` and `flamegraph.pl`.
+pub struct Profiler<L> {
+    label: L,
+    start: Option<Instant>,
+    samples: Vec<Sample>,
+    events: Vec<Event>,
+}
+
+impl<L> Profiler<L> {
+    pub fn new(label: L) -> Self {
+        Self {
+            label,
+            start: None,
+            samples: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    /// Writes one line `<elapsed ns> <depth> <label>` per sample.
+    pub fn write_timeline(&self, w: &mut impl io::Write) -> io::Result<()> {
+        for sample in &self.samples {
+            writeln!(
+                w,
+                "{} {} {}",
+                sample.elapsed.as_nanos(),
+                sample.depth,
+                sample.label
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the time spent in each stack of labels, excluding the time spent
+    /// in callees, in nanoseconds. Building the stacks takes time proportional
+    /// to the depth of each call, so this is meant for moderately deep runs.
+    /// Semicolons in labels are written as colons and whitespace as
+    /// underscores, since the format reserves them.
+    pub fn write_folded(&self, w: &mut impl io::Write) -> io::Result<()> {
+        let mut totals: HashMap<String, u128> = HashMap::new();
+        let mut order = Vec::new();
+        let mut path = String::new();
+        // For every active call: length of `path` before it, its start time and
+        // the time spent in its callees.
+        let mut active: Vec<(usize, Duration, Duration)> = Vec::new();
+        let mut samples = self.samples.iter();
+
+        for event in &self.events {
+            match event {
+                Event::Enter => {
+                    let sample = samples.next().unwrap();
+                    active.push((path.len(), sample.elapsed, Duration::ZERO));
+                    if !path.is_empty() {
+                        path.push(';');
+                    }
+                    // `;` separates the frames and whitespace the count.
+                    path.extend(sample.label.chars().map(|c| match c {
+                        ';' => ':',
+                        c if c.is_whitespace() => '_',
+                        c => c,
+                    }));
+                }
+                Event::Exit(elapsed) => {
+                    let (len, start, callees) = active.pop().unwrap();
+                    let total = *elapsed - start;
+                    let own = total.saturating_sub(callees).as_nanos();
+                    match totals.get_mut(&path) {
+                        Some(sum) => *sum += own,
+                        None => {
+                            order.push(path.clone());
+                            totals.insert(path.clone(), own);
+                        }
+                    }
+                    path.truncate(len);
+                    if let Some(caller) = active.last_mut() {
+                        caller.2 += total;
+                    }
+                }
+            }
+        }
+
+        for path in order {
+            writeln!(w, "{} {}", path, totals[&path])?;
+        }
+        Ok(())
+    }
+}
+
+impl<Arg, L: Fn(&Arg) -> String> Observer<Arg> for Profiler<L> {
+    fn enter(&mut self, arg: &Arg, depth: usize) {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        self.samples.push(Sample {
+            elapsed: start.elapsed(),
+            depth,
+            label: (self.label)(arg),
+        });
+        self.events.push(Event::Enter);
+    }
+
+    fn exit(&mut self, _depth: usize) {
+        let elapsed = self.start.map_or(Duration::ZERO, |start| start.elapsed());
+        self.events.push(Event::Exit(elapsed));
+    }
+}
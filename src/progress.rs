@@ -0,0 +1,100 @@
+use crate::Observer;
+
+/// A snapshot of the progress of a recursion.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ProgressUpdate {
+    /// The number of frames that have completed so far.
+    pub completed: u64,
+    /// The estimated number of frames overall, if known.
+    pub total: Option<u64>,
+    /// The depth of the frame that completed last.
+    pub depth: usize,
+}
+
+impl ProgressUpdate {
+    /// Returns the completed fraction of the estimated total. It is capped at
+    /// 1 because the estimate may be too low.
+    pub fn fraction(&self) -> Option<f64> {
+        let total = self.total?;
+        if total == 0 {
+            return Some(1.0);
+        }
+        Some((self.completed as f64 / total as f64).min(1.0))
+    }
+}
+
+/// Reports the progress of a run of `trampoline_observed` to `report`. An
+/// update is reported after every `interval` completed frames and whenever a
+/// frame at depth 0 completes, so the last update of a run is always
+/// reported.
+pub struct Progress<R> {
+    report: R,
+    total: Option<u64>,
+    interval: u64,
+    completed: u64,
+    /// The number of frames completed since the last update.
+    unreported: u64,
+}
+
+impl<R: FnMut(&ProgressUpdate)> Progress<R> {
+    pub fn new(report: R) -> Self {
+        Self {
+            report,
+            total: None,
+            interval: 1000,
+            completed: 0,
+            unreported: 0,
+        }
+    }
+
+    /// Sets the estimated number of frames, e.g., the number of nodes of the
+    /// structure the recursion traverses.
+    pub fn total(mut self, estimate: u64) -> Self {
+        self.total = Some(estimate);
+        self
+    }
+
+    /// Reports an update every `interval` completed frames instead of every
+    /// 1000 ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is 0.
+    pub fn interval(mut self, interval: u64) -> Self {
+        assert!(interval > 0, "the interval must be positive");
+        self.interval = interval;
+        self
+    }
+
+    pub fn completed(&self) -> u64 {
+        self.completed
+    }
+}
+
+impl<Arg, R: FnMut(&ProgressUpdate)> Observer<Arg> for Progress<R> {
+    fn exit(&mut self, depth: usize) {
+        self.completed += 1;
+        self.unreported += 1;
+        if self.unreported == self.interval || depth == 0 {
+            self.unreported = 0;
+            (self.report)(&ProgressUpdate {
+                completed: self.completed,
+                total: self.total,
+                depth,
+            });
+        }
+    }
+}
+
+/// Shows the reported progress on `bar`, for use as the callback of
+/// `Progress`. The length of the bar is set to the estimated total, if any.
+#[cfg(feature = "indicatif")]
+pub fn progress_bar(bar: indicatif::ProgressBar) -> impl FnMut(&ProgressUpdate) {
+    move |update: &ProgressUpdate| {
+        if let Some(total) = update.total {
+            bar.set_length(total.max(update.completed));
+        }
+        bar.set_position(update.completed);
+        bar.set_message(format!("depth {}", update.depth));
+    }
+}
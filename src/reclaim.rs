@@ -0,0 +1,127 @@
+use std::ops::Generator;
+
+use crate::{try_drive, FrameStack};
+
+/// How a `ReclaimingStack` gives back memory after a call.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Shrink {
+    /// Keeps all capacity, so a single deep call holds on to its memory until
+    /// the stack is dropped or shrunk explicitly.
+    Never,
+    /// Keeps capacity for exactly as many frames as the last call needed.
+    ToFit,
+    /// Keeps capacity for the deepest recent call. Every given number of
+    /// calls that stay below that depth, the capacity is halved, but never
+    /// below the depth of the last call.
+    Decay(usize),
+}
+
+/// A frame stack that is reused across calls and frees unused capacity
+/// according to a `Shrink` policy when `reclaim` is called after a call.
+pub struct ReclaimingStack<G> {
+    frames: Vec<G>,
+    policy: Shrink,
+    /// The largest depth since the last `reclaim`.
+    peak: usize,
+    /// The depth `Shrink::Decay` keeps capacity for.
+    high_water: usize,
+    /// The calls since `high_water` was last reached or halved.
+    calls: usize,
+}
+
+impl<G> ReclaimingStack<G> {
+    pub fn new(policy: Shrink) -> Self {
+        Self {
+            frames: Vec::new(),
+            policy,
+            peak: 0,
+            high_water: 0,
+            calls: 0,
+        }
+    }
+
+    pub fn policy(&self) -> Shrink {
+        self.policy
+    }
+
+    /// The number of frames the stack can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.frames.capacity()
+    }
+
+    /// Applies the policy to the capacity. Meant to be called between calls,
+    /// once the stack is empty.
+    pub fn reclaim(&mut self) {
+        let peak = std::mem::replace(&mut self.peak, self.frames.len());
+        let keep = match self.policy {
+            Shrink::Never => return,
+            Shrink::ToFit => peak,
+            Shrink::Decay(period) => {
+                if peak >= self.high_water {
+                    self.high_water = peak;
+                    self.calls = 0;
+                } else {
+                    self.calls += 1;
+                    if self.calls >= period {
+                        self.high_water = (self.high_water / 2).max(peak);
+                        self.calls = 0;
+                    }
+                }
+                self.high_water
+            }
+        };
+        self.frames.shrink_to(keep);
+    }
+
+    /// Frees all capacity that is not in use right now, regardless of the
+    /// policy.
+    pub fn shrink(&mut self) {
+        self.frames.shrink_to_fit();
+        self.peak = self.frames.len();
+        self.high_water = self.frames.len();
+        self.calls = 0;
+    }
+}
+
+impl<G> FrameStack<G> for ReclaimingStack<G> {
+    fn push(&mut self, frame: G) -> Result<(), G> {
+        self.frames.push(frame);
+        self.peak = self.peak.max(self.frames.len());
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<G> {
+        self.frames.pop()
+    }
+
+    fn last_mut(&mut self) -> Option<&mut G> {
+        self.frames.last_mut()
+    }
+
+    fn len(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+/// Like `trampoline`, but keeps one frame stack for all calls and frees
+/// its unused capacity after every call according to `policy`, so that a
+/// single pathological input does not make a long-lived function hold on to
+/// its peak memory forever.
+pub fn trampoline_reclaiming<Arg, Res, Gen>(
+    policy: Shrink,
+    f: impl Fn(Arg) -> Gen,
+) -> impl FnMut(Arg) -> Res
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    let mut stack = ReclaimingStack::new(policy);
+    move |arg: Arg| {
+        let res = match try_drive(&f, arg, &mut stack) {
+            Ok(res) => res,
+            Err(_) => unreachable!("a `ReclaimingStack` never refuses frames"),
+        };
+        stack.reclaim();
+        res
+    }
+}
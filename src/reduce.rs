@@ -0,0 +1,49 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+/// Like `trampoline`, but for folds with an associative `combine`. Frames
+/// only yield their children and return their own contribution. The driver
+/// combines each child's result directly into the parent's accumulator, so
+/// parents are resumed with `()` rather than with results.
+///
+/// The own contribution of a frame comes first, followed by the results of
+/// its children in the order they were yielded.
+pub fn recurse_reduce<Arg, Res, Gen>(
+    f: impl Fn(Arg) -> Gen,
+    combine: impl Fn(Res, Res) -> Res,
+) -> impl Fn(Arg) -> Res
+where
+    Gen: Generator<(), Yield = Arg, Return = Res> + Unpin,
+{
+    move |arg: Arg| {
+        let mut stack = Vec::new();
+        let mut current = f(arg);
+        let mut acc: Option<Res> = None;
+
+        loop {
+            match Pin::new(&mut current).resume(()) {
+                GeneratorState::Yielded(arg) => {
+                    stack.push((current, acc));
+                    current = f(arg);
+                    acc = None;
+                }
+                GeneratorState::Complete(own) => {
+                    let res = match acc {
+                        None => own,
+                        Some(children) => combine(own, children),
+                    };
+                    match stack.pop() {
+                        None => return res,
+                        Some((parent, parent_acc)) => {
+                            current = parent;
+                            acc = Some(match parent_acc {
+                                None => res,
+                                Some(parent_acc) => combine(parent_acc, res),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
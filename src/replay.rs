@@ -0,0 +1,155 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+/// A step of a recursion in a `Trace`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TraceEvent<Arg, Res> {
+    /// A call on `Arg`, i.e., the initial call or a frame yielding `Arg`.
+    Call(Arg),
+    /// A frame returning `Res`, with which its caller is resumed.
+    Return(Res),
+}
+
+/// The calls and returns of a run of `trampoline_recorded`, in order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trace<Arg, Res> {
+    events: Vec<TraceEvent<Arg, Res>>,
+}
+
+impl<Arg, Res> Trace<Arg, Res> {
+    pub fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    pub fn events(&self) -> &[TraceEvent<Arg, Res>] {
+        &self.events
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+impl<Arg, Res> Default for Trace<Arg, Res> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where a replayed run first deviated from its trace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Divergence<Arg, Res> {
+    /// The index of the event in the trace.
+    pub step: usize,
+    /// The event in the trace, unless the trace has ended.
+    pub expected: Option<TraceEvent<Arg, Res>>,
+    /// The event of the run, unless the run has ended.
+    pub actual: Option<TraceEvent<Arg, Res>>,
+}
+
+/// Like `trampoline`, but appends the initial call and every call and
+/// return after it to a `Trace`. The trace can later be checked against a
+/// run of the same or a modified `f` with `replay`, e.g., as a golden trace
+/// in a regression test.
+pub fn trampoline_recorded<Arg, Res, Gen>(
+    f: impl Fn(Arg) -> Gen,
+) -> impl Fn(Arg, &mut Trace<Arg, Res>) -> Res
+where
+    Arg: Clone,
+    Res: Clone + Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    move |arg: Arg, trace: &mut Trace<Arg, Res>| {
+        let mut stack = Vec::new();
+        trace.events.push(TraceEvent::Call(arg.clone()));
+        let mut current = f(arg);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current).resume(res) {
+                GeneratorState::Yielded(arg) => {
+                    trace.events.push(TraceEvent::Call(arg.clone()));
+                    stack.push(current);
+                    current = f(arg);
+                    res = Res::default();
+                }
+                GeneratorState::Complete(real_res) => {
+                    trace.events.push(TraceEvent::Return(real_res.clone()));
+                    match stack.pop() {
+                        None => return real_res,
+                        Some(top) => {
+                            current = top;
+                            res = real_res;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Re-drives the frames of `f` from the initial call in `trace` and checks
+/// every call and return against the trace. Returns the result of the run if
+/// it matches the trace exactly, and the first difference otherwise, which
+/// stops the run.
+///
+/// # Panics
+///
+/// Panics if `trace` does not start with a call.
+pub fn replay<Arg, Res, Gen>(
+    f: impl Fn(Arg) -> Gen,
+    trace: &Trace<Arg, Res>,
+) -> Result<Res, Divergence<Arg, Res>>
+where
+    Arg: Clone + PartialEq,
+    Res: Clone + Default + PartialEq,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    let diverge = |step: usize, actual: Option<TraceEvent<Arg, Res>>| Divergence {
+        step,
+        expected: trace.events.get(step).cloned(),
+        actual,
+    };
+    let arg = match trace.events.first() {
+        Some(TraceEvent::Call(arg)) => arg.clone(),
+        _ => panic!("a trace starts with the initial call"),
+    };
+    let mut step = 1;
+    let mut stack = Vec::new();
+    let mut current = f(arg);
+    let mut res = Res::default();
+
+    loop {
+        match Pin::new(&mut current).resume(res) {
+            GeneratorState::Yielded(arg) => {
+                match trace.events.get(step) {
+                    Some(TraceEvent::Call(expected)) if *expected == arg => {}
+                    _ => return Err(diverge(step, Some(TraceEvent::Call(arg)))),
+                }
+                step += 1;
+                stack.push(current);
+                current = f(arg);
+                res = Res::default();
+            }
+            GeneratorState::Complete(real_res) => {
+                match trace.events.get(step) {
+                    Some(TraceEvent::Return(expected)) if *expected == real_res => {}
+                    _ => return Err(diverge(step, Some(TraceEvent::Return(real_res)))),
+                }
+                step += 1;
+                match stack.pop() {
+                    None if step < trace.events.len() => return Err(diverge(step, None)),
+                    None => return Ok(real_res),
+                    Some(top) => {
+                        current = top;
+                        res = real_res;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::alloc::{self, Layout};
+use std::cell::Cell;
+use std::mem::{self, ManuallyDrop};
+use std::ops::Generator;
+use std::ptr::NonNull;
+
+use crate::drive;
+
+/// An empty allocation that was the buffer of a `Vec`.
+struct Buffer {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+thread_local! {
+    static RETAINED: Cell<Option<Buffer>> = const { Cell::new(None) };
+}
+
+/// Takes the retained buffer of this thread as an empty `Vec<G>` if its
+/// layout fits `G`.
+fn take<G>() -> Vec<G> {
+    if mem::size_of::<G>() == 0 {
+        return Vec::new();
+    }
+    match RETAINED.try_with(Cell::take) {
+        Ok(Some(buffer))
+            if buffer.layout.align() == mem::align_of::<G>()
+                && buffer.layout.size() % mem::size_of::<G>() == 0 =>
+        {
+            let buffer = ManuallyDrop::new(buffer);
+            let capacity = buffer.layout.size() / mem::size_of::<G>();
+            // SAFETY: The buffer was allocated by the global allocator with
+            // the layout of an array of `capacity` values of `G`.
+            unsafe { Vec::from_raw_parts(buffer.ptr.as_ptr().cast(), 0, capacity) }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Retains the buffer of `stack`, which must be empty, unless it exceeds
+/// `max_retained` bytes.
+fn give_back<G>(stack: Vec<G>, max_retained: usize) {
+    debug_assert!(stack.is_empty());
+    let layout = Layout::array::<G>(stack.capacity()).unwrap();
+    if layout.size() == 0 || layout.size() > max_retained {
+        return;
+    }
+    let mut stack = ManuallyDrop::new(stack);
+    let buffer = Buffer {
+        ptr: NonNull::new(stack.as_mut_ptr().cast()).unwrap(),
+        layout,
+    };
+    // If the thread local is gone, the buffer is simply freed.
+    let _ = RETAINED.try_with(|retained| retained.set(Some(buffer)));
+}
+
+/// Like `trampoline`, but keeps the buffer of the frame stack in a
+/// thread-local after every call and reuses it in the next call on the same
+/// thread, e.g., for evaluations per request. Buffers larger than
+/// `max_retained` bytes are freed instead. Every thread retains at most one
+/// buffer, which is shared by all trampolines and only reused by those whose
+/// frames fit its layout.
+pub fn trampoline_reusing<Arg, Res, Gen>(
+    max_retained: usize,
+    f: impl Fn(Arg) -> Gen,
+) -> impl Fn(Arg) -> Res
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    move |arg: Arg| {
+        let mut stack = take();
+        let res = drive(&f, arg, &mut stack);
+        give_back(stack, max_retained);
+        res
+    }
+}
+
+/// Returns the size in bytes of the buffer the current thread retains for
+/// `trampoline_reusing`.
+pub fn retained_bytes() -> usize {
+    RETAINED
+        .try_with(|retained| {
+            let buffer = retained.take();
+            let size = buffer.as_ref().map_or(0, |buffer| buffer.layout.size());
+            retained.set(buffer);
+            size
+        })
+        .unwrap_or(0)
+}
+
+/// Frees the buffer the current thread retains for `trampoline_reusing`.
+pub fn release_retained() {
+    let _ = RETAINED.try_with(Cell::take);
+}
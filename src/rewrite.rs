@@ -0,0 +1,139 @@
+//! Term rewriting.
+//!
+//! `rewrite` applies a rule to a term and all of its subterms until no part
+//! of the term can be rewritten anymore. Neither the traversal nor the
+//! normalization of the terms the rule produces recurses, so terms and
+//! chains of rewrites can be arbitrarily deep.
+//!
+//! Terms are taken apart and put back together with the `Term` trait. A rule
+//! takes ownership of the term it rewrites, so it can move the children of
+//! the term into its result instead of cloning them.
+use std::vec;
+
+use crate::{Never, Partial, Stop};
+
+pub trait Term: Sized {
+    /// A term without its children.
+    type Head;
+
+    fn into_parts(self) -> (Self::Head, Vec<Self>);
+
+    fn from_parts(head: Self::Head, children: Vec<Self>) -> Self;
+}
+
+/// The order in which the subterms of a term are rewritten.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Strategy {
+    /// Rewrites a term only once all of its subterms are in normal form.
+    Innermost,
+    /// Rewrites a term before its subterms, and again after them if they
+    /// made it rewritable. Subterms that a rewrite discards are never
+    /// visited.
+    Outermost,
+}
+
+/// A rule that can be combined with others of different types.
+pub type BoxedRule<'a, T> = Box<dyn FnMut(T) -> Result<T, T> + 'a>;
+
+/// Combines `rules` into a single rule, which applies the first of them that
+/// applies.
+pub fn first_of<'a, T>(mut rules: Vec<BoxedRule<'a, T>>) -> impl FnMut(T) -> Result<T, T> + 'a
+where
+    T: 'a,
+{
+    move |mut term: T| {
+        for rule in &mut rules {
+            match rule(term) {
+                Ok(rewritten) => return Ok(rewritten),
+                Err(unchanged) => term = unchanged,
+            }
+        }
+        Err(term)
+    }
+}
+
+/// Every entry is a term whose children are being normalized, split into its
+/// head, the children in normal form and the remaining ones.
+type Stack<T> = Vec<(<T as Term>::Head, Vec<T>, vec::IntoIter<T>)>;
+
+/// Rewrites `term` until no part of it can be rewritten. `rule` either
+/// returns the rewritten term or gives the term back unchanged. If the
+/// rewriting does not terminate, neither does this function.
+pub fn rewrite<T: Term>(term: T, strategy: Strategy, rule: impl FnMut(T) -> Result<T, T>) -> T {
+    match rewrite_until(term, strategy, rule, &mut Never) {
+        Ok(term) => term,
+        Err(_) => unreachable!("the rewriting never stops"),
+    }
+}
+
+/// Like `rewrite`, but checks `stop` before every application of `rule`. If
+/// it stops the rewriting, the term is returned with all rewrites made up to
+/// that point.
+pub fn rewrite_until<T, S>(
+    term: T,
+    strategy: Strategy,
+    mut rule: impl FnMut(T) -> Result<T, T>,
+    stop: &mut S,
+) -> Result<T, Partial<T>>
+where
+    T: Term,
+    S: Stop + ?Sized,
+{
+    let mut stack: Stack<T> = Vec::new();
+    let mut term = term;
+
+    loop {
+        if strategy == Strategy::Outermost {
+            loop {
+                if let Some(reason) = stop.check() {
+                    let value = plug(term, stack);
+                    return Err(Partial { value, reason });
+                }
+                match rule(term) {
+                    Ok(rewritten) => term = rewritten,
+                    Err(unchanged) => {
+                        term = unchanged;
+                        break;
+                    }
+                }
+            }
+        }
+        let (head, children) = term.into_parts();
+        stack.push((
+            head,
+            Vec::with_capacity(children.len()),
+            children.into_iter(),
+        ));
+        term = loop {
+            let (_, _, todo) = stack.last_mut().unwrap();
+            if let Some(child) = todo.next() {
+                break child;
+            }
+            let (head, done, _) = stack.pop().unwrap();
+            let normal = T::from_parts(head, done);
+            if let Some(reason) = stop.check() {
+                let value = plug(normal, stack);
+                return Err(Partial { value, reason });
+            }
+            match rule(normal) {
+                // The rewritten term takes the place of the old one and is
+                // normalized in turn.
+                Ok(rewritten) => break rewritten,
+                Err(normal) => match stack.last_mut() {
+                    None => return Ok(normal),
+                    Some((_, done, _)) => done.push(normal),
+                },
+            }
+        };
+    }
+}
+
+/// Puts `term` back into the terms on `stack`.
+fn plug<T: Term>(mut term: T, stack: Stack<T>) -> T {
+    for (head, mut children, todo) in stack.into_iter().rev() {
+        children.push(term);
+        children.extend(todo);
+        term = T::from_parts(head, children);
+    }
+    term
+}
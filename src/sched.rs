@@ -0,0 +1,297 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+use crate::many::ManyKind;
+use crate::ManyCall;
+
+/// Decides which runnable task `trampoline_scheduled` steps next.
+pub trait Scheduler<T> {
+    fn push(&mut self, task: T);
+
+    fn pop(&mut self) -> Option<T>;
+}
+
+/// Depth-first scheduling. The children of a frame are started in the order
+/// of their arguments and each child's subtree is finished before the next
+/// child is started, just like with `trampoline_many`.
+pub struct Dfs<T> {
+    stack: Vec<T>,
+    fresh: usize,
+}
+
+impl<T> Dfs<T> {
+    pub fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            fresh: 0,
+        }
+    }
+}
+
+impl<T> Default for Dfs<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Scheduler<T> for Dfs<T> {
+    fn push(&mut self, task: T) {
+        self.stack.push(task);
+        self.fresh += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        // All tasks pushed since the last `pop` come from the same step. They
+        // are reversed such that the first one of them is popped first.
+        let len = self.stack.len();
+        self.stack[len - self.fresh..].reverse();
+        self.fresh = 0;
+        self.stack.pop()
+    }
+}
+
+/// Breadth-first scheduling. Tasks are stepped in the order in which they
+/// become runnable.
+pub struct Bfs<T> {
+    queue: VecDeque<T>,
+}
+
+impl<T> Bfs<T> {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+        }
+    }
+}
+
+impl<T> Default for Bfs<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Scheduler<T> for Bfs<T> {
+    fn push(&mut self, task: T) {
+        self.queue.push_back(task);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.queue.pop_front()
+    }
+}
+
+struct Entry<K, T> {
+    key: K,
+    seq: u64,
+    task: T,
+}
+
+impl<K: Ord, T> PartialEq for Entry<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord, T> Eq for Entry<K, T> {}
+
+impl<K: Ord, T> PartialOrd for Entry<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, T> Ord for Entry<K, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key
+            .cmp(&other.key)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Steps the task with the greatest key first. Tasks with equal keys are
+/// stepped in the order in which they become runnable.
+pub struct Priority<T, K, F> {
+    heap: BinaryHeap<Entry<K, T>>,
+    seq: u64,
+    key: F,
+}
+
+impl<T, K: Ord, F: FnMut(&T) -> K> Priority<T, K, F> {
+    pub fn new(key: F) -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            seq: 0,
+            key,
+        }
+    }
+}
+
+impl<T, K: Ord, F: FnMut(&T) -> K> Scheduler<T> for Priority<T, K, F> {
+    fn push(&mut self, task: T) {
+        let key = (self.key)(&task);
+        self.heap.push(Entry {
+            key,
+            seq: self.seq,
+            task,
+        });
+        self.seq += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.heap.pop().map(|entry| entry.task)
+    }
+}
+
+enum TaskKind<Arg> {
+    Start {
+        arg: Arg,
+        parent: Option<(usize, usize)>,
+    },
+    Resume(usize),
+}
+
+/// A runnable unit of work of `trampoline_scheduled`: either a call that has
+/// not been started yet or a frame whose children have all completed.
+pub struct Task<Arg> {
+    kind: TaskKind<Arg>,
+    depth: usize,
+}
+
+impl<Arg> Task<Arg> {
+    /// The argument of a call that has not been started yet.
+    pub fn arg(&self) -> Option<&Arg> {
+        match &self.kind {
+            TaskKind::Start { arg, .. } => Some(arg),
+            TaskKind::Resume(_) => None,
+        }
+    }
+
+    /// The depth of the call. The depth of the initial call is 0.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+struct Slot<Gen, Res> {
+    gen: Gen,
+    parent: Option<(usize, usize)>,
+    results: Vec<Option<Res>>,
+    pending: usize,
+    depth: usize,
+}
+
+/// Like `trampoline_many`, but the order in which calls are made is decided
+/// by a scheduler obtained from `new_scheduler` on every call. Frames are
+/// still resumed with the results of their last yield in argument order,
+/// only their side effects can observe the schedule.
+pub fn trampoline_scheduled<Arg, Res, Gen, S>(
+    new_scheduler: impl Fn() -> S,
+    f: impl Fn(Arg) -> Gen,
+) -> impl Fn(Arg) -> Res
+where
+    Gen: Generator<Vec<Res>, Yield = ManyCall<Arg>, Return = Res> + Unpin,
+    S: Scheduler<Task<Arg>>,
+{
+    move |arg: Arg| {
+        let mut scheduler = new_scheduler();
+        let mut slots: Vec<Option<Slot<Gen, Res>>> = Vec::new();
+        let mut free = Vec::new();
+        scheduler.push(Task {
+            kind: TaskKind::Start { arg, parent: None },
+            depth: 0,
+        });
+
+        loop {
+            let task = scheduler.pop().expect("scheduler lost a task");
+            let (index, results) = match task.kind {
+                TaskKind::Start { arg, parent } => {
+                    let slot = Slot {
+                        gen: f(arg),
+                        parent,
+                        results: Vec::new(),
+                        pending: 0,
+                        depth: task.depth,
+                    };
+                    let index = match free.pop() {
+                        Some(index) => {
+                            slots[index] = Some(slot);
+                            index
+                        }
+                        None => {
+                            slots.push(Some(slot));
+                            slots.len() - 1
+                        }
+                    };
+                    (index, Vec::new())
+                }
+                TaskKind::Resume(index) => {
+                    let slot = slots[index].as_mut().unwrap();
+                    let results = std::mem::take(&mut slot.results);
+                    (index, results.into_iter().map(Option::unwrap).collect())
+                }
+            };
+
+            let slot = slots[index].as_mut().unwrap();
+            let mut results = results;
+            let state = loop {
+                match Pin::new(&mut slot.gen).resume(results) {
+                    GeneratorState::Yielded(ManyCall {
+                        kind: ManyKind::Tail(arg),
+                    }) => {
+                        slot.gen = f(arg);
+                        results = Vec::new();
+                    }
+                    state => break state,
+                }
+            };
+            match state {
+                GeneratorState::Yielded(call) => {
+                    let args = match call.kind {
+                        ManyKind::Normal(arg) => vec![arg],
+                        ManyKind::Many(args) => args,
+                        ManyKind::Tail(_) => unreachable!(),
+                    };
+                    slot.results = args.iter().map(|_| None).collect();
+                    slot.pending = args.len();
+                    let depth = slot.depth + 1;
+                    if args.is_empty() {
+                        scheduler.push(Task {
+                            kind: TaskKind::Resume(index),
+                            depth: depth - 1,
+                        });
+                    }
+                    for (pos, arg) in args.into_iter().enumerate() {
+                        scheduler.push(Task {
+                            kind: TaskKind::Start {
+                                arg,
+                                parent: Some((index, pos)),
+                            },
+                            depth,
+                        });
+                    }
+                }
+                GeneratorState::Complete(res) => {
+                    let parent = slot.parent;
+                    slots[index] = None;
+                    free.push(index);
+                    match parent {
+                        None => return res,
+                        Some((index, pos)) => {
+                            let slot = slots[index].as_mut().unwrap();
+                            slot.results[pos] = Some(res);
+                            slot.pending -= 1;
+                            if slot.pending == 0 {
+                                scheduler.push(Task {
+                                    kind: TaskKind::Resume(index),
+                                    depth: slot.depth,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
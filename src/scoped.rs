@@ -0,0 +1,26 @@
+use std::ops::Generator;
+
+use crate::trampoline;
+
+/// Like `trampoline`, but hands `scope` to every call of `f` instead of
+/// having `f` capture it. Frames can hence borrow from `scope` for the whole
+/// lifetime `'s`, arguments can contain references into it, and the result
+/// can be returned from a function that receives `scope` as a parameter:
+///
+/// ```ignore
+/// fn evaluator<'s>(env: &'s Env) -> impl Fn(&'s Expr) -> i64 + 's {
+///     recurse_scoped(env, |env, expr: &'s Expr| move |_: i64| ...)
+/// }
+/// ```
+pub fn recurse_scoped<'s, T, Arg, Res, Gen>(
+    scope: &'s T,
+    f: impl Fn(&'s T, Arg) -> Gen + 's,
+) -> impl Fn(Arg) -> Res + 's
+where
+    T: ?Sized,
+    Arg: 's,
+    Res: Default + 's,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin + 's,
+{
+    trampoline(move |arg: Arg| f(scope, arg))
+}
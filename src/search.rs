@@ -0,0 +1,194 @@
+//! Backtracking search with choice points and cut.
+//!
+//! Frames yield `Step`s. Besides calling the function on another argument,
+//! a frame can open a choice point with `Step::Choose(n)`, which resumes it
+//! with `Resume::Choice(0)`. When the search fails later on, the computation
+//! is rewound to the most recent choice point and resumed with the next
+//! alternative. `Step::Cut` discards all choice points opened since the
+//! current frame was entered, including those opened by its children.
+//!
+//! Rewinding requires copies of the suspended frames, so they must be
+//! `Clone`. Opening a choice point costs time linear in the current depth.
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+#[derive(Clone, Debug)]
+pub enum Step<Arg> {
+    /// Calls the function on `Arg`. The frame is resumed with one solution
+    /// of the call and, upon backtracking into the call, with the others.
+    Call(Arg),
+    /// Opens a choice point with `n` alternatives.
+    Choose(usize),
+    /// Fails and backtracks to the most recent choice point.
+    Fail,
+    /// Discards the choice points opened since the current frame was entered.
+    Cut,
+}
+
+#[derive(Clone, Debug)]
+pub enum Resume<Res> {
+    /// The resume value of a newly started frame and after `Step::Cut`.
+    Continue,
+    Solution(Res),
+    Choice(usize),
+}
+
+impl<Res> Resume<Res> {
+    /// # Panics
+    ///
+    /// Panics if `self` is not a `Resume::Solution`.
+    pub fn into_solution(self) -> Res {
+        match self {
+            Self::Solution(res) => res,
+            _ => panic!("expected a solution"),
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `self` is not a `Resume::Choice`.
+    pub fn into_choice(self) -> usize {
+        match self {
+            Self::Choice(index) => index,
+            _ => panic!("expected a choice"),
+        }
+    }
+}
+
+/// A frame together with the number of choice points that existed when it
+/// was entered.
+#[derive(Clone)]
+struct Frame<Gen> {
+    gen: Gen,
+    mark: usize,
+}
+
+struct ChoicePoint<Gen> {
+    stack: Vec<Frame<Gen>>,
+    current: Frame<Gen>,
+    next: usize,
+    len: usize,
+}
+
+/// An iterator over all solutions of a search, in the order in which they are
+/// found. See the module documentation.
+pub struct Solutions<F, Gen, Res> {
+    f: F,
+    stack: Vec<Frame<Gen>>,
+    current: Option<Frame<Gen>>,
+    resume: Resume<Res>,
+    choice_points: Vec<ChoicePoint<Gen>>,
+}
+
+pub fn solutions<Arg, Res, Gen, F>(f: F, arg: Arg) -> Solutions<F, Gen, Res>
+where
+    F: Fn(Arg) -> Gen,
+    Gen: Generator<Resume<Res>, Yield = Step<Arg>, Return = Res> + Unpin + Clone,
+{
+    let current = Frame {
+        gen: f(arg),
+        mark: 0,
+    };
+    Solutions {
+        f,
+        stack: Vec::new(),
+        current: Some(current),
+        resume: Resume::Continue,
+        choice_points: Vec::new(),
+    }
+}
+
+impl<Arg, Res, Gen, F> Solutions<F, Gen, Res>
+where
+    F: Fn(Arg) -> Gen,
+    Gen: Generator<Resume<Res>, Yield = Step<Arg>, Return = Res> + Unpin + Clone,
+{
+    /// Rewinds to the most recent choice point. Returns `false` if there is
+    /// none left.
+    fn backtrack(&mut self) -> bool {
+        match self.choice_points.last_mut() {
+            None => {
+                self.stack.clear();
+                self.current = None;
+                false
+            }
+            Some(point) => {
+                let index = point.next;
+                point.next += 1;
+                if point.next < point.len {
+                    self.stack = point.stack.clone();
+                    self.current = Some(point.current.clone());
+                } else {
+                    let point = self.choice_points.pop().unwrap();
+                    self.stack = point.stack;
+                    self.current = Some(point.current);
+                }
+                self.resume = Resume::Choice(index);
+                true
+            }
+        }
+    }
+}
+
+impl<Arg, Res, Gen, F> Iterator for Solutions<F, Gen, Res>
+where
+    F: Fn(Arg) -> Gen,
+    Gen: Generator<Resume<Res>, Yield = Step<Arg>, Return = Res> + Unpin + Clone,
+{
+    type Item = Res;
+
+    fn next(&mut self) -> Option<Res> {
+        let mut current = self.current.take()?;
+        loop {
+            let resume = std::mem::replace(&mut self.resume, Resume::Continue);
+            match Pin::new(&mut current.gen).resume(resume) {
+                GeneratorState::Yielded(Step::Call(arg)) => {
+                    let mark = self.choice_points.len();
+                    self.stack.push(current);
+                    current = Frame {
+                        gen: (self.f)(arg),
+                        mark,
+                    };
+                }
+                GeneratorState::Yielded(Step::Choose(len)) => {
+                    if len == 0 {
+                        if !self.backtrack() {
+                            return None;
+                        }
+                        current = self.current.take().unwrap();
+                    } else {
+                        if len > 1 {
+                            self.choice_points.push(ChoicePoint {
+                                stack: self.stack.clone(),
+                                current: current.clone(),
+                                next: 1,
+                                len,
+                            });
+                        }
+                        self.resume = Resume::Choice(0);
+                    }
+                }
+                GeneratorState::Yielded(Step::Fail) => {
+                    if !self.backtrack() {
+                        return None;
+                    }
+                    current = self.current.take().unwrap();
+                }
+                GeneratorState::Yielded(Step::Cut) => {
+                    self.choice_points.truncate(current.mark);
+                }
+                GeneratorState::Complete(res) => match self.stack.pop() {
+                    None => {
+                        // The next solution is searched for by backtracking.
+                        self.backtrack();
+                        return Some(res);
+                    }
+                    Some(top) => {
+                        current = top;
+                        self.resume = Resume::Solution(res);
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+use crate::EvalStatus;
+
+enum Next<Arg, Res> {
+    /// A frame has yielded `Arg`, which has not been called yet.
+    Call(Arg),
+    Resume(Res),
+    Done,
+}
+
+impl<Arg: Clone, Res: Clone> Clone for Next<Arg, Res> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Call(arg) => Self::Call(arg.clone()),
+            Self::Resume(res) => Self::Resume(res.clone()),
+            Self::Done => Self::Done,
+        }
+    }
+}
+
+/// A recursion that is driven one step at a time and can be cloned in the
+/// middle of it whenever its frames can. A search can hence take a snapshot
+/// before a decision, explore one alternative, roll back to the snapshot and
+/// try another one without re-running the path from the root. Decisions are
+/// made by answering a pending call with a result instead of making it.
+pub struct Computation<F, Gen, Arg, Res> {
+    f: F,
+    stack: Vec<Gen>,
+    current: Option<Gen>,
+    next: Next<Arg, Res>,
+}
+
+impl<F, Gen, Arg, Res> Computation<F, Gen, Arg, Res>
+where
+    F: Fn(Arg) -> Gen,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+    Res: Default,
+{
+    /// Creates a computation whose first step is the call of `f` on `arg`.
+    pub fn new(f: F, arg: Arg) -> Self {
+        Self {
+            f,
+            stack: Vec::new(),
+            current: None,
+            next: Next::Call(arg),
+        }
+    }
+
+    /// Makes the pending call, or resumes the current frame, and runs it
+    /// until it yields or returns. `depth` is the number of suspended frames.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the computation is already done.
+    pub fn step(&mut self) -> EvalStatus<Res> {
+        let (current, res) = match std::mem::replace(&mut self.next, Next::Done) {
+            Next::Call(arg) => {
+                self.stack.extend(self.current.take());
+                (self.current.insert((self.f)(arg)), Res::default())
+            }
+            Next::Resume(res) => (self.current.as_mut().unwrap(), res),
+            Next::Done => panic!("computation is already done"),
+        };
+        match Pin::new(current).resume(res) {
+            GeneratorState::Yielded(arg) => self.next = Next::Call(arg),
+            GeneratorState::Complete(res) => match self.stack.pop() {
+                None => {
+                    self.current = None;
+                    return EvalStatus::Done(res);
+                }
+                Some(top) => {
+                    self.current = Some(top);
+                    self.next = Next::Resume(res);
+                }
+            },
+        }
+        EvalStatus::Pending {
+            depth: self.stack.len(),
+        }
+    }
+
+    /// Steps until the computation is done or `stop_at` returns `true` for
+    /// the argument of a pending call.
+    pub fn run_until(&mut self, mut stop_at: impl FnMut(&Arg) -> bool) -> EvalStatus<Res> {
+        loop {
+            if let Some(arg) = self.pending_call() {
+                if stop_at(arg) {
+                    return EvalStatus::Pending {
+                        depth: self.stack.len(),
+                    };
+                }
+            }
+            if let EvalStatus::Done(res) = self.step() {
+                return EvalStatus::Done(res);
+            }
+        }
+    }
+
+    /// Returns the argument of the call the next step makes, if any.
+    pub fn pending_call(&self) -> Option<&Arg> {
+        match &self.next {
+            Next::Call(arg) => Some(arg),
+            _ => None,
+        }
+    }
+
+    /// Resumes the frame that made the pending call with `res` instead of
+    /// making the call, and returns the argument of the call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no pending call or it is the initial one.
+    pub fn answer(&mut self, res: Res) -> Arg {
+        match std::mem::replace(&mut self.next, Next::Resume(res)) {
+            Next::Call(arg) if self.current.is_some() => arg,
+            next => {
+                self.next = next;
+                panic!("there is no pending call to answer")
+            }
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        matches!(self.next, Next::Done)
+    }
+}
+
+impl<F, Gen, Arg, Res> Computation<F, Gen, Arg, Res>
+where
+    F: Clone,
+    Gen: Clone,
+    Arg: Clone,
+    Res: Clone,
+{
+    /// Clones the whole computation, including all suspended frames.
+    pub fn snapshot(&self) -> Self {
+        self.clone()
+    }
+
+    /// Returns to the state of `snapshot`, reusing the memory of the frame
+    /// stack.
+    pub fn rollback(&mut self, snapshot: &Self) {
+        self.clone_from(snapshot);
+    }
+}
+
+impl<F: Clone, Gen: Clone, Arg: Clone, Res: Clone> Clone for Computation<F, Gen, Arg, Res> {
+    fn clone(&self) -> Self {
+        Self {
+            f: self.f.clone(),
+            stack: self.stack.clone(),
+            current: self.current.clone(),
+            next: self.next.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.f = source.f.clone();
+        self.stack.clone_from(&source.stack);
+        self.current = source.current.clone();
+        self.next = source.next.clone();
+    }
+}
@@ -0,0 +1,169 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::FrameStack;
+
+/// Types whose values can be written to disk as raw bytes and read back.
+///
+/// # Safety
+///
+/// Implementors must not contain padding bytes and must be valid for any
+/// bit pattern they are written as. Pointers and references are only valid
+/// if their referents outlive the stack.
+pub unsafe trait Plain: Copy {}
+
+unsafe impl Plain for u8 {}
+unsafe impl Plain for u16 {}
+unsafe impl Plain for u32 {}
+unsafe impl Plain for u64 {}
+unsafe impl Plain for usize {}
+unsafe impl Plain for i8 {}
+unsafe impl Plain for i16 {}
+unsafe impl Plain for i32 {}
+unsafe impl Plain for i64 {}
+unsafe impl Plain for isize {}
+
+/// A frame stack that keeps at most a byte budget's worth of frames in
+/// memory and moves the oldest ones to a file beyond that. Meant for manual
+/// state machines implementing `Generator`, which can be `Plain`.
+///
+/// If writing to the file fails, the stack refuses to store the frame and
+/// the error can be obtained with `take_error`. Failing to read from the
+/// file panics.
+pub struct SpillStack<G> {
+    memory: Vec<G>,
+    capacity: usize,
+    file: File,
+    path: Option<PathBuf>,
+    spilled: usize,
+    error: Option<io::Error>,
+}
+
+impl<G: Plain> SpillStack<G> {
+    /// Creates a stack that spills into `file`, which must be readable and
+    /// writable. Its previous contents are overwritten.
+    pub fn new(file: File, budget: usize) -> Self {
+        let capacity = (budget / mem::size_of::<G>().max(1)).max(2);
+        Self {
+            memory: Vec::with_capacity(capacity),
+            capacity,
+            file,
+            path: None,
+            spilled: 0,
+            error: None,
+        }
+    }
+
+    /// Creates a stack that spills into a fresh file in the temporary
+    /// directory, which is deleted again when the stack is dropped.
+    pub fn with_temp_file(budget: usize) -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "stack-safe-{}-{}.spill",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let mut stack = Self::new(file, budget);
+        stack.path = Some(path);
+        Ok(stack)
+    }
+
+    /// The number of frames currently stored in the file.
+    pub fn spilled(&self) -> usize {
+        self.spilled
+    }
+
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    fn offset(&self, frames: usize) -> u64 {
+        (frames * mem::size_of::<G>()) as u64
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        let count = self.capacity / 2;
+        // SAFETY: `G: Plain` has no padding, so all its bytes are initialized.
+        let bytes = unsafe {
+            slice::from_raw_parts(
+                self.memory.as_ptr() as *const u8,
+                count * mem::size_of::<G>(),
+            )
+        };
+        self.file.seek(SeekFrom::Start(self.offset(self.spilled)))?;
+        self.file.write_all(bytes)?;
+        self.memory.drain(..count);
+        self.spilled += count;
+        Ok(())
+    }
+
+    fn unspill(&mut self) -> io::Result<()> {
+        let count = (self.capacity / 2).min(self.spilled);
+        let mut bytes = vec![0u8; count * mem::size_of::<G>()];
+        self.file
+            .seek(SeekFrom::Start(self.offset(self.spilled - count)))?;
+        self.file.read_exact(&mut bytes)?;
+        for index in 0..count {
+            // SAFETY: The bytes have been written from a valid `G: Plain`.
+            let frame = unsafe {
+                ptr::read_unaligned(bytes.as_ptr().add(index * mem::size_of::<G>()) as *const G)
+            };
+            self.memory.push(frame);
+        }
+        self.spilled -= count;
+        Ok(())
+    }
+
+    fn reload(&mut self) {
+        if self.memory.is_empty() && self.spilled > 0 {
+            if let Err(err) = self.unspill() {
+                panic!("failed to read spilled frames: {}", err);
+            }
+        }
+    }
+}
+
+impl<G: Plain> FrameStack<G> for SpillStack<G> {
+    fn push(&mut self, frame: G) -> Result<(), G> {
+        if self.memory.len() == self.capacity {
+            if let Err(err) = self.spill() {
+                self.error = Some(err);
+                return Err(frame);
+            }
+        }
+        self.memory.push(frame);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<G> {
+        self.reload();
+        self.memory.pop()
+    }
+
+    fn last_mut(&mut self) -> Option<&mut G> {
+        self.reload();
+        self.memory.last_mut()
+    }
+
+    fn len(&self) -> usize {
+        self.memory.len() + self.spilled
+    }
+}
+
+impl<G> Drop for SpillStack<G> {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,445 @@
+use std::alloc::Allocator;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+/// Storage for the suspended frames of a driver. `push` hands the frame back
+/// if the stack is out of capacity.
+pub trait FrameStack<G> {
+    fn push(&mut self, frame: G) -> Result<(), G>;
+
+    fn pop(&mut self) -> Option<G>;
+
+    fn last_mut(&mut self) -> Option<&mut G>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<G, A: Allocator> FrameStack<G> for Vec<G, A> {
+    fn push(&mut self, frame: G) -> Result<(), G> {
+        Vec::push(self, frame);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<G> {
+        Vec::pop(self)
+    }
+
+    fn last_mut(&mut self) -> Option<&mut G> {
+        <[G]>::last_mut(self)
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+}
+
+/// How a `GrowingStack` acquires more memory once it is full.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Growth {
+    /// Double the capacity, like `Vec` does.
+    Doubling,
+    /// Grow the capacity by a fixed number of frames.
+    Fixed(usize),
+    /// Allocate a new chunk of the given number of frames. Frames are never
+    /// moved, so pushing never pays for copying the whole stack.
+    Chunked(usize),
+}
+
+pub struct GrowingStack<G> {
+    growth: Growth,
+    chunks: Vec<Vec<G>>,
+    spare: Option<Vec<G>>,
+    len: usize,
+}
+
+impl<G> GrowingStack<G> {
+    pub fn new(growth: Growth) -> Self {
+        Self {
+            growth,
+            chunks: vec![Vec::new()],
+            spare: None,
+            len: 0,
+        }
+    }
+}
+
+impl<G> FrameStack<G> for GrowingStack<G> {
+    fn push(&mut self, frame: G) -> Result<(), G> {
+        let chunk = self.chunks.last_mut().unwrap();
+        if chunk.len() == chunk.capacity() {
+            match self.growth {
+                Growth::Doubling => {}
+                Growth::Fixed(increment) => chunk.reserve_exact(increment.max(1)),
+                Growth::Chunked(size) => {
+                    if !chunk.is_empty() {
+                        let size = size.max(1);
+                        let chunk = self
+                            .spare
+                            .take()
+                            .unwrap_or_else(|| Vec::with_capacity(size));
+                        self.chunks.push(chunk);
+                    } else if chunk.capacity() == 0 {
+                        chunk.reserve_exact(size.max(1));
+                    }
+                }
+            }
+        }
+        self.chunks.last_mut().unwrap().push(frame);
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<G> {
+        let frame = self.chunks.last_mut().unwrap().pop()?;
+        self.len -= 1;
+        if self.chunks.len() > 1 && self.chunks.last().unwrap().is_empty() {
+            self.spare = self.chunks.pop();
+        }
+        Some(frame)
+    }
+
+    fn last_mut(&mut self) -> Option<&mut G> {
+        self.chunks.last_mut().unwrap().last_mut()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// A stack with a fixed capacity that is allocated up front. Pushing onto
+/// it never allocates.
+pub struct BoundedStack<G> {
+    frames: Vec<G>,
+    max_depth: usize,
+}
+
+impl<G> BoundedStack<G> {
+    pub fn with_max_depth(max_depth: usize) -> Self {
+        Self {
+            frames: Vec::with_capacity(max_depth),
+            max_depth,
+        }
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+}
+
+impl<G> FrameStack<G> for BoundedStack<G> {
+    fn push(&mut self, frame: G) -> Result<(), G> {
+        if self.frames.len() < self.max_depth {
+            self.frames.push(frame);
+            Ok(())
+        } else {
+            Err(frame)
+        }
+    }
+
+    fn pop(&mut self) -> Option<G> {
+        self.frames.pop()
+    }
+
+    fn last_mut(&mut self) -> Option<&mut G> {
+        self.frames.last_mut()
+    }
+
+    fn len(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+/// A stack that refuses frames once they would take up more than `budget`
+/// bytes. A frame accounts for its own size plus the size reported by
+/// `heap_size`, e.g., for the contents of a boxed frame.
+pub struct BudgetedStack<G> {
+    frames: Vec<G>,
+    budget: usize,
+    used: usize,
+    heap_size: fn(&G) -> usize,
+}
+
+impl<G> BudgetedStack<G> {
+    pub fn with_budget(budget: usize) -> Self {
+        Self::with_heap_size(budget, |_| 0)
+    }
+
+    pub fn with_heap_size(budget: usize, heap_size: fn(&G) -> usize) -> Self {
+        Self {
+            frames: Vec::new(),
+            budget,
+            used: 0,
+            heap_size,
+        }
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// The approximate number of bytes taken up by the frames on the stack.
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    fn size(&self, frame: &G) -> usize {
+        mem::size_of::<G>() + (self.heap_size)(frame)
+    }
+}
+
+impl<G> FrameStack<G> for BudgetedStack<G> {
+    fn push(&mut self, frame: G) -> Result<(), G> {
+        let used = self.used + self.size(&frame);
+        if used <= self.budget {
+            self.frames.push(frame);
+            self.used = used;
+            Ok(())
+        } else {
+            Err(frame)
+        }
+    }
+
+    fn pop(&mut self) -> Option<G> {
+        let frame = self.frames.pop()?;
+        self.used -= self.size(&frame);
+        Some(frame)
+    }
+
+    fn last_mut(&mut self) -> Option<&mut G> {
+        self.frames.last_mut()
+    }
+
+    fn len(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DepthExceeded {
+    pub max_depth: usize,
+}
+
+impl fmt::Display for DepthExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "recursion depth exceeds {}", self.max_depth)
+    }
+}
+
+impl std::error::Error for DepthExceeded {}
+
+/// The frames of a computation exceeded their memory budget.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BudgetExceeded {
+    pub budget: usize,
+    /// The number of frames on the stack when the budget ran out.
+    pub depth: usize,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frames exceed the budget of {} bytes at depth {}",
+            self.budget, self.depth
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Runs the trampoline for `f` on `arg` with `stack` holding the suspended
+/// frames. This is the loop behind `trampoline`, for callers that want to
+/// own the stack, e.g., to reuse it across many calls or to interleave
+/// drives on different stacks. The stack is empty again when `drive`
+/// returns, but keeps its capacity.
+///
+/// The running frame stays on top of `stack` and is resumed in place, so
+/// frames with large captures are not moved in and out of the stack on
+/// every call.
+pub fn drive<Arg, Res, Gen>(f: impl Fn(Arg) -> Gen, arg: Arg, stack: &mut Vec<Gen>) -> Res
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    let base = stack.len();
+    stack.push(f(arg));
+    let mut res = Res::default();
+
+    loop {
+        let current = stack.last_mut().unwrap();
+        match Pin::new(current).resume(res) {
+            GeneratorState::Yielded(arg) => {
+                stack.push(f(arg));
+                res = Res::default();
+            }
+            GeneratorState::Complete(real_res) => {
+                stack.pop();
+                if stack.len() == base {
+                    return real_res;
+                }
+                res = real_res;
+            }
+        }
+    }
+}
+
+/// Like `drive`, but on any `FrameStack`. Frames already on the stack are
+/// left alone. If the stack refuses a frame or a frame panics, the frames
+/// pushed by this call are dropped and the stack is left as it was found.
+pub fn try_drive<Arg, Res, Gen, Stack>(
+    f: impl Fn(Arg) -> Gen,
+    arg: Arg,
+    stack: &mut Stack,
+) -> Result<Res, DepthExceeded>
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+    Stack: FrameStack<Gen>,
+{
+    let base = stack.len();
+    let stack = &mut Unwind {
+        stack,
+        base,
+        frame: PhantomData,
+    };
+    let mut current = f(arg);
+    let mut res = Res::default();
+    let mut depth = 0;
+
+    loop {
+        invariant!(
+            stack.stack.len() == base + depth,
+            "the frame stack holds {} frames but {} are suspended",
+            stack.stack.len() - base,
+            depth
+        );
+        match Pin::new(&mut current).resume(res) {
+            GeneratorState::Yielded(arg) => {
+                if stack.stack.push(current).is_err() {
+                    return Err(DepthExceeded {
+                        max_depth: stack.stack.len(),
+                    });
+                }
+                depth += 1;
+                current = f(arg);
+                res = Res::default();
+            }
+            GeneratorState::Complete(real_res) => {
+                if stack.stack.len() == base {
+                    invariant!(depth == 0, "the frame stack lost {} frames", depth);
+                    return Ok(real_res);
+                }
+                current = stack.stack.pop().unwrap();
+                depth -= 1;
+                res = real_res;
+            }
+        }
+    }
+}
+
+/// Pops the frames above `base` when dropped, so that neither an error nor
+/// a panic leaves frames of a finished call behind.
+struct Unwind<'a, G, Stack: FrameStack<G>> {
+    stack: &'a mut Stack,
+    base: usize,
+    frame: PhantomData<G>,
+}
+
+impl<G, Stack: FrameStack<G>> Drop for Unwind<'_, G, Stack> {
+    fn drop(&mut self) {
+        while self.stack.len() > self.base && self.stack.pop().is_some() {}
+    }
+}
+
+/// Like `trampoline`, but stores the suspended frames in a fresh stack
+/// obtained from `new_stack` on every call.
+///
+/// # Panics
+///
+/// Panics if the stack refuses to store another frame.
+pub fn trampoline_with<Arg, Res, Gen, Stack>(
+    new_stack: impl Fn() -> Stack,
+    f: impl Fn(Arg) -> Gen,
+) -> impl Fn(Arg) -> Res
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+    Stack: FrameStack<Gen>,
+{
+    move |arg: Arg| match try_drive(&f, arg, &mut new_stack()) {
+        Ok(res) => res,
+        Err(_) => panic!("frame stack is full"),
+    }
+}
+
+/// Like `trampoline`, but allocates the frame stack with `alloc`, e.g., in
+/// a dedicated arena or through an allocator that tracks its usage.
+pub fn trampoline_in<Arg, Res, Gen, A>(alloc: A, f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Res
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+    A: Allocator + Clone,
+{
+    trampoline_with(move || Vec::new_in(alloc.clone()), f)
+}
+
+/// Like `trampoline`, but with a frame stack for `max_depth` frames that is
+/// allocated once when the trampoline is created and reused by all calls.
+/// The driver itself does not allocate, so the calls only allocate if `f`
+/// or the frames do.
+pub fn trampoline_bounded<Arg, Res, Gen>(
+    max_depth: usize,
+    f: impl Fn(Arg) -> Gen,
+) -> impl FnMut(Arg) -> Result<Res, DepthExceeded>
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    let mut stack = BoundedStack::with_max_depth(max_depth);
+    move |arg: Arg| try_drive(&f, arg, &mut stack)
+}
+
+/// Like `trampoline`, but every call fails once its suspended frames take
+/// up more than `budget` bytes, e.g., to bound the memory used per request.
+/// The frames are accounted for with `size_of::<Gen>()` each.
+pub fn trampoline_budgeted<Arg, Res, Gen>(
+    budget: usize,
+    f: impl Fn(Arg) -> Gen,
+) -> impl Fn(Arg) -> Result<Res, BudgetExceeded>
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+{
+    move |arg: Arg| {
+        try_drive(&f, arg, &mut BudgetedStack::with_budget(budget)).map_err(|err| BudgetExceeded {
+            budget,
+            depth: err.max_depth,
+        })
+    }
+}
+
+/// Like `trampoline_bounded`, but runs on a caller-provided stack, e.g., an
+/// `ArrayStack` or a `SliceStack`. The error reports the depth at which the
+/// stack refused to store another frame.
+///
+/// With those stacks, the driver never allocates. The crate still depends on
+/// `std`, so this does not make it usable in `no_std` builds.
+pub fn trampoline_on<'s, Arg, Res, Gen, Stack>(
+    stack: &'s mut Stack,
+    f: impl Fn(Arg) -> Gen + 's,
+) -> impl FnMut(Arg) -> Result<Res, DepthExceeded> + 's
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Res> + Unpin,
+    Stack: FrameStack<Gen>,
+{
+    move |arg: Arg| try_drive(&f, arg, stack)
+}
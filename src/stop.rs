@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::StackSafeError;
+
+/// Decides whether a computation has to stop early. Drivers that support
+/// stopping call `check` before every step.
+pub trait Stop {
+    /// Returns the reason to stop, if any.
+    fn check(&mut self) -> Option<StackSafeError>;
+}
+
+/// Never stops.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Never;
+
+impl Stop for Never {
+    fn check(&mut self) -> Option<StackSafeError> {
+        None
+    }
+}
+
+/// Stops after the given number of steps.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Fuel(pub usize);
+
+impl Stop for Fuel {
+    fn check(&mut self) -> Option<StackSafeError> {
+        if self.0 == 0 {
+            Some(StackSafeError::FuelExhausted)
+        } else {
+            self.0 -= 1;
+            None
+        }
+    }
+}
+
+/// Stops once the given instant has passed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Deadline(pub Instant);
+
+impl Stop for Deadline {
+    fn check(&mut self) -> Option<StackSafeError> {
+        if Instant::now() >= self.0 {
+            Some(StackSafeError::DeadlineExceeded)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stops once `cancel` has been called on any of its clones, e.g., from
+/// another thread.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Stop for CancelToken {
+    fn check(&mut self) -> Option<StackSafeError> {
+        if self.is_cancelled() {
+            Some(StackSafeError::Cancelled)
+        } else {
+            None
+        }
+    }
+}
+
+/// What a computation had produced when it was stopped early, together with
+/// the reason for stopping.
+#[derive(Debug)]
+pub struct Partial<T> {
+    pub value: T,
+    pub reason: StackSafeError,
+}
@@ -0,0 +1,56 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+/// A `Stream` of the items of an iterator, e.g., one of the traversals in
+/// `iter`. Every poll advances the iterator at most once. After `budget`
+/// items in a row, the stream wakes its task and returns `Pending` once, so
+/// that a consumer that is always ready does not starve the other tasks of
+/// its executor during a long traversal.
+pub struct TraversalStream<I> {
+    iter: I,
+    budget: usize,
+    remaining: usize,
+}
+
+impl<I: Iterator> TraversalStream<I> {
+    pub fn new(iter: I) -> Self {
+        Self::with_budget(iter, 128)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `budget` is 0.
+    pub fn with_budget(iter: I, budget: usize) -> Self {
+        assert!(budget > 0, "the budget must be positive");
+        Self {
+            iter,
+            budget,
+            remaining: budget,
+        }
+    }
+
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I: Iterator + Unpin> Stream for TraversalStream<I> {
+    type Item = I::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<I::Item>> {
+        let this = self.get_mut();
+        if this.remaining == 0 {
+            this.remaining = this.budget;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        this.remaining -= 1;
+        Poll::Ready(this.iter.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
@@ -0,0 +1,156 @@
+//! Stack-safe traversals of `syn` expressions for proc-macro authors who
+//! have to deal with adversarial input like `a + a + ... + a`.
+//!
+//! Only sub-expressions are traversed, the traversals do not descend into
+//! blocks, statements or patterns.
+use syn::Expr;
+
+use crate::{recurse_owned, walk, Visitor};
+
+macro_rules! sub_exprs {
+    ($expr:expr, $($mut:tt)?) => {{
+        let mut subs = Vec::new();
+        match $expr {
+            Expr::Array(e) => subs.extend(& $($mut)? e.elems),
+            Expr::Assign(e) => {
+                subs.push(& $($mut)? *e.left);
+                subs.push(& $($mut)? *e.right);
+            }
+            Expr::AssignOp(e) => {
+                subs.push(& $($mut)? *e.left);
+                subs.push(& $($mut)? *e.right);
+            }
+            Expr::Await(e) => subs.push(& $($mut)? *e.base),
+            Expr::Binary(e) => {
+                subs.push(& $($mut)? *e.left);
+                subs.push(& $($mut)? *e.right);
+            }
+            Expr::Box(e) => subs.push(& $($mut)? *e.expr),
+            Expr::Break(e) => {
+                if let Some(sub) = & $($mut)? e.expr {
+                    subs.push(& $($mut)? **sub);
+                }
+            }
+            Expr::Call(e) => {
+                subs.push(& $($mut)? *e.func);
+                subs.extend(& $($mut)? e.args);
+            }
+            Expr::Cast(e) => subs.push(& $($mut)? *e.expr),
+            Expr::Closure(e) => subs.push(& $($mut)? *e.body),
+            Expr::Field(e) => subs.push(& $($mut)? *e.base),
+            Expr::Group(e) => subs.push(& $($mut)? *e.expr),
+            Expr::If(e) => {
+                subs.push(& $($mut)? *e.cond);
+                if let Some((_, else_branch)) = & $($mut)? e.else_branch {
+                    subs.push(& $($mut)? **else_branch);
+                }
+            }
+            Expr::Index(e) => {
+                subs.push(& $($mut)? *e.expr);
+                subs.push(& $($mut)? *e.index);
+            }
+            Expr::Let(e) => subs.push(& $($mut)? *e.expr),
+            Expr::Match(e) => {
+                subs.push(& $($mut)? *e.expr);
+                for arm in & $($mut)? e.arms {
+                    if let Some((_, guard)) = & $($mut)? arm.guard {
+                        subs.push(& $($mut)? **guard);
+                    }
+                    subs.push(& $($mut)? *arm.body);
+                }
+            }
+            Expr::MethodCall(e) => {
+                subs.push(& $($mut)? *e.receiver);
+                subs.extend(& $($mut)? e.args);
+            }
+            Expr::Paren(e) => subs.push(& $($mut)? *e.expr),
+            Expr::Range(e) => {
+                if let Some(sub) = & $($mut)? e.from {
+                    subs.push(& $($mut)? **sub);
+                }
+                if let Some(sub) = & $($mut)? e.to {
+                    subs.push(& $($mut)? **sub);
+                }
+            }
+            Expr::Reference(e) => subs.push(& $($mut)? *e.expr),
+            Expr::Repeat(e) => {
+                subs.push(& $($mut)? *e.expr);
+                subs.push(& $($mut)? *e.len);
+            }
+            Expr::Return(e) => {
+                if let Some(sub) = & $($mut)? e.expr {
+                    subs.push(& $($mut)? **sub);
+                }
+            }
+            Expr::Struct(e) => {
+                for field in & $($mut)? e.fields {
+                    subs.push(& $($mut)? field.expr);
+                }
+                if let Some(sub) = & $($mut)? e.rest {
+                    subs.push(& $($mut)? **sub);
+                }
+            }
+            Expr::Try(e) => subs.push(& $($mut)? *e.expr),
+            Expr::Tuple(e) => subs.extend(& $($mut)? e.elems),
+            Expr::Type(e) => subs.push(& $($mut)? *e.expr),
+            Expr::Unary(e) => subs.push(& $($mut)? *e.expr),
+            Expr::While(e) => subs.push(& $($mut)? *e.cond),
+            Expr::Yield(e) => {
+                if let Some(sub) = & $($mut)? e.expr {
+                    subs.push(& $($mut)? **sub);
+                }
+            }
+            _ => {}
+        }
+        subs
+    }};
+}
+
+/// The direct sub-expressions of `expr`.
+pub fn sub_exprs(expr: &Expr) -> Vec<&Expr> {
+    sub_exprs!(expr,)
+}
+
+/// Like `sub_exprs`, in the same order, but mutable.
+pub fn sub_exprs_mut(expr: &mut Expr) -> Vec<&mut Expr> {
+    sub_exprs!(expr, mut)
+}
+
+pub fn visit_expr<'a, V>(root: &'a Expr, visitor: &mut V)
+where
+    V: Visitor<'a, Expr> + ?Sized,
+{
+    walk(root, sub_exprs, visitor)
+}
+
+fn take_sub_exprs(expr: &mut Expr) -> Vec<Expr> {
+    sub_exprs_mut(expr)
+        .into_iter()
+        .map(|sub| std::mem::replace(sub, Expr::Verbatim(Default::default())))
+        .collect()
+}
+
+/// Rebuilds the expression bottom-up, applying `f` to every node after its
+/// sub-expressions have been folded.
+pub fn fold_expr(root: Expr, f: impl Fn(Expr) -> Expr) -> Expr {
+    recurse_owned(
+        |mut expr: Expr| {
+            let subs = take_sub_exprs(&mut expr);
+            (expr, subs)
+        },
+        |mut expr: Expr, folded: Vec<Expr>| {
+            for (sub, folded) in sub_exprs_mut(&mut expr).into_iter().zip(folded) {
+                *sub = folded;
+            }
+            f(expr)
+        },
+    )(root)
+}
+
+/// Drops an expression without recursing on its nesting depth.
+pub fn drop_expr(expr: Expr) {
+    let mut stack = vec![expr];
+    while let Some(mut expr) = stack.pop() {
+        stack.append(&mut take_sub_exprs(&mut expr));
+    }
+}
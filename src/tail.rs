@@ -0,0 +1,64 @@
+use std::mem;
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+/// A call in tail position. Frames of `trampoline_tail` can only return it,
+/// so unlike with `Call::tail`, no work can follow the call and be silently
+/// dropped.
+#[must_use = "a `TailCall` only has an effect when it is returned from a frame"]
+pub struct TailCall<Arg> {
+    arg: Arg,
+}
+
+impl<Arg> TailCall<Arg> {
+    pub fn new(arg: Arg) -> Self {
+        Self { arg }
+    }
+}
+
+/// What a frame of `trampoline_tail` ends with.
+pub enum Tail<Arg, Res> {
+    Return(Res),
+    Call(TailCall<Arg>),
+}
+
+impl<Arg, Res> From<TailCall<Arg>> for Tail<Arg, Res> {
+    fn from(call: TailCall<Arg>) -> Self {
+        Self::Call(call)
+    }
+}
+
+/// Like `trampoline_tco`, but frames make tail calls by returning a
+/// `TailCall`, e.g., with `tail_call!`, which replaces their frame with the
+/// callee's. Normal calls are made by yielding the argument.
+pub fn trampoline_tail<Arg, Res, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg) -> Res
+where
+    Res: Default,
+    Gen: Generator<Res, Yield = Arg, Return = Tail<Arg, Res>> + Unpin,
+{
+    move |arg: Arg| {
+        let mut stack = Vec::new();
+        let mut current = f(arg);
+        let mut res = Res::default();
+
+        loop {
+            match Pin::new(&mut current).resume(res) {
+                GeneratorState::Yielded(arg) => {
+                    stack.push(mem::replace(&mut current, f(arg)));
+                    res = Res::default();
+                }
+                GeneratorState::Complete(Tail::Call(call)) => {
+                    current = f(call.arg);
+                    res = Res::default();
+                }
+                GeneratorState::Complete(Tail::Return(real_res)) => match stack.pop() {
+                    None => return real_res,
+                    Some(top) => {
+                        current = top;
+                        res = real_res;
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Random arithmetic expressions.
+use rand::random;
+use std::fmt::Display;
+
+pub type Num = f64;
+
+#[derive(Debug)]
+pub enum Expr {
+    Num(Num),
+    Add(Box<Self>, Box<Self>),
+    Mul(Box<Self>, Box<Self>),
+}
+
+impl Drop for Expr {
+    fn drop(&mut self) {
+        if let Self::Add(lhs, rhs) | Self::Mul(lhs, rhs) = self {
+            let mut stack = vec![std::mem::take(lhs.as_mut()), std::mem::take(rhs.as_mut())];
+            while let Some(mut expr) = stack.pop() {
+                if let Self::Add(lhs, rhs) | Self::Mul(lhs, rhs) = &mut expr {
+                    stack.push(std::mem::take(lhs.as_mut()));
+                    stack.push(std::mem::take(rhs.as_mut()));
+                }
+            }
+        }
+    }
+}
+
+impl Default for Expr {
+    fn default() -> Self {
+        Self::Num(0 as Num)
+    }
+}
+
+/// An expression together with its value and its number of nodes.
+pub struct Case {
+    pub expr: Expr,
+    pub eval: Num,
+    pub size: usize,
+}
+
+/// The operators used in the inner nodes of generated expressions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Ops {
+    Add,
+    Mul,
+    Rnd,
+}
+
+impl Display for Ops {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Add => write!(f, "add"),
+            Self::Mul => write!(f, "mul"),
+            Self::Rnd => write!(f, "rnd"),
+        }
+    }
+}
+
+pub fn simple() -> (Expr, Num) {
+    let expr = Expr::Add(
+        Box::new(Expr::Num(1 as Num)),
+        Box::new(Expr::Mul(
+            Box::new(Expr::Num(2 as Num)),
+            Box::new(Expr::Num(3 as Num)),
+        )),
+    );
+    (expr, 7 as Num)
+}
+
+fn random_num() -> Case {
+    let num = random();
+    Case {
+        expr: Expr::Num(num),
+        eval: num,
+        size: 1,
+    }
+}
+
+fn random_bin(ops: Ops, lhs: Case, rhs: Case) -> Case {
+    let lhs_expr = Box::new(lhs.expr);
+    let rhs_expr = Box::new(rhs.expr);
+    let add = match ops {
+        Ops::Add => true,
+        Ops::Mul => false,
+        Ops::Rnd => random(),
+    };
+    let (expr, eval) = if add {
+        (Expr::Add(lhs_expr, rhs_expr), lhs.eval + rhs.eval)
+    } else {
+        (Expr::Mul(lhs_expr, rhs_expr), lhs.eval * rhs.eval)
+    };
+    let size = 1 + lhs.size + rhs.size;
+    Case { expr, eval, size }
+}
+
+/// A complete binary tree of height `n` whose leaves are produced by `leaf`.
+pub fn tree_with(ops: Ops, n: usize, leaf: &dyn Fn() -> Case) -> Case {
+    if n == 0 {
+        leaf()
+    } else {
+        random_bin(
+            ops,
+            tree_with(ops, n - 1, leaf),
+            tree_with(ops, n - 1, leaf),
+        )
+    }
+}
+
+/// A single branch of `n` inner nodes, each with one leaf produced by `leaf`.
+pub fn branch_with(ops: Ops, n: usize, leaf: &dyn Fn() -> Case) -> Case {
+    let mut expr = leaf();
+    for _ in 0..n {
+        expr = if random::<bool>() {
+            random_bin(ops, expr, leaf())
+        } else {
+            random_bin(ops, leaf(), expr)
+        }
+    }
+    expr
+}
+
+pub fn one_branch(ops: Ops) -> Case {
+    branch_with(ops, 512 * 1024 - 1, &random_num)
+}
+
+pub fn many_trees(ops: Ops) -> Case {
+    branch_with(ops, 1023, &|| tree_with(ops, 9, &random_num))
+}
+
+pub fn one_tree(ops: Ops) -> Case {
+    tree_with(ops, 19, &random_num)
+}
+
+pub fn many_branches(ops: Ops) -> Case {
+    tree_with(ops, 10, &|| branch_with(ops, 511, &random_num))
+}
@@ -0,0 +1,167 @@
+//! `Arbitrary` implementations for the deep inputs, for fuzzing drivers and
+//! stack-safe rewrites with adversarially deep inputs.
+//!
+//! None of the generators recurse, so they can produce inputs of any depth.
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use super::calc::{Expr, Num};
+use super::graph::{Graph, Node};
+use super::tree::Tree;
+use crate::list::List;
+
+/// Controls the shape of the generated inputs.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Shape {
+    /// The maximum number of nodes.
+    pub max_nodes: usize,
+    /// How likely every new node deepens the input rather than widening
+    /// it, from `0` for shallow and wide to `255` for as deep as possible.
+    pub depth_bias: u8,
+}
+
+impl Shape {
+    pub const DEFAULT_MAX_NODES: usize = 100_000;
+
+    /// A shape with the default size and a depth bias chosen by `u`.
+    pub fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            max_nodes: Self::DEFAULT_MAX_NODES,
+            depth_bias: u.arbitrary()?,
+        })
+    }
+
+    fn deepen(&self, u: &mut Unstructured<'_>) -> Result<bool> {
+        Ok(u.arbitrary::<u8>()? < self.depth_bias || self.depth_bias == u8::MAX)
+    }
+
+    fn len(&self, u: &mut Unstructured<'_>) -> Result<usize> {
+        u.int_in_range(1..=self.max_nodes.max(1))
+    }
+}
+
+/// A tree in which every node is attached either to the previous node or to
+/// a random earlier one.
+pub fn tree(u: &mut Unstructured<'_>, shape: &Shape) -> Result<Tree> {
+    let len = shape.len(u)?;
+    let mut nodes = vec![Tree::new(u.arbitrary()?)];
+    let mut parents = vec![0];
+    for index in 1..len {
+        let parent = if shape.deepen(u)? {
+            index - 1
+        } else {
+            u.int_in_range(0..=index - 1)?
+        };
+        nodes.push(Tree::new(u.arbitrary()?));
+        parents.push(parent);
+    }
+    // Children come after their parents, so attaching the nodes from last
+    // to first moves every node only after all of its children.
+    for index in (1..len).rev() {
+        let node = nodes.pop().unwrap();
+        nodes[parents[index]].children.push(node);
+    }
+    let mut root = nodes.pop().unwrap();
+    let mut stack = vec![&mut root];
+    while let Some(node) = stack.pop() {
+        node.children.reverse();
+        stack.extend(node.children.iter_mut());
+    }
+    Ok(root)
+}
+
+fn binary(u: &mut Unstructured<'_>, lhs: Expr, rhs: Expr) -> Result<Expr> {
+    let (lhs, rhs) = (Box::new(lhs), Box::new(rhs));
+    Ok(if u.arbitrary()? {
+        Expr::Add(lhs, rhs)
+    } else {
+        Expr::Mul(lhs, rhs)
+    })
+}
+
+fn num(u: &mut Unstructured<'_>) -> Result<Expr> {
+    Ok(Expr::Num(Num::from(u.arbitrary::<i8>()?)))
+}
+
+/// An expression that is deepened by combining it with a new leaf or
+/// widened by starting a new subexpression. The subexpressions are combined
+/// into balanced trees in the end.
+pub fn expr(u: &mut Unstructured<'_>, shape: &Shape) -> Result<Expr> {
+    let mut exprs = vec![num(u)?];
+    let leaves = shape.len(u)? / 2 + 1;
+    for _ in 1..leaves {
+        let leaf = num(u)?;
+        if shape.deepen(u)? {
+            let expr = exprs.pop().unwrap();
+            let expr = if u.arbitrary()? {
+                binary(u, expr, leaf)?
+            } else {
+                binary(u, leaf, expr)?
+            };
+            exprs.push(expr);
+        } else {
+            exprs.push(leaf);
+        }
+    }
+    while exprs.len() > 1 {
+        let mut pairs = Vec::with_capacity(exprs.len() / 2 + 1);
+        let mut iter = exprs.into_iter();
+        while let Some(lhs) = iter.next() {
+            match iter.next() {
+                Some(rhs) => pairs.push(binary(u, lhs, rhs)?),
+                None => pairs.push(lhs),
+            }
+        }
+        exprs = pairs;
+    }
+    Ok(exprs.pop().unwrap())
+}
+
+/// A graph in which every node has an edge to the next node with a
+/// probability given by the depth bias, plus up to three random edges.
+pub fn graph(u: &mut Unstructured<'_>, shape: &Shape) -> Result<Graph> {
+    let len = shape.len(u)?;
+    let mut graph = Vec::with_capacity(len);
+    for index in 0..len {
+        let mut edges = Vec::new();
+        if index + 1 < len && shape.deepen(u)? {
+            edges.push(Node::new(index + 1));
+        }
+        for _ in 0..u.int_in_range(0..=3)? {
+            edges.push(Node::new(u.int_in_range(0..=len - 1)?));
+        }
+        graph.push(edges);
+    }
+    Ok(graph)
+}
+
+impl<'a> Arbitrary<'a> for Tree {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let shape = Shape::arbitrary(u)?;
+        tree(u, &shape)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Expr {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let shape = Shape::arbitrary(u)?;
+        expr(u, &shape)
+    }
+}
+
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for List<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary_iter()?.collect()
+    }
+}
+
+/// A graph as generated by `graph`. `Graph` itself is a `Vec` and can hence
+/// not implement `Arbitrary` in this crate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArbitraryGraph(pub Graph);
+
+impl<'a> Arbitrary<'a> for ArbitraryGraph {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let shape = Shape::arbitrary(u)?;
+        graph(u, &shape).map(Self)
+    }
+}
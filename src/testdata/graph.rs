@@ -0,0 +1,66 @@
+//! Directed graphs in adjacency list form.
+//!
+//! Every generator comes with a companion returning the strongly connected
+//! components in the order Tarjan's algorithm discovers them when started
+//! from the nodes in increasing order.
+#![allow(non_upper_case_globals)]
+
+pub use crate::graph::{Graph, Node, SCCs};
+
+const v0: Node = Node::new(0);
+const v1: Node = Node::new(1);
+const v2: Node = Node::new(2);
+const v3: Node = Node::new(3);
+const v4: Node = Node::new(4);
+
+pub fn simple() -> Graph {
+    vec![vec![v1], vec![v2, v3], vec![v1, v4], vec![v2], vec![]]
+}
+
+pub fn simple_sccs() -> SCCs {
+    vec![vec![v4], vec![v3, v2, v1], vec![v0]]
+}
+
+/// The path `0 -> 1 -> ... -> n - 1`.
+pub fn path(n: usize) -> Graph {
+    (0..n)
+        .map(|id| {
+            if id + 1 < n {
+                vec![Node::new(id + 1)]
+            } else {
+                vec![]
+            }
+        })
+        .collect()
+}
+
+pub fn path_sccs(n: usize) -> SCCs {
+    (0..n).rev().map(|id| vec![Node::new(id)]).collect()
+}
+
+/// The path `n - 1 -> ... -> 1 -> 0`.
+pub fn path_rev(n: usize) -> Graph {
+    (0..n)
+        .map(|id| {
+            if id > 0 {
+                vec![Node::new(id - 1)]
+            } else {
+                vec![]
+            }
+        })
+        .collect()
+}
+
+pub fn path_rev_sccs(n: usize) -> SCCs {
+    (0..n).map(|id| vec![Node::new(id)]).collect()
+}
+
+/// The complete graph on `n` nodes, including self-loops.
+pub fn complete(n: usize) -> Graph {
+    let outgoing: Vec<_> = (0..n).map(Node::new).collect();
+    (0..n).map(|_| outgoing.clone()).collect()
+}
+
+pub fn complete_sccs(n: usize) -> SCCs {
+    vec![(0..n).rev().map(Node::new).collect()]
+}
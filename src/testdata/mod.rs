@@ -0,0 +1,7 @@
+//! The deep inputs used by the benchmarks, for stress testing downstream
+//! implementations. Dropping them never overflows the stack.
+pub mod calc;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+pub mod graph;
+pub mod tree;
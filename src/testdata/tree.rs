@@ -0,0 +1,67 @@
+//! Rose trees of various shapes.
+//!
+//! Every generator returns the tree together with its depth and the sum of
+//! its values.
+
+#[derive(Clone, Debug)]
+pub struct Tree {
+    pub value: i64,
+    pub children: Vec<Tree>,
+}
+
+impl Tree {
+    pub fn new(value: i64) -> Self {
+        Self {
+            value,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl Drop for Tree {
+    fn drop(&mut self) {
+        if !self.children.is_empty() {
+            let mut stack = std::mem::take(&mut self.children);
+            while let Some(mut node) = stack.pop() {
+                stack.append(&mut node.children);
+            }
+        }
+    }
+}
+
+pub fn simple() -> (Tree, usize, i64) {
+    let mut v0 = Tree::new(0);
+    let v1 = Tree::new(1);
+    let mut v2 = Tree::new(2);
+    let v3 = Tree::new(3);
+    v2.children = vec![v3];
+    v0.children = vec![v1, v2];
+    (v0, 3, 6)
+}
+
+/// A path of `n` nodes with values `0, ..., n - 1` from the root downwards.
+///
+/// # Panics
+///
+/// Panics if `n` is zero since a tree has at least one node.
+pub fn path(n: usize) -> (Tree, usize, i64) {
+    assert!(n > 0, "a path needs at least one node");
+    let mut tree = Tree::new(n as i64 - 1);
+    for k in (0..(n - 1)).rev() {
+        let mut parent = Tree::new(k as i64);
+        parent.children.push(tree);
+        tree = parent;
+    }
+    (tree, n, (n * (n - 1) / 2) as i64)
+}
+
+/// A complete binary tree of depth `n` with all values set to `1`.
+pub fn binary(n: usize) -> (Tree, usize, i64) {
+    if n <= 1 {
+        (Tree::new(1), 1, 1)
+    } else {
+        let mut tree = Tree::new(1);
+        tree.children = vec![binary(n - 1).0, binary(n - 1).0];
+        (tree, n, 2i64.pow(n as u32) - 1)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::dom::{depth, descendants, transform};
+use crate::{flatten, with_stack_size, Arena};
+
+#[test]
+fn traversals_over_arenas() {
+    let mut arena = Arena::new();
+    let b = arena.alloc(2, vec![]);
+    let c = arena.alloc(3, vec![]);
+    let a = arena.alloc(1, vec![b, c]);
+    *arena.get_mut(c) = 4;
+    let root = arena.node(a);
+    let values: Vec<_> = descendants(root).map(|node| *node.value()).collect();
+    assert_eq!(values, [1, 2, 4]);
+    assert_eq!(depth(root), 2);
+    assert_eq!(
+        transform(root, |node, sums: Vec<i32>| node.value()
+            + sums.iter().sum::<i32>()),
+        7
+    );
+}
+
+#[test]
+fn deep_arenas() {
+    const LARGE: u64 = 10_000;
+    let mut arena = Arena::new();
+    let mut id = arena.alloc(0, vec![]);
+    for n in 1..LARGE {
+        id = arena.alloc(n, vec![id]);
+    }
+    let result = with_stack_size(1024, move || {
+        let sum = transform(arena.node(id), |node, sums: Vec<u64>| {
+            node.value() + sums.iter().sum::<u64>()
+        });
+        (depth(arena.node(id)), sum)
+    });
+    assert_eq!(result.unwrap(), (LARGE as usize, LARGE * (LARGE - 1) / 2));
+
+    let arena = Arena::from(flatten(3u64, |n| (n, (0..n).collect::<Vec<_>>())));
+    let root = arena.node(arena.last().unwrap());
+    assert_eq!(descendants(root).count(), 8);
+}
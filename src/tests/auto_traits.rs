@@ -0,0 +1,124 @@
+use std::marker::PhantomData;
+use std::ops::Generator;
+
+use crate::{
+    recurse_dag, recurse_fn, recurse_owned, recurse_reduce, recurse_scoped, trampoline,
+    trampoline_bounded, trampoline_tco, trampoline_with, trampoline_with_path, try_recurse, Call,
+    Path,
+};
+
+fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+fn assert_clone<T: Clone>(_: &T) {}
+
+/// Tells whether the type of a value is `Clone` without requiring it to be.
+/// Method resolution prefers `ViaClone`, which needs one reference less.
+struct Probe<T>(PhantomData<T>);
+
+fn probe<T>(_: &T) -> Probe<T> {
+    Probe(PhantomData)
+}
+
+trait ViaClone {
+    fn is_clone(&self) -> bool {
+        true
+    }
+}
+
+impl<T: Clone> ViaClone for &Probe<T> {}
+
+trait ViaAny {
+    fn is_clone(&self) -> bool {
+        false
+    }
+}
+
+impl<T> ViaAny for Probe<T> {}
+
+macro_rules! is_clone {
+    ($value:expr) => {
+        (&&probe(&$value)).is_clone()
+    };
+}
+
+fn triangular(n: u64) -> impl Generator<u64, Yield = u64, Return = u64> + Unpin {
+    move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) }
+}
+
+fn triangular_tco(n: u64) -> impl Generator<u64, Yield = Call<u64>, Return = u64> + Unpin {
+    move |_: u64| {
+        if n == 0 {
+            0
+        } else {
+            let res = yield Call::normal(n - 1);
+            res + n
+        }
+    }
+}
+
+#[test]
+fn combinators_are_send_and_sync() {
+    assert_send_sync(&trampoline(triangular));
+    assert_send_sync(&trampoline_with(Vec::new, triangular));
+    assert_send_sync(&trampoline_bounded(10, triangular));
+    assert_send_sync(&trampoline_tco(triangular_tco));
+    assert_send_sync(&recurse_dag(triangular));
+    assert_send_sync(&recurse_fn::<u64, u64, _>(triangular));
+    assert_send_sync(&trampoline_with_path(|_: Path<u64>, n| triangular(n)));
+    assert_send_sync(&try_recurse(|n: u64| {
+        move |_: u64| {
+            if n == 0 {
+                Err(())
+            } else {
+                Ok(yield (n - 1))
+            }
+        }
+    }));
+    assert_send_sync(&recurse_reduce(
+        |n: u64| {
+            move |_: ()| {
+                if n > 0 {
+                    yield n - 1;
+                }
+                n
+            }
+        },
+        u64::max,
+    ));
+    assert_send_sync(&recurse_owned(
+        |n: u64| (n, (0..n).collect::<Vec<_>>()),
+        |n, results: Vec<u64>| n + results.iter().sum::<u64>(),
+    ));
+    let input = vec![1, 2, 3];
+    assert_send_sync(&recurse_scoped(input.as_slice(), |input, i: usize| {
+        move |_: u64| {
+            if i == input.len() {
+                0
+            } else {
+                input[i] + (yield i + 1)
+            }
+        }
+    }));
+}
+
+#[test]
+fn combinators_are_clone_when_f_is() {
+    let sum = trampoline(triangular);
+    assert_clone(&sum);
+    assert_eq!(sum.clone()(10), sum(10));
+    let sum_tco = trampoline_tco(triangular_tco);
+    assert_clone(&sum_tco);
+    assert_eq!(sum_tco.clone()(10), 55);
+}
+
+#[test]
+fn other_combinators_are_not_clone() {
+    assert!(is_clone!(trampoline(triangular)));
+    assert!(!is_clone!(trampoline_with(Vec::new, triangular)));
+    assert!(!is_clone!(trampoline_bounded(10, triangular)));
+    assert!(!is_clone!(recurse_dag(triangular)));
+    assert!(!is_clone!(recurse_owned(
+        |n: u64| (n, (0..n).collect::<Vec<_>>()),
+        |n, results: Vec<u64>| n + results.iter().sum::<u64>(),
+    )));
+}
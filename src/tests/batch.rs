@@ -0,0 +1,36 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use crate::{recurse_batch, recurse_batch_with_cache, with_stack_size};
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn roots_share_the_cache() {
+    let calls = Cell::new(0);
+    let fib = |n: u64| {
+        calls.set(calls.get() + 1);
+        move |_: u64| {
+            if n < 2 {
+                n
+            } else {
+                (yield n - 1) + (yield n - 2)
+            }
+        }
+    };
+    let mut cache = HashMap::new();
+    let results = recurse_batch_with_cache(fib, vec![20, 10, 30], &mut cache);
+    assert_eq!(results, vec![6765, 55, 832040]);
+    // Every argument up to 30 is evaluated once.
+    assert_eq!((calls.get(), cache.len()), (31, 31));
+}
+
+#[test]
+fn deep_roots() {
+    let result = with_stack_size(1024, || {
+        let triangular = |n: u64| move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) };
+        recurse_batch(triangular, [LARGE, 0, LARGE / 2])
+    });
+    let expected = vec![LARGE * (LARGE + 1) / 2, 0, LARGE * (LARGE + 2) / 8];
+    assert_eq!(result.unwrap(), expected);
+}
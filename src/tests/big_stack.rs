@@ -0,0 +1,69 @@
+use crate::{trampoline, BigStackThread};
+
+fn triangular(n: u64) -> u64 {
+    if n == 0 {
+        0
+    } else {
+        n + triangular(n - 1)
+    }
+}
+
+#[test]
+fn runs_named_thread_with_big_stack() {
+    let n = 10_000;
+    let result = BigStackThread::new()
+        .stack_size(16 * 1024 * 1024)
+        .name("big")
+        .run(|| {
+            assert_eq!(std::thread::current().name(), Some("big"));
+            triangular(n)
+        });
+    assert_eq!(result.unwrap(), n * (n + 1) / 2);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn pins_thread_to_core() {
+    let sum = trampoline(|n: u64| move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) });
+    let result = BigStackThread::new()
+        .stack_size(1024)
+        .pin_to_core(0)
+        .run(|| sum(100));
+    assert_eq!(result.unwrap(), 5050);
+    assert!(BigStackThread::new()
+        .pin_to_core(usize::MAX / 2)
+        .run(|| ())
+        .is_err());
+}
+
+#[crate::main(stack_size = "16MiB")]
+fn deep_main() -> u64 {
+    triangular(10_000)
+}
+
+#[crate::main(stack_size = 1048576)]
+fn failing_main() {
+    panic!("propagated");
+}
+
+#[test]
+fn main_attribute_runs_on_big_stack() {
+    assert_eq!(deep_main(), 10_000 * 10_001 / 2);
+}
+
+#[test]
+#[should_panic(expected = "propagated")]
+fn main_attribute_propagates_panics() {
+    failing_main();
+}
+
+#[crate::test(stack_size = "16MiB")]
+fn test_attribute_runs_on_big_stack() {
+    assert_eq!(triangular(10_000), 10_000 * 10_001 / 2);
+}
+
+#[crate::test(stack_size = "1KiB")]
+#[should_panic(expected = "inside")]
+fn test_attribute_supports_should_panic() {
+    panic!("inside");
+}
@@ -0,0 +1,54 @@
+use std::cell::Cell;
+
+use crate::{bisimilar, with_stack_size};
+
+struct Node<'a> {
+    label: u32,
+    next: Cell<Option<&'a Node<'a>>>,
+}
+
+fn next<'a>(node: &'a Node<'a>) -> Option<&'a Node<'a>> {
+    node.next.get()
+}
+
+fn same_label(a: &Node, b: &Node) -> bool {
+    a.label == b.label
+}
+
+// Ties the nodes into a single cycle.
+fn cycle<'a>(nodes: &'a [Node<'a>]) -> &'a Node<'a> {
+    for (index, node) in nodes.iter().enumerate() {
+        node.next.set(Some(&nodes[(index + 1) % nodes.len()]));
+    }
+    &nodes[0]
+}
+
+fn nodes<'a>(labels: impl IntoIterator<Item = u32>) -> Vec<Node<'a>> {
+    labels
+        .into_iter()
+        .map(|label| Node {
+            label,
+            next: Cell::new(None),
+        })
+        .collect()
+}
+
+const LARGE: u32 = 10_000;
+
+#[test]
+fn cycles_are_compared_by_unfolding() {
+    let (one, two, mixed) = (nodes([1]), nodes([1, 1]), nodes([1, 2]));
+    let (one, two, mixed) = (cycle(&one), cycle(&two), cycle(&mixed));
+    assert!(bisimilar(one, two, next, same_label));
+    assert!(!bisimilar(one, mixed, next, same_label));
+    assert!(!bisimilar(two, mixed, next, same_label));
+}
+
+#[test]
+fn long_cycles_do_not_overflow() {
+    let result = with_stack_size(1024, || {
+        let (long, short) = (nodes((0..LARGE).map(|_| 1)), nodes([1]));
+        bisimilar(cycle(&long), cycle(&short), next, same_label)
+    });
+    assert!(result.unwrap());
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+use std::thread;
+
+use crate::{trampoline_tco, with_stack_size, Call, RecursiveFn, StackSafeFn};
+
+struct Interpreter {
+    triangular: StackSafeFn<'static, u64, u64>,
+    count_down: StackSafeFn<'static, u64, u64>,
+}
+
+impl Interpreter {
+    fn new() -> Self {
+        Self {
+            triangular: StackSafeFn::new(|n: u64| {
+                move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) }
+            }),
+            count_down: StackSafeFn::from_fn(trampoline_tco(|n: u64| {
+                move |_: u64| {
+                    if n == 0 {
+                        0
+                    } else {
+                        yield Call::tail(n - 1)
+                    }
+                }
+            })),
+        }
+    }
+}
+
+#[test]
+fn functions_in_struct_fields() {
+    const LARGE: u64 = 10_000;
+    let result = with_stack_size(1024, || {
+        let interpreter = Interpreter::new();
+        (
+            (interpreter.triangular)(LARGE),
+            interpreter.count_down.call(LARGE),
+        )
+    });
+    assert_eq!(result.unwrap(), (LARGE * (LARGE + 1) / 2, 0));
+}
+
+fn apply_twice(f: impl Fn(u64) -> u64, arg: u64) -> u64 {
+    f(f(arg))
+}
+
+#[test]
+fn shared_functions() {
+    let mut registry: HashMap<&str, RecursiveFn<u64, u64>> = HashMap::new();
+    registry.insert(
+        "triangular",
+        RecursiveFn::new(|n: u64| move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) }),
+    );
+    let triangular = registry["triangular"].clone();
+    assert_eq!(apply_twice(triangular.clone(), 3), 21);
+    let handle = thread::spawn(move || triangular(100));
+    assert_eq!(handle.join().unwrap(), 5050);
+}
@@ -0,0 +1,107 @@
+use std::cell::Cell;
+
+use crate::{with_stack_size, Call, Fuel, Observer, Recursion, StackSafeError};
+
+const LARGE: u64 = 10_000;
+
+#[derive(Default)]
+struct MaxDepth(usize);
+
+impl<Arg> Observer<Arg> for MaxDepth {
+    fn enter(&mut self, _arg: &Arg, depth: usize) {
+        self.0 = self.0.max(depth);
+    }
+}
+
+#[test]
+fn options_combine() {
+    let calls = Cell::new(0);
+    let mut fib = Recursion::new(|n: u64| {
+        calls.set(calls.get() + 1);
+        move |_: u64| {
+            if n < 2 {
+                n
+            } else {
+                let a = yield (n - 1);
+                let b = yield (n - 2);
+                a + b
+            }
+        }
+    })
+    .memoize()
+    .depth_limit(100)
+    .build();
+    assert_eq!(fib(90).unwrap(), 2_880_067_194_370_816_120);
+    assert_eq!(calls.get(), 91);
+    assert!(matches!(
+        fib(200),
+        Err(StackSafeError::DepthLimit { max_depth: 100 })
+    ));
+
+    let mut triangular =
+        Recursion::new(|n: u64| move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) })
+            .stop(Fuel(1000))
+            .build();
+    assert_eq!(triangular(100).unwrap(), 5050);
+    assert!(matches!(
+        triangular(1000),
+        Err(StackSafeError::FuelExhausted)
+    ));
+}
+
+#[test]
+fn tail_calls_with_observer() {
+    let result = with_stack_size(1024, || {
+        let mut depth = MaxDepth::default();
+        let mut sum = Recursion::new(|(n, acc): (u64, u64)| {
+            move |_: u64| {
+                if n == 0 {
+                    acc
+                } else {
+                    yield Call::tail((n - 1, acc + n))
+                }
+            }
+        })
+        .tail_calls()
+        .observer(&mut depth)
+        .build();
+        let res = sum((LARGE, 0)).unwrap();
+        drop(sum);
+        (res, depth.0)
+    });
+    assert_eq!(result.unwrap(), (LARGE * (LARGE + 1) / 2, 0));
+}
+
+#[derive(Default)]
+struct Labels(Vec<(Option<String>, usize)>);
+
+impl<Arg> Observer<Arg> for Labels {
+    fn enter_labeled(&mut self, _arg: &Arg, label: Option<&str>, depth: usize) {
+        self.0.push((label.map(str::to_string), depth));
+    }
+}
+
+#[test]
+fn labels_reach_observers() {
+    let mut labels = Labels::default();
+    let mut sum = Recursion::new(|n: u64| {
+        move |_: u64| match n {
+            0 => 0,
+            1 => n + yield Call::normal(n - 1),
+            _ => n + yield Call::normal(n - 1).with_label(format!("visiting node {}", n - 1)),
+        }
+    })
+    .observer(&mut labels)
+    .build();
+    assert_eq!(sum(3).unwrap(), 6);
+    drop(sum);
+    assert_eq!(
+        labels.0,
+        vec![
+            (None, 0),
+            (Some("visiting node 2".to_string()), 1),
+            (Some("visiting node 1".to_string()), 2),
+            (None, 3),
+        ]
+    );
+}
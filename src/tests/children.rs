@@ -0,0 +1,125 @@
+use std::cmp::Ordering;
+
+use crate::{deep_cmp, map_mut, with_stack_size, ChildrenMut, Order};
+
+#[derive(Debug, PartialEq)]
+enum Expr {
+    Num(u64),
+    Var(&'static str),
+    Add(Box<Expr>, Box<Expr>),
+}
+
+impl ChildrenMut for Expr {
+    type IterMut<'a> = std::vec::IntoIter<&'a mut Expr>;
+
+    fn children_mut(&mut self) -> Self::IterMut<'_> {
+        match self {
+            Self::Num(_) | Self::Var(_) => vec![],
+            Self::Add(lhs, rhs) => vec![lhs.as_mut(), rhs.as_mut()],
+        }
+        .into_iter()
+    }
+}
+
+fn add(lhs: Expr, rhs: Expr) -> Expr {
+    Expr::Add(Box::new(lhs), Box::new(rhs))
+}
+
+fn fold_constants(expr: &mut Expr) {
+    map_mut(expr, Order::Post, |expr| {
+        if let Expr::Add(lhs, rhs) = expr {
+            if let (Expr::Num(x), Expr::Num(y)) = (lhs.as_ref(), rhs.as_ref()) {
+                *expr = Expr::Num(x + y);
+            }
+        }
+    })
+}
+
+#[test]
+fn renaming_and_folding() {
+    let mut expr = add(
+        add(Expr::Num(1), Expr::Num(2)),
+        add(Expr::Var("x"), Expr::Num(3)),
+    );
+    map_mut(&mut expr, Order::Pre, |expr| {
+        if let Expr::Var(name) = expr {
+            *name = "y";
+        }
+    });
+    fold_constants(&mut expr);
+    assert_eq!(expr, add(Expr::Num(3), add(Expr::Var("y"), Expr::Num(3))));
+}
+
+#[test]
+fn folding_is_stack_safe() {
+    const LARGE: u64 = 10_000;
+    let mut expr = Expr::Num(0);
+    for n in 1..=LARGE {
+        expr = add(expr, Expr::Num(n));
+    }
+    let result = with_stack_size(1024, move || {
+        fold_constants(&mut expr);
+        expr
+    });
+    assert_eq!(result.unwrap(), Expr::Num(LARGE * (LARGE + 1) / 2));
+}
+
+fn children(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Num(_) | Expr::Var(_) => vec![],
+        Expr::Add(lhs, rhs) => vec![lhs.as_ref(), rhs.as_ref()],
+    }
+}
+
+/// Orders like `#[derive(Ord)]` would: by variant, then by payload.
+fn shallow_cmp(a: &Expr, b: &Expr) -> Ordering {
+    match (a, b) {
+        (Expr::Num(x), Expr::Num(y)) => x.cmp(y),
+        (Expr::Var(x), Expr::Var(y)) => x.cmp(y),
+        (Expr::Add(..), Expr::Add(..)) => Ordering::Equal,
+        (Expr::Num(_), _) | (Expr::Var(_), Expr::Add(..)) => Ordering::Less,
+        _ => Ordering::Greater,
+    }
+}
+
+#[test]
+fn sorting_by_deep_comparison() {
+    let mut exprs = vec![
+        add(Expr::Var("x"), Expr::Num(1)),
+        Expr::Var("y"),
+        add(Expr::Num(2), Expr::Var("x")),
+        Expr::Num(7),
+        add(Expr::Var("x"), Expr::Num(0)),
+    ];
+    exprs.sort_by(|a, b| deep_cmp(a, b, children, shallow_cmp));
+    assert_eq!(
+        exprs,
+        vec![
+            Expr::Num(7),
+            Expr::Var("y"),
+            add(Expr::Num(2), Expr::Var("x")),
+            add(Expr::Var("x"), Expr::Num(0)),
+            add(Expr::Var("x"), Expr::Num(1)),
+        ]
+    );
+}
+
+#[test]
+fn deep_comparison_is_stack_safe() {
+    const LARGE: u64 = 10_000;
+    let chain = |last| {
+        let mut expr = Expr::Num(last);
+        for n in 1..=LARGE {
+            expr = add(expr, Expr::Num(n));
+        }
+        expr
+    };
+    let (a, b) = (chain(0), chain(1));
+    let result = with_stack_size(1024, || {
+        (
+            deep_cmp(&a, &b, children, shallow_cmp),
+            deep_cmp(&b, &b, children, shallow_cmp),
+        )
+    });
+    assert_eq!(result.unwrap(), (Ordering::Less, Ordering::Equal));
+}
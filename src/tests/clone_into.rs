@@ -0,0 +1,55 @@
+use super::node;
+use crate::dom::{depth, descendants};
+use crate::{deep_clone_into, with_stack_size, Arena, Storage};
+
+/// Renders every stored node as an s-expression and counts the allocations.
+#[derive(Default)]
+struct Render {
+    count: usize,
+}
+
+impl Storage<char> for Render {
+    type Handle = String;
+
+    fn store(&mut self, value: char, children: Vec<String>) -> String {
+        self.count += 1;
+        if children.is_empty() {
+            value.to_string()
+        } else {
+            format!("({} {})", value, children.join(" "))
+        }
+    }
+}
+
+#[test]
+fn clone_into_custom_storage() {
+    let tree = node(
+        'a',
+        vec![node('b', vec![node('c', vec![])]), node('d', vec![])],
+    );
+    let mut render = Render::default();
+    let res = deep_clone_into(&tree, &mut render, |tree| &tree.children, |tree| tree.label);
+    assert_eq!((res, render.count), ("(a (b c) d)".to_string(), 4));
+
+    let mut arena = Arena::new();
+    let root = deep_clone_into(&tree, &mut arena, |tree| &tree.children, |tree| tree.label);
+    let labels: String = descendants(arena.node(root))
+        .map(|node| *node.value())
+        .collect();
+    assert_eq!(labels, "abcd");
+}
+
+#[test]
+fn deep_clone_into_arena() {
+    const LARGE: usize = 10_000;
+    let result = with_stack_size(1024, || {
+        let mut tree = node('x', vec![]);
+        for _ in 1..LARGE {
+            tree = node('y', vec![tree]);
+        }
+        let mut arena = Arena::new();
+        let root = deep_clone_into(&tree, &mut arena, |tree| &tree.children, |tree| tree.label);
+        (arena.len(), depth(arena.node(root)))
+    });
+    assert_eq!(result.unwrap(), (LARGE, LARGE));
+}
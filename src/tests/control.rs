@@ -0,0 +1,114 @@
+use std::cell::Cell;
+use std::ops::ControlFlow;
+
+use crate::{
+    trampoline_break, trampoline_control, trampoline_labeled, with_stack_size, Control, Labeled,
+};
+
+const LARGE: u64 = 10_000;
+
+// Multiplies the numbers `n, n - 1, ..., 1` except that `zero_at` is
+// replaced by zero. Once the zero is reached, the product is known and the
+// pending multiplications are skipped by aborting to the prompt.
+fn product(n: u64, zero_at: u64, resumed: &Cell<u64>) -> u64 {
+    trampoline_control(|(n, prompt): (u64, bool)| {
+        move |_: u64| {
+            if prompt {
+                return yield Control::Prompt((n, false));
+            }
+            if n == zero_at {
+                yield Control::Abort(0);
+            }
+            if n == 0 {
+                return 1;
+            }
+            let product = yield Control::Call((n - 1, false));
+            resumed.set(resumed.get() + 1);
+            n.wrapping_mul(product)
+        }
+    })((n, true))
+}
+
+#[test]
+fn abort_to_prompt() {
+    let resumed = Cell::new(0);
+    assert_eq!(product(5, 100, &resumed), 120);
+    assert_eq!(resumed.get(), 5);
+    resumed.set(0);
+    assert_eq!(product(5, 2, &resumed), 0);
+    assert_eq!(resumed.get(), 0);
+
+    let nested = trampoline_control(|n: u64| {
+        move |_: u64| match n {
+            0 => yield Control::Abort(10),
+            1 => 1 + yield Control::Prompt(0),
+            _ => 100 + yield Control::Call(n - 1),
+        }
+    });
+    assert_eq!(nested(2), 111);
+    assert_eq!(nested(0), 10);
+}
+
+#[test]
+fn deep_aborts() {
+    let result = with_stack_size(1024, || {
+        let resumed = Cell::new(0);
+        (product(LARGE, 1, &resumed), resumed.get())
+    });
+    assert_eq!(result.unwrap(), (0, 0));
+}
+
+#[test]
+fn break_from_any_depth() {
+    // Finds the first multiple of `divisor` in the path `n, n - 1, ..., 0`
+    // and counts how many frames are resumed afterwards.
+    let result = with_stack_size(1024, || {
+        let resumed = Cell::new(0);
+        let find = trampoline_break(|(n, divisor): (u64, u64)| {
+            let resumed = &resumed;
+            move |_: ()| {
+                if n % divisor == 0 {
+                    return ControlFlow::Break(n);
+                }
+                yield (n - 1, divisor);
+                resumed.set(resumed.get() + 1);
+                ControlFlow::Continue(())
+            }
+        });
+        (find((LARGE, 7)), find((LARGE, LARGE + 1)), resumed.get())
+    });
+    assert_eq!(
+        result.unwrap(),
+        (ControlFlow::Break(LARGE / 7 * 7), ControlFlow::Break(0), 0)
+    );
+}
+
+// Counts the frames between `n` and the frame labeled `target`. Every
+// thousandth frame is labeled with its argument.
+fn labeled_distance(n: u64, target: u64) -> u64 {
+    trampoline_labeled(|n: u64| {
+        move |_: u64| {
+            if n == 0 {
+                return yield Labeled::ReturnTo(target, 0);
+            }
+            let res = if n % 1000 == 1 {
+                yield Labeled::Label(n - 1, n - 1)
+            } else {
+                yield Labeled::Call(n - 1)
+            };
+            res + 1
+        }
+    })(n)
+}
+
+#[test]
+fn return_to_label() {
+    let result = with_stack_size(1024, || {
+        (
+            labeled_distance(LARGE, 3000),
+            labeled_distance(LARGE, 0),
+            labeled_distance(LARGE, 5),
+        )
+    });
+    assert_eq!(result.unwrap(), (LARGE - 3000, LARGE, 0));
+}
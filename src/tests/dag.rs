@@ -0,0 +1,183 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ops::Generator;
+use std::rc::Rc;
+
+use super::shared_node;
+use crate::{
+    recurse_dag, recurse_dag_by, recurse_dag_by_ptr, recurse_graph_by_ptr, recurse_with_cache,
+    with_stack_size, ByPtr, Revisit, Visit,
+};
+
+type Node = super::SharedTree<()>;
+
+// A chain of `n` nodes in which every node refers to its successor twice.
+// Unfolded into a tree, it has `2^n - 1` nodes.
+fn diamonds(n: usize) -> Rc<Node> {
+    let mut node = shared_node((), vec![]);
+    for _ in 1..n {
+        node = shared_node((), vec![node.clone(), node]);
+    }
+    node
+}
+
+const LARGE: usize = 10_000;
+
+#[test]
+fn shared_nodes_are_evaluated_once() {
+    let calls = Cell::new(0);
+    let size = recurse_dag_by(
+        |node: &Rc<Node>| Rc::as_ptr(node),
+        |node: Rc<Node>| {
+            calls.set(calls.get() + 1);
+            move |_: u64| {
+                let mut size = 1;
+                for i in 0..node.children.len() {
+                    size += yield node.children[i].clone();
+                }
+                size
+            }
+        },
+    );
+    assert_eq!(size(diamonds(40)), (1 << 40) - 1);
+    assert_eq!(calls.get(), 40);
+
+    let result = with_stack_size(1024, || {
+        let depth = recurse_dag_by(
+            |node: &Rc<Node>| Rc::as_ptr(node),
+            |node: Rc<Node>| {
+                move |_: usize| {
+                    let mut depth = 0;
+                    for i in 0..node.children.len() {
+                        depth = depth.max(yield node.children[i].clone());
+                    }
+                    depth + 1
+                }
+            },
+        );
+        depth(diamonds(LARGE))
+    });
+    assert_eq!(result.unwrap(), LARGE);
+}
+
+#[test]
+fn pointer_identity() {
+    let size = recurse_dag_by_ptr(|node: Rc<Node>| {
+        move |_: u64| {
+            let mut size = 1;
+            for i in 0..node.children.len() {
+                size += yield node.children[i].clone();
+            }
+            size
+        }
+    });
+    let root = diamonds(40);
+    assert_eq!(size(root.clone()), (1 << 40) - 1);
+
+    let calls = Cell::new(0);
+    let mut cache = HashMap::new();
+    let mut depth = recurse_with_cache(
+        |node: ByPtr<Rc<Node>>| {
+            calls.set(calls.get() + 1);
+            move |_: usize| {
+                let mut depth = 0;
+                for i in 0..node.children.len() {
+                    depth = depth.max(yield ByPtr(node.children[i].clone()));
+                }
+                depth + 1
+            }
+        },
+        &mut cache,
+    );
+    assert_eq!(depth(ByPtr(root.clone())), 40);
+    assert_eq!(depth(ByPtr(root.children[0].clone())), 39);
+    assert_eq!(calls.get(), 40);
+}
+
+#[test]
+#[should_panic(expected = "cycle detected")]
+fn cycles_are_detected() {
+    recurse_dag(|n: u64| move |_: u64| yield ((n + 1) % 3))(0);
+}
+
+struct Object {
+    refs: RefCell<Vec<Rc<Object>>>,
+}
+
+// A chain of `n` objects in which every object also refers to the first one.
+// The caller has to break the cycles by clearing the references.
+fn ring(n: usize) -> Vec<Rc<Object>> {
+    let objects: Vec<_> = (0..n)
+        .map(|_| {
+            Rc::new(Object {
+                refs: RefCell::new(vec![]),
+            })
+        })
+        .collect();
+    for (i, object) in objects.iter().enumerate() {
+        let mut refs = object.refs.borrow_mut();
+        refs.extend(objects.get(i + 1).cloned());
+        refs.push(objects[0].clone());
+    }
+    objects
+}
+
+fn count(object: Rc<Object>) -> impl Generator<usize, Yield = Rc<Object>, Return = usize> {
+    move |_: usize| {
+        let refs = object.refs.borrow().clone();
+        let mut count = 1;
+        for object in refs {
+            count += yield object;
+        }
+        count
+    }
+}
+
+#[test]
+fn object_graphs_with_cycles() {
+    let cycles = Cell::new(0);
+    let count_objects = recurse_graph_by_ptr(count, |_, visit| {
+        assert_eq!(visit, Visit::Cycle);
+        cycles.set(cycles.get() + 1);
+        Revisit::Answer(0)
+    });
+    let objects = ring(5);
+    assert_eq!(count_objects(objects[0].clone()), 5);
+    assert_eq!(cycles.get(), 5);
+    objects
+        .iter()
+        .for_each(|object| object.refs.borrow_mut().clear());
+
+    let result = with_stack_size(1024, || {
+        let objects = ring(LARGE);
+        let count = recurse_graph_by_ptr(count, |_, _| Revisit::Answer(0))(objects[0].clone());
+        objects
+            .iter()
+            .for_each(|object| object.refs.borrow_mut().clear());
+        count
+    });
+    assert_eq!(result.unwrap(), LARGE);
+}
+
+#[test]
+fn shared_nodes_can_be_revisited() {
+    let size = |revisit: Revisit<u64>| {
+        recurse_graph_by_ptr(
+            |node: Rc<Node>| {
+                move |_: u64| {
+                    let mut size = 1;
+                    for i in 0..node.children.len() {
+                        size += yield node.children[i].clone();
+                    }
+                    size
+                }
+            },
+            move |_, visit| {
+                assert_eq!(visit, Visit::Shared);
+                revisit
+            },
+        )(diamonds(10))
+    };
+    assert_eq!(size(Revisit::Enter), (1 << 10) - 1);
+    assert_eq!(size(Revisit::Answer(0)), 10);
+}
@@ -0,0 +1,69 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{deep_drop_arc, deep_drop_rc, with_stack_size};
+
+const LARGE: u64 = 10_000;
+
+struct List {
+    value: u64,
+    next: Option<Rc<List>>,
+}
+
+impl Drop for List {
+    fn drop(&mut self) {
+        if let Some(next) = self.next.take() {
+            deep_drop_rc(next, |list| list.next.take());
+        }
+    }
+}
+
+struct Tree {
+    children: Vec<Arc<Tree>>,
+}
+
+impl Drop for Tree {
+    fn drop(&mut self) {
+        for child in std::mem::take(&mut self.children) {
+            deep_drop_arc(child, |tree| std::mem::take(&mut tree.children));
+        }
+    }
+}
+
+#[test]
+fn shared_tails_survive() {
+    let result = with_stack_size(1024, || {
+        let mut tail = Rc::new(List {
+            value: 0,
+            next: None,
+        });
+        for value in 1..LARGE {
+            tail = Rc::new(List {
+                value,
+                next: Some(tail),
+            });
+        }
+        let list = Rc::new(List {
+            value: LARGE,
+            next: Some(Rc::clone(&tail)),
+        });
+        drop(list);
+        let value = tail.value;
+        drop(tail);
+        value
+    });
+    assert_eq!(result.unwrap(), LARGE - 1);
+}
+
+#[test]
+fn deep_arc_trees() {
+    let mut tree = Arc::new(Tree { children: vec![] });
+    for _ in 0..LARGE {
+        let shared = Arc::new(Tree { children: vec![] });
+        tree = Arc::new(Tree {
+            children: vec![tree, Arc::clone(&shared), shared],
+        });
+    }
+    let result = with_stack_size(1024, move || drop(tree));
+    assert!(result.is_ok());
+}
@@ -0,0 +1,57 @@
+use super::node;
+use crate::{deep_diff, deep_diff_all, with_stack_size};
+
+type Tree = super::Tree<u32>;
+
+fn children(tree: &Tree) -> &[Tree] {
+    &tree.children
+}
+
+fn same_label(a: &Tree, b: &Tree) -> bool {
+    a.label == b.label
+}
+
+#[test]
+fn paths_to_differences() {
+    let a = node(
+        0,
+        vec![
+            node(1, vec![]),
+            node(2, vec![node(3, vec![])]),
+            node(4, vec![]),
+        ],
+    );
+    let b = node(
+        0,
+        vec![
+            node(1, vec![]),
+            node(2, vec![node(5, vec![])]),
+            node(6, vec![node(7, vec![])]),
+        ],
+    );
+    assert_eq!(deep_diff(&a, &a, children, same_label), None);
+    assert_eq!(deep_diff(&a, &b, children, same_label), Some(vec![1, 0]));
+    assert_eq!(
+        deep_diff_all(&a, &b, children, same_label, 10),
+        [vec![1, 0], vec![2]]
+    );
+    assert_eq!(
+        deep_diff_all(&a, &b, children, same_label, 0),
+        Vec::<Vec<usize>>::new()
+    );
+}
+
+#[test]
+fn deep_differences() {
+    const LARGE: usize = 10_000;
+    let chain = |leaf: u32| {
+        let mut tree = node(leaf, vec![]);
+        for _ in 1..LARGE {
+            tree = node(0, vec![tree]);
+        }
+        tree
+    };
+    let (a, b) = (chain(1), chain(2));
+    let result = with_stack_size(1024, move || deep_diff(&a, &b, children, same_label));
+    assert_eq!(result.unwrap(), Some(vec![0; LARGE - 1]));
+}
@@ -0,0 +1,32 @@
+use crate::disjoint_set::DisjointSet;
+use crate::with_stack_size;
+
+const LARGE: usize = 10_000;
+
+#[test]
+fn unions_and_finds() {
+    let mut sets = DisjointSet::new(6);
+    assert!(sets.union(0, 1));
+    assert!(sets.union(2, 3));
+    assert!(sets.union(1, 3));
+    assert!(!sets.union(0, 2));
+    assert_eq!(sets.count(), 3);
+    assert!(sets.same_set(0, 3));
+    assert!(!sets.same_set(0, 4));
+    assert_eq!(sets.set_size(2), 4);
+    let new = sets.push();
+    assert_eq!((new, sets.count()), (6, 4));
+}
+
+#[test]
+fn many_unions() {
+    let result = with_stack_size(1024, || {
+        let mut sets = DisjointSet::new(LARGE);
+        for elem in 1..LARGE {
+            sets.union(elem - 1, elem);
+        }
+        let roots: Vec<_> = (0..LARGE).map(|elem| sets.find(elem)).collect();
+        (sets.count(), roots.iter().all(|&root| root == roots[0]))
+    });
+    assert_eq!(result.unwrap(), (1, true));
+}
@@ -0,0 +1,65 @@
+use crate::dom::{depth, descendants, transform, Children};
+
+#[derive(Clone, Copy)]
+struct Node<'a> {
+    tag: &'a str,
+    tree: &'a [(&'a str, Vec<usize>)],
+}
+
+impl<'a> Children for Node<'a> {
+    type Iter = std::vec::IntoIter<Node<'a>>;
+
+    fn children(&self) -> Self::Iter {
+        let tree = self.tree;
+        let (_, children) = tree.iter().find(|(tag, _)| *tag == self.tag).unwrap();
+        let nodes: Vec<_> = children
+            .iter()
+            .map(|&index| Node {
+                tag: tree[index].0,
+                tree,
+            })
+            .collect();
+        nodes.into_iter()
+    }
+}
+
+#[test]
+fn generic_handles_are_traversed() {
+    let tree = [
+        ("html", vec![1, 2]),
+        ("head", vec![]),
+        ("body", vec![3]),
+        ("p", vec![]),
+    ];
+    let root = Node {
+        tag: "html",
+        tree: &tree,
+    };
+    let tags: Vec<_> = descendants(root).map(|node| node.tag).collect();
+    assert_eq!(tags, ["html", "head", "body", "p"]);
+    assert_eq!(depth(root), 3);
+    let printed = transform(root, |node, children: Vec<String>| {
+        format!("<{}>{}</{}>", node.tag, children.concat(), node.tag)
+    });
+    assert_eq!(printed, "<html><head></head><body><p></p></body></html>");
+}
+
+#[cfg(feature = "roxmltree")]
+#[test]
+fn deep_xml_is_stack_safe() {
+    const LARGE: usize = 10_000;
+
+    let xml = format!("{}x{}", "<a>".repeat(LARGE), "</a>".repeat(LARGE));
+    let doc = roxmltree::Document::parse(&xml).unwrap();
+    let root = doc.root_element();
+    let result = crate::with_stack_size(1024, || {
+        let elements = descendants(root).filter(|node| node.is_element()).count();
+        let text = transform(root, |node, texts: Vec<String>| {
+            node.text()
+                .map(String::from)
+                .unwrap_or_else(|| texts.concat())
+        });
+        (elements, depth(root), text)
+    });
+    assert_eq!(result.unwrap(), (LARGE, LARGE + 1, "x".to_string()));
+}
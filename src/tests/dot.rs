@@ -0,0 +1,49 @@
+use crate::{trampoline_observed, CallTree};
+
+#[test]
+fn fibonacci_call_tree() {
+    let fib = trampoline_observed(|n: u64| {
+        move |_: u64| {
+            if n < 2 {
+                n
+            } else {
+                (yield n - 1) + (yield n - 2)
+            }
+        }
+    });
+    let mut calls = CallTree::new(|n: &u64| format!("fib({})", n));
+    assert_eq!(fib(3, &mut calls), 2);
+    assert_eq!(calls.len(), 5);
+    assert_eq!(
+        calls.to_dot(),
+        "digraph calls {
+    n0 [label=\"fib(3)\"];
+    n1 [label=\"fib(2)\"];
+    n2 [label=\"fib(1)\"];
+    n3 [label=\"fib(0)\"];
+    n4 [label=\"fib(1)\"];
+    n0 -> n1;
+    n1 -> n2;
+    n1 -> n3;
+    n0 -> n4;
+}
+"
+    );
+}
+
+#[test]
+fn labels_are_escaped() {
+    let leaf = trampoline_observed(|s: &str| {
+        move |_: ()| {
+            if s.is_empty() {
+                yield s;
+            }
+        }
+    });
+    let mut calls = CallTree::new(|s: &&str| s.to_string());
+    leaf("a \"b\"\\\nc\r", &mut calls);
+    assert_eq!(
+        calls.to_dot(),
+        "digraph calls {\n    n0 [label=\"a \\\"b\\\"\\\\\\nc\\r\"];\n}\n"
+    );
+}
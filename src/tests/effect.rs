@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use crate::{trampoline_effects, with_stack_size, Effect, Handler};
+
+enum Expr {
+    Num(i64),
+    Var(&'static str),
+    Add(Box<Expr>, Box<Expr>),
+}
+
+impl Drop for Expr {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        if let Expr::Add(lhs, rhs) = self {
+            stack.push(std::mem::replace(lhs.as_mut(), Expr::Num(0)));
+            stack.push(std::mem::replace(rhs.as_mut(), Expr::Num(0)));
+        }
+        while let Some(mut expr) = stack.pop() {
+            if let Expr::Add(lhs, rhs) = &mut expr {
+                stack.push(std::mem::replace(lhs.as_mut(), Expr::Num(0)));
+                stack.push(std::mem::replace(rhs.as_mut(), Expr::Num(0)));
+            }
+        }
+    }
+}
+
+enum Request {
+    Lookup(&'static str),
+    Log(String),
+}
+
+struct Env {
+    vars: HashMap<&'static str, i64>,
+    log: Vec<String>,
+}
+
+impl Handler<Request, i64> for Env {
+    fn handle(&mut self, request: Request) -> i64 {
+        match request {
+            Request::Lookup(name) => self.vars[name],
+            Request::Log(line) => {
+                self.log.push(line);
+                0
+            }
+        }
+    }
+}
+
+fn eval(expr: &Expr, env: &mut Env) -> i64 {
+    trampoline_effects(|expr: &Expr| {
+        move |_: i64| match expr {
+            Expr::Num(n) => *n,
+            Expr::Var(name) => {
+                let value = yield Effect::Perform(Request::Lookup(name));
+                yield Effect::Perform(Request::Log(format!("{} = {}", name, value)));
+                value
+            }
+            Expr::Add(lhs, rhs) => {
+                let lhs = yield Effect::Call(lhs.as_ref());
+                let rhs = yield Effect::Call(rhs.as_ref());
+                lhs + rhs
+            }
+        }
+    })(expr, env)
+}
+
+fn env() -> Env {
+    Env {
+        vars: HashMap::from([("x", 1), ("y", 2)]),
+        log: Vec::new(),
+    }
+}
+
+#[test]
+fn handlers_answer_effects() {
+    let expr = Expr::Add(
+        Box::new(Expr::Var("x")),
+        Box::new(Expr::Add(Box::new(Expr::Num(3)), Box::new(Expr::Var("y")))),
+    );
+    let mut env = env();
+    assert_eq!(eval(&expr, &mut env), 6);
+    assert_eq!(env.log, ["x = 1", "y = 2"]);
+
+    let mut count = 0;
+    let sum = trampoline_effects(|n: i64| {
+        move |_: i64| match n {
+            0 => yield Effect::Perform(()),
+            _ => n + yield Effect::Call(n - 1),
+        }
+    })(3, &mut |()| {
+        count += 1;
+        100
+    });
+    assert_eq!((sum, count), (106, 1));
+}
+
+#[test]
+fn deep_effects() {
+    const LARGE: i64 = 10_000;
+    let mut expr = Expr::Num(0);
+    for _ in 0..LARGE {
+        expr = Expr::Add(Box::new(expr), Box::new(Expr::Var("y")));
+    }
+    let result = with_stack_size(1024, move || {
+        let mut env = env();
+        (eval(&expr, &mut env), env.log.len())
+    });
+    assert_eq!(result.unwrap(), (2 * LARGE, LARGE as usize));
+}
@@ -0,0 +1,50 @@
+use crate::{trampoline_bounded, BigStackThread, StackSafeError};
+
+#[test]
+fn panics_become_errors() {
+    let err = BigStackThread::new()
+        .stack_size(64 * 1024)
+        .run(|| panic!("boom"))
+        .unwrap_err();
+    assert!(
+        matches!(&err, StackSafeError::FramePanicked(payload) if payload.message() == Some("boom"))
+    );
+    assert_eq!(err.to_string(), "frame panicked: boom");
+}
+
+#[cfg(all(debug_assertions, target_os = "linux"))]
+#[test]
+fn probe_failures_become_errors() {
+    fn recursive(n: u64) -> u64 {
+        stack_probe!(depth = n as usize);
+        if n == u64::MAX {
+            0
+        } else {
+            1 + recursive(n + 1)
+        }
+    }
+
+    let err = BigStackThread::new()
+        .stack_size(256 * 1024)
+        .run(|| recursive(0))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        StackSafeError::OverflowDetected { depth: Some(_), .. }
+    ));
+}
+
+#[test]
+fn depth_limit_converts() {
+    let sum = |n: u64| -> Result<u64, StackSafeError> {
+        let res = trampoline_bounded(10, |n: u64| {
+            move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) }
+        })(n)?;
+        Ok(res)
+    };
+    assert_eq!(sum(5).unwrap(), 15);
+    assert!(matches!(
+        sum(100),
+        Err(StackSafeError::DepthLimit { max_depth: 10 })
+    ));
+}
@@ -0,0 +1,176 @@
+use crate::{
+    try_recurse, try_recurse_recover, try_recurse_tco, try_recurse_traced, with_stack_size, Call,
+    Recovered, Traced,
+};
+
+enum Expr {
+    Num(u64),
+    Var(&'static str),
+    Div(Box<Expr>, Box<Expr>),
+}
+
+impl Drop for Expr {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        if let Expr::Div(lhs, rhs) = self {
+            stack.push(std::mem::replace(lhs.as_mut(), Expr::Num(0)));
+            stack.push(std::mem::replace(rhs.as_mut(), Expr::Num(0)));
+        }
+        while let Some(mut expr) = stack.pop() {
+            if let Expr::Div(lhs, rhs) = &mut expr {
+                stack.push(std::mem::replace(lhs.as_mut(), Expr::Num(0)));
+                stack.push(std::mem::replace(rhs.as_mut(), Expr::Num(0)));
+            }
+        }
+    }
+}
+
+fn div(lhs: Expr, rhs: Expr) -> Expr {
+    Expr::Div(Box::new(lhs), Box::new(rhs))
+}
+
+fn label(expr: &&Expr) -> String {
+    match expr {
+        Expr::Num(n) => n.to_string(),
+        Expr::Var(x) => x.to_string(),
+        Expr::Div(..) => "_ / _".to_string(),
+    }
+}
+
+fn eval(expr: &Expr) -> Result<u64, String> {
+    try_recurse(|expr: &Expr| {
+        move |_: u64| match expr {
+            Expr::Num(n) => Ok(*n),
+            Expr::Var(x) => Err(format!("unbound variable {}", x)),
+            Expr::Div(lhs, rhs) => {
+                let x = yield lhs.as_ref();
+                let y = yield rhs.as_ref();
+                x.checked_div(y)
+                    .ok_or_else(|| "division by zero".to_string())
+            }
+        }
+    })(expr)
+}
+
+fn eval_traced(expr: &Expr) -> Result<u64, Traced<String, String>> {
+    try_recurse_traced(label, |expr: &Expr| {
+        move |_: u64| match expr {
+            Expr::Num(n) => Ok(*n),
+            Expr::Var(x) => Err(format!("unbound variable {}", x)),
+            Expr::Div(lhs, rhs) => {
+                let x = yield lhs.as_ref();
+                let y = yield rhs.as_ref();
+                x.checked_div(y)
+                    .ok_or_else(|| "division by zero".to_string())
+            }
+        }
+    })(expr)
+}
+
+#[test]
+fn errors_with_trace() {
+    let expr = div(Expr::Num(8), div(Expr::Num(4), Expr::Num(2)));
+    assert_eq!(eval(&expr), Ok(4));
+    assert_eq!(eval_traced(&expr), Ok(4));
+
+    let expr = div(Expr::Num(8), div(Expr::Var("x"), Expr::Num(2)));
+    assert_eq!(eval(&expr), Err("unbound variable x".to_string()));
+    let error = eval_traced(&expr).unwrap_err();
+    assert_eq!(error.trace, ["_ / _", "_ / _", "x"]);
+    assert_eq!(
+        error.to_string(),
+        "unbound variable x\n    in x\n    in _ / _\n    in _ / _"
+    );
+}
+
+#[test]
+fn deep_errors() {
+    const LARGE: usize = 10_000;
+    let mut expr = div(Expr::Num(1), Expr::Num(0));
+    for _ in 1..LARGE {
+        expr = div(expr, Expr::Num(1));
+    }
+    let result = with_stack_size(1024, move || {
+        let error = eval_traced(&expr).unwrap_err();
+        (eval(&expr), error.error, error.trace.len())
+    });
+    assert_eq!(
+        result.unwrap(),
+        (
+            Err("division by zero".to_string()),
+            "division by zero".to_string(),
+            LARGE
+        )
+    );
+}
+
+fn eval_recovering(expr: &Expr) -> Result<Recovered<&Expr, u64, String>, String> {
+    try_recurse_recover(
+        |expr: &Expr| {
+            move |_: u64| match expr {
+                Expr::Num(n) => Ok(*n),
+                Expr::Var(x) => Err(format!("unbound variable {}", x)),
+                Expr::Div(lhs, rhs) => {
+                    let x = yield lhs.as_ref();
+                    let y = yield rhs.as_ref();
+                    x.checked_div(y)
+                        .ok_or_else(|| "division by zero".to_string())
+                }
+            }
+        },
+        |expr, _| match expr {
+            Expr::Var(_) => Some(1),
+            _ => None,
+        },
+    )(expr)
+}
+
+#[test]
+fn recovered_errors() {
+    let expr = div(div(Expr::Var("x"), Expr::Num(1)), Expr::Var("y"));
+    let recovered = eval_recovering(&expr).unwrap();
+    assert_eq!(recovered.result, 1);
+    let errors: Vec<_> = recovered
+        .errors
+        .into_iter()
+        .map(|(expr, error)| (label(&expr), error))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            ("x".to_string(), "unbound variable x".to_string()),
+            ("y".to_string(), "unbound variable y".to_string())
+        ]
+    );
+
+    let expr = div(Expr::Var("x"), Expr::Num(0));
+    assert!(matches!(eval_recovering(&expr), Err(error) if error == "division by zero"));
+}
+
+#[test]
+fn tail_calls_with_errors() {
+    const LARGE: u64 = 10_000;
+    let result = with_stack_size(1024, || {
+        // Sums `1..=n` with tail calls, failing once the sum exceeds `limit`.
+        let sum = try_recurse_tco(|(n, acc, limit): (u64, u64, u64)| {
+            move |_: u64| {
+                if n == 0 {
+                    Ok(acc)
+                } else if acc > limit {
+                    Err(format!("sum exceeds {} with {} left", limit, n))
+                } else {
+                    let step = yield Call::normal((0, n, limit));
+                    Ok(yield Call::tail((n - 1, acc + step, limit)))
+                }
+            }
+        });
+        (sum((LARGE, 0, u64::MAX)), sum((LARGE, 0, 100)))
+    });
+    assert_eq!(
+        result.unwrap(),
+        (
+            Ok(LARGE * (LARGE + 1) / 2),
+            Err(format!("sum exceeds 100 with {} left", LARGE - 1))
+        )
+    );
+}
@@ -0,0 +1,40 @@
+use std::mem::MaybeUninit;
+use std::rc::Rc;
+
+use crate::{trampoline_on, ArrayStack, DepthExceeded, FrameStack, SliceStack};
+
+fn triangular(n: u64) -> impl std::ops::Generator<u64, Yield = u64, Return = u64> + Unpin {
+    move |_: u64| {
+        if n == 0 {
+            0
+        } else {
+            n + yield (n - 1)
+        }
+    }
+}
+
+#[test]
+fn array_stack_reports_overflow() {
+    let mut stack = ArrayStack::<_, 100>::new();
+    let mut run = trampoline_on(&mut stack, triangular);
+    assert_eq!(run(100), Ok(5050));
+    assert_eq!(run(1000), Err(DepthExceeded { max_depth: 100 }));
+    assert_eq!(run(10), Ok(55));
+}
+
+#[test]
+fn slice_stack_drops_remaining_frames() {
+    let marker = Rc::new(());
+    let mut slots: [MaybeUninit<Rc<()>>; 8] = unsafe { MaybeUninit::uninit().assume_init() };
+    let mut stack = SliceStack::new(&mut slots);
+    for _ in 0..8 {
+        stack.push(marker.clone()).unwrap();
+    }
+    assert!(stack.push(marker.clone()).is_err());
+    assert_eq!(Rc::strong_count(&marker), 9);
+    *stack.last_mut().unwrap() = Rc::new(());
+    assert!(stack.pop().is_some());
+    assert_eq!(Rc::strong_count(&marker), 8);
+    drop(stack);
+    assert_eq!(Rc::strong_count(&marker), 1);
+}
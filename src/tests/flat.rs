@@ -0,0 +1,66 @@
+use super::node;
+use crate::{flatten, unflatten, with_stack_size, FlatNode};
+
+type Tree = super::Tree<char>;
+
+fn unfold(mut tree: Tree) -> (char, Vec<Tree>) {
+    (tree.label, std::mem::take(&mut tree.children))
+}
+
+fn build(label: char, children: Vec<Tree>) -> Tree {
+    node(label, children)
+}
+
+#[test]
+fn round_trip() {
+    let tree = || {
+        node(
+            'a',
+            vec![node('b', vec![node('c', vec![])]), node('d', vec![])],
+        )
+    };
+    let nodes = flatten(tree(), unfold);
+    assert_eq!(
+        nodes,
+        [
+            FlatNode {
+                value: 'c',
+                children: vec![]
+            },
+            FlatNode {
+                value: 'b',
+                children: vec![0]
+            },
+            FlatNode {
+                value: 'd',
+                children: vec![]
+            },
+            FlatNode {
+                value: 'a',
+                children: vec![1, 2]
+            },
+        ]
+    );
+    assert_eq!(unflatten(nodes, 3, build), tree());
+}
+
+#[test]
+fn deep_round_trip() {
+    const LARGE: usize = 10_000;
+    let result = with_stack_size(1024, || {
+        let mut tree = node('x', vec![]);
+        for _ in 1..LARGE {
+            tree = node('y', vec![tree]);
+        }
+        let nodes = flatten(tree, unfold);
+        let len = nodes.len();
+        let mut tree = unflatten(nodes, len - 1, build);
+        let mut depth = 1;
+        while let Some(child) = tree.children.pop() {
+            tree = child;
+            depth += 1;
+        }
+        (len, depth, tree.label)
+    });
+    assert_eq!(result.unwrap(), (LARGE, LARGE, 'x'));
+}
@@ -0,0 +1,105 @@
+use std::cmp::max;
+use std::time::Instant;
+
+use super::node;
+use crate::{
+    fold_tree, recurse_fold, recurse_fold_until, with_stack_size, CancelToken, Deadline, Fuel,
+    Order, StackSafeError, Stop,
+};
+
+type Tree = super::Tree<char>;
+
+fn number(tree: &Tree, order: Order) -> Vec<(char, usize)> {
+    let (_, numbering) = recurse_fold(
+        |tree: &Tree| {
+            move |()| {
+                for child in &tree.children {
+                    yield child;
+                }
+            }
+        },
+        order,
+        |(next, mut numbering): (usize, Vec<_>), tree: &&Tree| {
+            numbering.push((tree.label, next));
+            (next + 1, numbering)
+        },
+    )(tree, (0, Vec::new()));
+    numbering
+}
+
+#[test]
+fn pre_and_post_order() {
+    let tree = node(
+        'a',
+        vec![node('b', vec![node('c', vec![])]), node('d', vec![])],
+    );
+    assert_eq!(
+        number(&tree, Order::Pre),
+        vec![('a', 0), ('b', 1), ('c', 2), ('d', 3)]
+    );
+    assert_eq!(
+        number(&tree, Order::Post),
+        vec![('c', 0), ('b', 1), ('d', 2), ('a', 3)]
+    );
+}
+
+#[test]
+fn count_is_stack_safe() {
+    const LARGE: usize = 10_000;
+    let mut tree = node('x', vec![]);
+    for _ in 1..LARGE {
+        tree = node('x', vec![tree]);
+    }
+    let count = recurse_fold(
+        |tree: &Tree| {
+            move |()| {
+                for child in &tree.children {
+                    yield child;
+                }
+            }
+        },
+        Order::Post,
+        |count, _| count + 1,
+    );
+    let result = with_stack_size(1024, || count(&tree, 0));
+    assert_eq!(result.unwrap(), LARGE);
+}
+
+#[test]
+fn fold_tree_depth() {
+    const LARGE: usize = 10_000;
+    let mut tree = node('x', vec![]);
+    for _ in 1..LARGE {
+        tree = node('x', vec![node('y', vec![]), tree]);
+    }
+    let depth = |tree: &Tree| fold_tree(tree, |tree| &tree.children, |_| 1, |d, c| max(d, c + 1));
+    let result = with_stack_size(1024, || depth(&tree));
+    assert_eq!(result.unwrap(), LARGE);
+}
+
+#[test]
+fn stopped_folds_return_partial_results() {
+    let count = recurse_fold_until(
+        |n: u64| {
+            move |()| {
+                if n > 0 {
+                    yield n - 1;
+                }
+            }
+        },
+        Order::Pre,
+        |acc: u64, _: &u64| acc + 1,
+    );
+    assert_eq!(count(10, 0, &mut Fuel(100) as &mut dyn Stop).unwrap(), 11);
+    let partial = count(10, 0, &mut Fuel(5)).unwrap_err();
+    assert_eq!(partial.value, 5);
+    assert!(matches!(partial.reason, StackSafeError::FuelExhausted));
+
+    let token = CancelToken::new();
+    token.clone().cancel();
+    let partial = count(10, 0, &mut token.clone()).unwrap_err();
+    assert_eq!(partial.value, 0);
+    assert!(matches!(partial.reason, StackSafeError::Cancelled));
+    let partial = count(10, 0, &mut Deadline(Instant::now())).unwrap_err();
+    assert!(matches!(partial.reason, StackSafeError::DeadlineExceeded));
+}
@@ -0,0 +1,63 @@
+use std::mem::size_of;
+
+use crate::{recurse_fn, trampoline, with_stack_size, BoxedFrame};
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn explicit_types() {
+    let triangular =
+        recurse_fn::<u64, u64, _>(|n| move |_| if n == 0 { 0 } else { n + yield (n - 1) });
+    let result = with_stack_size(1024, move || triangular(LARGE));
+    assert_eq!(result.unwrap(), LARGE * (LARGE + 1) / 2);
+}
+
+#[test]
+fn boxed_frames() {
+    // Odd and even arguments get frames of different types.
+    let triangular = trampoline(|n: u64| -> BoxedFrame<u64, u64> {
+        if n % 2 == 1 {
+            Box::new(move |_: u64| {
+                let res = yield (n - 1);
+                res + n
+            })
+        } else {
+            Box::new(move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) })
+        }
+    });
+    assert_eq!(triangular(100), 5050);
+}
+
+#[crate::frame]
+fn triangular(n: u64) -> Frame<u64, u64> {
+    if n == 0 {
+        0
+    } else {
+        let res: u64 = yield n - 1;
+        n + res
+    }
+}
+
+#[crate::frame]
+fn sum<'a>(xs: &'a [u64]) -> Frame<&'a [u64], u64> {
+    match xs {
+        [] => 0,
+        [x, rest @ ..] => {
+            let x: u64 = *x;
+            let sum: u64 = yield rest;
+            x + sum
+        }
+    }
+}
+
+#[test]
+fn expanded_frames() {
+    let result = with_stack_size(1024, || trampoline(triangular)(LARGE));
+    assert_eq!(result.unwrap(), LARGE * (LARGE + 1) / 2);
+    // Only `n` is kept across the `yield`.
+    assert_eq!(size_of::<TriangularFrame>(), 2 * size_of::<u64>());
+
+    let xs: Vec<u64> = (1..=LARGE).collect();
+    let result = with_stack_size(1024, || trampoline(sum)(&xs));
+    assert_eq!(result.unwrap(), LARGE * (LARGE + 1) / 2);
+}
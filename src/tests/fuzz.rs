@@ -0,0 +1,64 @@
+use arbitrary::{Arbitrary, Unstructured};
+use rand::random;
+
+use crate::list::List;
+use crate::testdata::fuzz::{self, ArbitraryGraph, Shape};
+use crate::testdata::{calc, tree};
+use crate::{graph, trampoline, with_stack_size};
+
+const LARGE: usize = 10_000;
+
+fn data() -> Vec<u8> {
+    (0..1 << 20).map(|_| random()).collect()
+}
+
+fn depth_and_size(tree: &tree::Tree) -> (usize, usize) {
+    trampoline(|tree: &tree::Tree| {
+        move |_: (usize, usize)| {
+            let (mut depth, mut size) = (0, 1);
+            for child in &tree.children {
+                let (child_depth, child_size) = yield child;
+                depth = depth.max(child_depth);
+                size += child_size;
+            }
+            (depth + 1, size)
+        }
+    })(tree)
+}
+
+#[test]
+fn deep_shapes_are_paths() {
+    let data = data();
+    let deep = Shape {
+        max_nodes: LARGE,
+        depth_bias: u8::MAX,
+    };
+    let result = with_stack_size(1024, || {
+        let mut u = Unstructured::new(&data);
+        let tree = fuzz::tree(&mut u, &deep).unwrap();
+        let (depth, size) = depth_and_size(&tree);
+        assert_eq!(depth, size);
+        let graph = fuzz::graph(&mut u, &deep).unwrap();
+        assert!(graph[..graph.len() - 1]
+            .iter()
+            .enumerate()
+            .all(|(index, edges)| edges[0] == graph::Node::new(index + 1)));
+        graph::tarjan(&graph);
+        drop(fuzz::expr(&mut u, &deep).unwrap());
+    });
+    result.unwrap();
+}
+
+#[test]
+fn arbitrary_inputs_can_be_dropped() {
+    let data = data();
+    with_stack_size(1024, || {
+        let mut u = Unstructured::new(&data);
+        let tree = tree::Tree::arbitrary(&mut u).unwrap();
+        let expr = calc::Expr::arbitrary(&mut u).unwrap();
+        let graph = ArbitraryGraph::arbitrary(&mut u).unwrap();
+        let list = List::<u8>::arbitrary(&mut u).unwrap();
+        drop((tree, expr, graph, list));
+    })
+    .unwrap();
+}
@@ -0,0 +1,104 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use crate::gametree::{alpha_beta, alpha_beta_with, iterative_deepening, Game, NoTable};
+use crate::{with_stack_size, Fuel, Never};
+
+// A pile of stones from which the players take turns removing between one
+// and `max_take` stones. Whoever cannot move loses. Piles that are a
+// multiple of `max_take + 1` are lost for the player to move.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct Pile {
+    stones: u32,
+    max_take: u32,
+}
+
+thread_local! {
+    static EVALUATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+impl Game for Pile {
+    type Move = u32;
+
+    fn moves(&self) -> Vec<u32> {
+        (1..=self.max_take.min(self.stones)).collect()
+    }
+
+    fn play(&self, take: &u32) -> Self {
+        Self {
+            stones: self.stones - take,
+            max_take: self.max_take,
+        }
+    }
+
+    fn evaluate(&self) -> i32 {
+        EVALUATIONS.with(|evaluations| evaluations.set(evaluations.get() + 1));
+        if self.stones == 0 {
+            -1
+        } else {
+            0
+        }
+    }
+}
+
+fn evaluations() -> usize {
+    EVALUATIONS.with(|evaluations| evaluations.replace(0))
+}
+
+const LARGE: u32 = 10_000;
+
+#[test]
+fn winning_moves_are_found() {
+    let pile = Pile {
+        stones: 14,
+        max_take: 3,
+    };
+    let outcome = alpha_beta(&pile, 14);
+    assert_eq!((outcome.score, outcome.best), (1, Some(2)));
+    let without_table = evaluations();
+
+    let mut table = HashMap::new();
+    let outcome = alpha_beta_with(&pile, 14, &mut table, &mut Never).unwrap();
+    assert_eq!((outcome.score, outcome.best), (1, Some(2)));
+    assert!(evaluations() < without_table);
+
+    let lost = Pile {
+        stones: 12,
+        max_take: 3,
+    };
+    assert_eq!(alpha_beta(&lost, 12).score, -1);
+    assert_eq!(alpha_beta(&lost, 2).score, 0);
+}
+
+#[test]
+fn iterative_deepening_stops_early() {
+    let pile = Pile {
+        stones: 30,
+        max_take: 3,
+    };
+    let mut table = HashMap::new();
+    let outcome = iterative_deepening(&pile, 30, &mut table, &mut Never).unwrap();
+    assert_eq!(
+        (outcome.score, outcome.best, outcome.depth),
+        (1, Some(2), 30)
+    );
+
+    let outcome = iterative_deepening(&pile, 30, &mut NoTable, &mut Fuel(1000)).unwrap();
+    assert!(outcome.depth < 30);
+    assert_eq!(
+        iterative_deepening(&pile, 30, &mut NoTable, &mut Fuel(0)),
+        None
+    );
+}
+
+#[test]
+fn deep_games() {
+    let result = with_stack_size(1024, || {
+        let pile = Pile {
+            stones: LARGE,
+            max_take: 1,
+        };
+        alpha_beta(&pile, LARGE).score
+    });
+    assert_eq!(result.unwrap(), -1);
+}
@@ -0,0 +1,127 @@
+use crate::graph::{
+    euler_tour, kruskal, tarjan, tarjan_condensed, weak_components, Condensation, Graph, Node,
+};
+use crate::with_stack_size;
+
+fn nodes(ids: &[usize]) -> Vec<Node> {
+    ids.iter().copied().map(Node::new).collect()
+}
+
+#[test]
+fn condensation_of_simple_graph() {
+    let graph: Graph = vec![
+        nodes(&[1]),
+        nodes(&[2, 3]),
+        nodes(&[1, 4]),
+        nodes(&[2]),
+        vec![],
+    ];
+    let sccs = vec![nodes(&[4]), nodes(&[3, 2, 1]), nodes(&[0])];
+    assert_eq!(tarjan(&graph), sccs);
+    assert_eq!(
+        tarjan_condensed(&graph),
+        Condensation {
+            sccs,
+            components: vec![2, 1, 1, 1, 0],
+            dag: vec![vec![], nodes(&[0]), nodes(&[1])],
+        }
+    );
+}
+
+#[test]
+fn spanning_forests_and_weak_components() {
+    let graph: Graph = vec![nodes(&[1]), vec![], nodes(&[1]), nodes(&[4]), vec![]];
+    assert_eq!(
+        weak_components(&graph),
+        vec![nodes(&[0, 1, 2]), nodes(&[3, 4])]
+    );
+
+    let (a, b, c, d) = (Node::new(0), Node::new(1), Node::new(2), Node::new(3));
+    let edges = [(a, b, 4), (b, c, 1), (a, c, 2), (c, d, 7), (b, d, 5)];
+    assert_eq!(kruskal(5, &edges), vec![(b, c, 1), (a, c, 2), (b, d, 5)]);
+}
+
+#[test]
+fn deep_graphs() {
+    const LARGE: usize = 10_000;
+    // A path `0 -> 1 -> ... -> LARGE - 1` with back edges to `0` from every
+    // node but the last one.
+    let mut graph: Graph = (1..LARGE).map(|id| nodes(&[id, 0])).collect();
+    graph.push(vec![]);
+    let result = with_stack_size(1024, move || tarjan_condensed(&graph));
+    let condensation = result.unwrap();
+    assert_eq!(condensation.sccs.len(), 2);
+    assert_eq!(condensation.sccs[1].len(), LARGE - 1);
+    assert_eq!(condensation.components[LARGE - 1], 0);
+    assert_eq!(condensation.dag, vec![vec![], nodes(&[0])]);
+}
+
+#[test]
+fn euler_tours() {
+    // 0 -- 1 -- 3
+    // |    `--- 4
+    // 2
+    let tree: Graph = vec![
+        nodes(&[1, 2]),
+        nodes(&[0, 3, 4]),
+        nodes(&[0]),
+        nodes(&[1]),
+        nodes(&[1]),
+    ];
+    let tour = euler_tour(&tree, Node::new(0));
+    assert_eq!(tour.tour, nodes(&[0, 1, 3, 1, 4, 1, 0, 2, 0]));
+    assert_eq!(tour.order, nodes(&[0, 1, 3, 4, 2]));
+    assert_eq!(tour.first, [0, 1, 7, 2, 4]);
+    assert_eq!(tour.subtree(Node::new(1)), 1..4);
+    assert!(tour.is_ancestor(Node::new(1), Node::new(4)));
+    assert!(!tour.is_ancestor(Node::new(2), Node::new(4)));
+
+    const LARGE: usize = 100_000;
+    let path: Graph = (0..LARGE)
+        .map(|id| {
+            if id + 1 < LARGE {
+                nodes(&[id + 1])
+            } else {
+                vec![]
+            }
+        })
+        .collect();
+    let tour = euler_tour(&path, Node::new(0));
+    assert_eq!(tour.tour.len(), 2 * LARGE - 1);
+    assert_eq!(tour.subtree(Node::new(LARGE / 2)), LARGE / 2..LARGE);
+}
+
+#[test]
+fn implicit_graphs() {
+    use crate::graph::{implicit_dfs, Event};
+    use std::collections::HashSet;
+
+    // The states `0..10` with moves `n -> 2n mod 10` and `n -> n + 1 mod 10`.
+    let mut visited = HashSet::new();
+    let events: Vec<_> = implicit_dfs(1, |&n: &u32| [2 * n % 10, (n + 1) % 10], &mut visited)
+        .take(6)
+        .collect();
+    assert_eq!(
+        events,
+        [
+            Event::Enter(1),
+            Event::Enter(2),
+            Event::Enter(4),
+            Event::Enter(8),
+            Event::Enter(6),
+            Event::Enter(7),
+        ]
+    );
+
+    const LARGE: usize = 100_000;
+    let mut visited = vec![false; LARGE];
+    let mut exits = 0;
+    let mut last_entered = 0;
+    for event in implicit_dfs(0, |&n: &usize| (n + 1 < LARGE).then(|| n + 1), &mut visited) {
+        match event {
+            Event::Enter(n) => last_entered = n,
+            Event::Exit(_) => exits += 1,
+        }
+    }
+    assert_eq!((last_entered, exits), (LARGE - 1, LARGE));
+}
@@ -0,0 +1,61 @@
+use std::cell::Cell;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use super::shared_node;
+use crate::{with_stack_size, StructuralHasher};
+
+type Node = super::SharedTree<u32>;
+
+fn chain(n: u32, last: u32) -> Rc<Node> {
+    (0..n).fold(shared_node(last, vec![]), |tail, label| {
+        shared_node(label, vec![tail])
+    })
+}
+
+fn children(node: &Node) -> impl Iterator<Item = &Node> {
+    node.children.iter().map(|child| &**child)
+}
+
+fn hash<'a>(hasher: &mut StructuralHasher<'a, Node>, root: &'a Node) -> u64 {
+    hasher.hash(root, children, |node, state| node.label.hash(state))
+}
+
+const LARGE: u32 = 10_000;
+
+#[test]
+fn equal_structures_hash_equal() {
+    let result = with_stack_size(1024, || {
+        let mut hasher = StructuralHasher::new();
+        let (a, b, c) = (chain(LARGE, 0), chain(LARGE, 0), chain(LARGE, 1));
+        let (a, b, c) = (
+            hash(&mut hasher, &a),
+            hash(&mut hasher, &b),
+            hash(&mut hasher, &c),
+        );
+        (a == b, a == c)
+    });
+    assert_eq!(result.unwrap(), (true, false));
+}
+
+#[test]
+fn cached_subtrees_are_not_visited_again() {
+    let visits = Cell::new(0);
+    let shared = chain(100, 0);
+    let old = shared_node(0, vec![shared.clone(), shared_node(1, vec![])]);
+    let new = shared_node(0, vec![shared.clone(), shared_node(2, vec![])]);
+
+    let mut hasher = StructuralHasher::new().cached();
+    let mut hash = |root| {
+        hasher.hash(root, children, |node: &Node, state| {
+            visits.set(visits.get() + 1);
+            node.label.hash(state)
+        })
+    };
+    let old_hash = hash(&old);
+    assert_eq!(visits.get(), 103);
+    let new_hash = hash(&new);
+    assert_eq!(visits.get(), 105);
+    assert_ne!(old_hash, new_hash);
+    assert!(hasher.get(&shared).is_some());
+}
@@ -0,0 +1,50 @@
+use crate::hook::logical_backtrace;
+use crate::{install_panic_hook, trampoline_with_labels, with_stack_size};
+
+#[test]
+fn records_labels_of_active_calls() {
+    install_panic_hook();
+    let backtrace = trampoline_with_labels(
+        |n: &u64| format!("n = {}", n),
+        |_, n: u64| {
+            move |_: String| {
+                if n == 0 {
+                    logical_backtrace().unwrap()
+                } else {
+                    yield n - 1
+                }
+            }
+        },
+    )(2);
+    assert_eq!(
+        backtrace,
+        "logical recursion backtrace (innermost call last):\n\
+         \x20    0: \"n = 2\"\n\
+         \x20    1: \"n = 1\"\n\
+         \x20    2: \"n = 0\"\n"
+    );
+    assert_eq!(logical_backtrace(), None);
+}
+
+#[test]
+fn panicking_frames_clear_their_labels() {
+    install_panic_hook();
+    let result = with_stack_size(64 * 1024, || {
+        let result = std::panic::catch_unwind(|| {
+            trampoline_with_labels(
+                |n: &u64| *n,
+                |_, n: u64| {
+                    move |_: ()| {
+                        if n == 0 {
+                            panic!("bottom")
+                        } else {
+                            yield n - 1
+                        }
+                    }
+                },
+            )(3)
+        });
+        (result.is_err(), logical_backtrace())
+    });
+    assert!(matches!(result, Ok((true, None))));
+}
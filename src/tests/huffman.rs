@@ -0,0 +1,64 @@
+use crate::huffman::{build, code_table, decode, depth, encode, skewed, Tree};
+use crate::with_stack_size;
+
+#[test]
+fn round_trip() {
+    let tree = build(&[
+        (b'a' as u32, 5),
+        (b'b' as u32, 2),
+        (b'c' as u32, 1),
+        (b'd' as u32, 1),
+    ])
+    .unwrap();
+    assert_eq!(depth(&tree), 3);
+    let table = code_table(&tree);
+    assert_eq!(table[&(b'a' as u32)], [true]);
+    let text: Vec<u32> = "abacabad".bytes().map(u32::from).collect();
+    let bits = encode(&table, &text).unwrap();
+    assert_eq!(bits.len(), 14);
+    assert_eq!(decode(&tree, &bits).unwrap(), text);
+    assert_eq!(decode(&tree, &bits[..13]), None);
+
+    assert_eq!(build(&[]), None);
+    let single = build(&[(7, 1)]).unwrap();
+    assert_eq!(single, Tree::Leaf(7));
+    assert_eq!(
+        decode(&single, &encode(&code_table(&single), &[7, 7]).unwrap()).unwrap(),
+        [7, 7]
+    );
+}
+
+#[test]
+fn fibonacci_frequencies_are_skewed() {
+    let mut frequencies = vec![(0, 1), (1, 1)];
+    for symbol in 2..30 {
+        let n = frequencies.len();
+        frequencies.push((symbol, frequencies[n - 1].1 + frequencies[n - 2].1));
+    }
+    let tree = build(&frequencies).unwrap();
+    assert_eq!(depth(&tree), 29);
+    assert_eq!(code_table(&tree), code_table(&skewed(30)));
+}
+
+#[test]
+fn large_frequencies_do_not_overflow() {
+    let tree = build(&[(0, u64::MAX), (1, u64::MAX), (2, 1)]).unwrap();
+    let leaf = |symbol| Box::new(Tree::Leaf(symbol));
+    let expected = Tree::Node(leaf(1), Box::new(Tree::Node(leaf(2), leaf(0))));
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn deep_trees() {
+    const LARGE: u32 = 200_000;
+    let result = with_stack_size(1024, || {
+        let tree = skewed(LARGE);
+        let mut bits = vec![true; LARGE as usize - 2];
+        bits.extend([false, true, false, false]);
+        (depth(&tree), decode(&tree, &bits))
+    });
+    assert_eq!(
+        result.unwrap(),
+        (LARGE as usize - 1, Some(vec![0, LARGE - 2, LARGE - 1]))
+    );
+}
@@ -0,0 +1,56 @@
+use crate::{with_stack_size, EvalStatus, IncrementalEval};
+
+fn triangular<'a>(n: u64) -> IncrementalEval<'a, u64, u64> {
+    IncrementalEval::new(
+        |n: u64| move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) },
+        n,
+    )
+}
+
+#[test]
+fn advances_in_slices() {
+    let mut eval = triangular(3);
+    assert_eq!(eval.advance(2), EvalStatus::Pending { depth: 2 });
+    assert_eq!(eval.advance(0), EvalStatus::Pending { depth: 2 });
+    assert_eq!(eval.advance(3), EvalStatus::Pending { depth: 1 });
+    assert!(!eval.is_done());
+    assert_eq!(eval.advance(10), EvalStatus::Done(6));
+    assert!(eval.is_done());
+}
+
+#[test]
+fn deep_evaluations_span_many_slices() {
+    const LARGE: u64 = 10_000;
+    let result = with_stack_size(1024, || {
+        let mut eval = triangular(LARGE);
+        let mut slices = 1;
+        loop {
+            match eval.advance(100) {
+                EvalStatus::Pending { .. } => slices += 1,
+                EvalStatus::Done(res) => return (res, slices),
+            }
+        }
+    });
+    // Every call but the innermost one is resumed twice.
+    assert_eq!(result.unwrap(), (LARGE * (LARGE + 1) / 2, 201));
+}
+
+#[test]
+#[should_panic(expected = "evaluation was aborted by a panicking frame")]
+fn advancing_after_a_panic_reports_the_panic() {
+    let mut eval = IncrementalEval::new(
+        |n: u64| {
+            move |_: u64| {
+                if n == 0 {
+                    panic!("frame failed");
+                }
+                yield (n - 1)
+            }
+        },
+        3,
+    );
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| eval.advance(10)));
+    assert!(res.is_err());
+    assert!(!eval.is_done());
+    eval.advance(10);
+}
@@ -0,0 +1,106 @@
+use std::rc::Rc;
+
+use crate::{deep_drop_rc, recurse_dag_by_ptr, with_stack_size, ByPtr, Interner};
+
+enum Tree {
+    Leaf(u64),
+    Node(Vec<Tree>),
+}
+
+impl Drop for Tree {
+    fn drop(&mut self) {
+        if let Tree::Node(children) = self {
+            let mut stack = std::mem::take(children);
+            while let Some(mut tree) = stack.pop() {
+                if let Tree::Node(children) = &mut tree {
+                    stack.append(children);
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct Term {
+    leaf: Option<u64>,
+    children: Vec<ByPtr<Rc<Term>>>,
+}
+
+impl Drop for Term {
+    fn drop(&mut self) {
+        for child in std::mem::take(&mut self.children) {
+            deep_drop_rc(child.0, |term| {
+                std::mem::take(&mut term.children)
+                    .into_iter()
+                    .map(|child| child.0)
+            });
+        }
+    }
+}
+
+fn intern(interner: &mut Interner<Term>, tree: Tree) -> Rc<Term> {
+    interner.intern_owned(
+        tree,
+        |mut tree| match &mut tree {
+            Tree::Leaf(n) => (Some(*n), vec![]),
+            Tree::Node(children) => (None, std::mem::take(children)),
+        },
+        |leaf, children| Term {
+            leaf,
+            children: children.into_iter().map(ByPtr).collect(),
+        },
+    )
+}
+
+#[test]
+fn equal_subtrees_are_shared() {
+    let mut interner = Interner::new();
+    let pair = || Tree::Node(vec![Tree::Leaf(1), Tree::Leaf(2)]);
+    let term = intern(
+        &mut interner,
+        Tree::Node(vec![pair(), pair(), Tree::Leaf(1)]),
+    );
+    assert_eq!(interner.len(), 4);
+    assert!(term.children[0] == term.children[1]);
+    assert!(Rc::ptr_eq(
+        &term.children[2].0,
+        &term.children[0].children[0].0
+    ));
+}
+
+#[test]
+fn deep_trees_become_small_dags() {
+    const LARGE: u64 = 10_000;
+    // A tree of depth `LARGE` in which every node has two equal subtrees.
+    // Interning it must neither recurse nor unfold the subtrees.
+    let result = with_stack_size(1024, || {
+        let mut interner = Interner::new();
+        let mut term = intern(&mut interner, Tree::Leaf(0));
+        for _ in 0..LARGE {
+            let node = Term {
+                leaf: None,
+                children: vec![ByPtr(term.clone()), ByPtr(term)],
+            };
+            term = interner.intern(node);
+        }
+        let mut tree = Tree::Leaf(0);
+        for n in 1..=LARGE {
+            tree = Tree::Node(vec![tree, Tree::Leaf(n % 2)]);
+        }
+        let chain = intern(&mut interner, tree);
+        let depth = recurse_dag_by_ptr(|term: Rc<Term>| {
+            move |_: u64| {
+                let mut depth = 0;
+                for i in 0..term.children.len() {
+                    depth = depth.max(yield term.children[i].0.clone());
+                }
+                depth + 1
+            }
+        });
+        (depth(term), depth(chain), interner.len())
+    });
+    assert_eq!(
+        result.unwrap(),
+        (LARGE + 1, LARGE + 1, 2 * LARGE as usize + 2)
+    );
+}
@@ -0,0 +1,173 @@
+use std::ops::{Generator, GeneratorState};
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+
+use crate::checked::Checked;
+use crate::{
+    trampoline, trampoline_tco, trampoline_with, try_recurse_tco, Call, FrameStack, Recursion,
+};
+
+/// A frame stack whose `len` forgets about pops.
+struct Leaky<G> {
+    frames: Vec<G>,
+    pushed: usize,
+}
+
+impl<G> FrameStack<G> for Leaky<G> {
+    fn push(&mut self, frame: G) -> Result<(), G> {
+        self.frames.push(frame);
+        self.pushed += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<G> {
+        self.frames.pop()
+    }
+
+    fn last_mut(&mut self) -> Option<&mut G> {
+        self.frames.last_mut()
+    }
+
+    fn len(&self) -> usize {
+        self.pushed
+    }
+}
+
+#[test]
+#[should_panic(expected = "invariant violated: the frame stack holds 2 frames but 1 are suspended")]
+fn inconsistent_frame_stacks_are_detected() {
+    let f = trampoline_with(
+        || Leaky {
+            frames: Vec::new(),
+            pushed: 0,
+        },
+        |n: u64| {
+            move |_: u64| {
+                if n == 0 {
+                    0
+                } else {
+                    let a = yield (n - 1);
+                    let b = yield (n - 1);
+                    a + b
+                }
+            }
+        },
+    );
+    f(2);
+}
+
+fn resume<G: Generator<u64> + Unpin>(frame: &mut G) -> GeneratorState<G::Yield, G::Return> {
+    Pin::new(frame).resume(0)
+}
+
+#[test]
+#[should_panic(expected = "invariant violated: a completed frame was resumed")]
+fn resuming_completed_frames_is_detected() {
+    let mut frame = Checked::new(|_: u64| {
+        yield 1;
+        2
+    });
+    assert!(matches!(resume(&mut frame), GeneratorState::Yielded(1)));
+    assert!(matches!(resume(&mut frame), GeneratorState::Complete(2)));
+    resume(&mut frame);
+}
+
+/// A hand-written frame that panics on its first resume and would happily
+/// carry on with inconsistent state afterwards.
+struct Flaky {
+    resumed: bool,
+}
+
+impl Generator<u64> for Flaky {
+    type Yield = u64;
+    type Return = u64;
+
+    fn resume(mut self: Pin<&mut Self>, _: u64) -> GeneratorState<u64, u64> {
+        if !self.resumed {
+            self.resumed = true;
+            panic!("flaky frame");
+        }
+        GeneratorState::Complete(0)
+    }
+}
+
+#[test]
+#[should_panic(expected = "invariant violated: a frame was resumed after it panicked")]
+fn resuming_panicked_frames_is_detected() {
+    let mut frame = Checked::new(Flaky { resumed: false });
+    let res = panic::catch_unwind(AssertUnwindSafe(|| resume(&mut frame)));
+    assert!(res.is_err());
+    resume(&mut frame);
+}
+
+#[test]
+fn checked_drivers_pass() {
+    let triangular = trampoline(|n: u64| move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) });
+    assert_eq!(triangular(100), 5050);
+    let count = trampoline_tco(|(n, acc): (u64, u64)| {
+        move |_: u64| {
+            if n == 0 {
+                acc
+            } else if n % 2 == 0 {
+                yield Call::tail((n - 1, acc + 1))
+            } else {
+                let res = yield Call::normal((n - 1, 0));
+                res + acc + 1
+            }
+        }
+    });
+    assert_eq!(count((100, 0)), 100);
+}
+
+#[test]
+#[should_panic(expected = "invariant violated: a frame yielded again after `Call::tail`")]
+fn trampoline_tco_detects_yields_after_tail_calls() {
+    let count = trampoline_tco(|n: u64| {
+        move |_: u64| {
+            if n == 0 {
+                1
+            } else {
+                let rest = yield Call::tail(n - 1);
+                let more = yield Call::normal(n - 1);
+                rest + more
+            }
+        }
+    });
+    count(3);
+}
+
+#[test]
+#[should_panic(expected = "invariant violated: a frame yielded again after `Call::tail`")]
+fn try_recurse_tco_detects_yields_after_tail_calls() {
+    let count = try_recurse_tco(|n: u64| {
+        move |_: u64| {
+            if n == 0 {
+                Ok::<_, ()>(1)
+            } else {
+                let rest = yield Call::tail(n - 1);
+                let more = yield Call::normal(n - 1);
+                Ok(rest + more)
+            }
+        }
+    });
+    let _ = count(3);
+}
+
+#[test]
+#[should_panic(expected = "invariant violated: a frame yielded again after `Call::tail`")]
+fn builder_detects_yields_after_tail_calls() {
+    let mut count = Recursion::new(|n: u64| {
+        move |_: u64| {
+            if n == 0 {
+                1
+            } else {
+                let rest = yield Call::tail(n - 1);
+                let more = yield Call::normal(n - 1);
+                rest + more
+            }
+        }
+    })
+    .tail_calls()
+    .build();
+    let _ = count(3);
+}
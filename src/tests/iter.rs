@@ -0,0 +1,50 @@
+use super::node;
+use crate::iter::{levelorder, postorder, preorder};
+use crate::with_stack_size;
+
+type Tree = super::Tree<u32>;
+
+fn children<'a>(tree: &&'a Tree) -> std::slice::Iter<'a, Tree> {
+    tree.children.iter()
+}
+
+const LARGE: u32 = 10_000;
+
+#[test]
+fn traversal_orders() {
+    let tree = node(
+        1,
+        vec![
+            node(2, vec![node(4, vec![])]),
+            node(3, vec![node(5, vec![])]),
+        ],
+    );
+    let labels = |iter: &mut dyn Iterator<Item = &Tree>| iter.map(|t| t.label).collect::<Vec<_>>();
+    assert_eq!(labels(&mut preorder(&tree, children)), [1, 2, 4, 3, 5]);
+    assert_eq!(labels(&mut postorder(&tree, children)), [4, 2, 5, 3, 1]);
+    assert_eq!(labels(&mut levelorder(&tree, children)), [1, 2, 3, 4, 5]);
+
+    let numbers: Vec<u32> = preorder(1, |&n| {
+        if n < 4 {
+            vec![2 * n, 2 * n + 1]
+        } else {
+            vec![]
+        }
+    })
+    .collect();
+    assert_eq!(numbers, [1, 2, 4, 5, 3, 6, 7]);
+}
+
+#[test]
+fn deep_traversals_are_stack_safe() {
+    let mut tree = node(0, vec![]);
+    for label in 1..LARGE {
+        tree = node(label, vec![tree]);
+    }
+    let result = with_stack_size(1024, || {
+        let first = preorder(&tree, children).last().unwrap().label;
+        let last = postorder(&tree, children).last().unwrap().label;
+        (first, last, levelorder(&tree, children).count())
+    });
+    assert_eq!(result.unwrap(), (0, LARGE - 1, LARGE as usize));
+}
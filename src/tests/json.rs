@@ -0,0 +1,75 @@
+use serde_json::json;
+
+use crate::json::{clone_value, drop_value, eq_values, merge_patch, parse, ParseError};
+use crate::{deep_merge, with_stack_size};
+
+const LARGE: usize = 100_000;
+
+fn nested(depth: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..depth {
+        input.push_str("[{\"a\":");
+    }
+    input.push_str("null");
+    for _ in 0..depth {
+        input.push_str("}]");
+    }
+    input
+}
+
+#[test]
+fn parse_agrees_with_serde_json() {
+    let input = r#"{"a": [1, -2.5e3, "x\"yé"], "b": {"c": true, "d": false}, "e": null, "f": []}"#;
+    let expected =
+        json!({"a": [1, -2.5e3, "x\"yé"], "b": {"c": true, "d": false}, "e": null, "f": []});
+    assert_eq!(parse(input), Ok(expected));
+    assert_eq!(parse("[1, 2"), Err(ParseError { offset: 5 }));
+    assert_eq!(parse("[1] x"), Err(ParseError { offset: 4 }));
+}
+
+#[test]
+fn deep_documents_do_not_overflow() {
+    let input = nested(LARGE);
+    with_stack_size(1024, || {
+        let value1 = parse(&input).unwrap();
+        let value2 = clone_value(&value1);
+        assert!(eq_values(&value1, &value2));
+        let value3 = parse(&input.replace("null", "0")).unwrap();
+        assert!(!eq_values(&value1, &value3));
+        drop_value(value1);
+        drop_value(value2);
+        drop_value(value3);
+        assert!(parse(&input[1..]).is_err());
+    })
+    .unwrap();
+}
+
+#[test]
+fn deep_duplicate_keys_do_not_overflow() {
+    let input = format!("{{\"a\":{},\"a\":1}}", nested(LARGE));
+    with_stack_size(1024, || {
+        let value = parse(&input).unwrap();
+        assert!(eq_values(&value, &json!({"a": 1})));
+    })
+    .unwrap();
+}
+
+#[test]
+fn merge_patch_follows_rfc_7386() {
+    let target = json!({"a": "b", "c": {"d": "e", "f": "g"}, "h": [1]});
+    let patch = json!({"a": "z", "c": {"f": null}, "h": {"i": 2}});
+    let expected = json!({"a": "z", "c": {"d": "e"}, "h": {"i": 2}});
+    assert_eq!(merge_patch(target.clone(), patch.clone()), expected);
+    let merged = deep_merge(target, patch, |_, patch| patch);
+    let expected = json!({"a": "z", "c": {"d": "e", "f": null}, "h": {"i": 2}});
+    assert_eq!(merged, expected);
+    with_stack_size(1024, || {
+        let target = parse(&nested(LARGE)).unwrap();
+        let patch = parse(&nested(LARGE).replace("null", "0")).unwrap();
+        let patched = merge_patch(target, clone_value(&patch));
+        assert!(eq_values(&patched, &patch));
+        drop_value(patched);
+        drop_value(patch);
+    })
+    .unwrap();
+}
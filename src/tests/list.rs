@@ -1,44 +1,20 @@
-use std::ops::Range;
-
+use crate::list::List;
 use crate::{trampoline, with_stack_size};
 
-enum List<T> {
-    Nil,
-    Cons { head: T, tail: Box<List<T>> },
-}
-
-impl<T> Drop for List<T> {
-    fn drop(&mut self) {
-        if let Self::Cons { .. } = self {
-            let mut list = std::mem::replace(self, List::Nil);
-            while let Self::Cons { head, tail } = &mut list {
-                let next = std::mem::replace(tail.as_mut(), Self::Nil);
-                unsafe {
-                    std::ptr::drop_in_place(head as *mut T);
-                    std::ptr::drop_in_place(tail as *mut Box<List<T>>);
-                }
-                std::mem::forget::<List<T>>(list);
-                list = next;
-            }
-            std::mem::forget::<List<T>>(list);
-        }
-    }
-}
-
 impl<T> List<T> {
     fn len_recursive(&self) -> usize {
-        match self {
-            Self::Nil => 0,
-            Self::Cons { head: _, tail } => 1 + tail.len_recursive(),
+        match self.split_first() {
+            None => 0,
+            Some((_, tail)) => 1 + tail.len_recursive(),
         }
     }
 
     fn len_stack_safe(&self) -> usize {
         trampoline(|list: &List<T>| {
-            move |_: usize| match list {
-                Self::Nil => 0,
-                Self::Cons { head: _, tail } => {
-                    let tail_len = yield tail.as_ref();
+            move |_: usize| match list.split_first() {
+                None => 0,
+                Some((_, tail)) => {
+                    let tail_len = yield tail;
                     1 + tail_len
                 }
             }
@@ -46,30 +22,82 @@ impl<T> List<T> {
     }
 }
 
-impl<T: std::iter::Step> From<Range<T>> for List<T> {
-    fn from(range: Range<T>) -> Self {
-        let mut result = Self::Nil;
-        for value in range.rev() {
-            result = Self::Cons {
-                head: value,
-                tail: Box::new(result),
-            }
-        }
-        result
-    }
-}
-
 const LARGE: usize = 10_000;
 
 #[test]
 #[ignore = "stack overflow is not an unwinding panic"]
 fn len_recursive_is_unsafe() {
-    let result = with_stack_size(10 * 1024, || List::from(0..LARGE).len_recursive());
+    let result = with_stack_size(10 * 1024, || {
+        (0..LARGE).collect::<List<_>>().len_recursive()
+    });
     assert!(result.is_err());
 }
 
 #[test]
 fn len_stack_safe_is_safe() {
-    let result = with_stack_size(1024, || List::from(0..LARGE).len_stack_safe());
+    let result = with_stack_size(1024, || (0..LARGE).collect::<List<_>>().len_stack_safe());
     assert_eq!(result.unwrap(), LARGE);
 }
+
+#[test]
+fn operations_on_long_lists() {
+    let result = with_stack_size(1024, || {
+        let mut list: List<usize> = (0..LARGE).rev().collect();
+        list.sort();
+        let doubled = list.map(|n| 2 * n);
+        let mut appended = list.clone();
+        appended.append(doubled);
+        appended.reverse();
+        (
+            appended.len(),
+            appended.first().copied(),
+            list.iter().sum::<usize>(),
+            list == (0..LARGE).collect(),
+        )
+    });
+    assert_eq!(
+        result.unwrap(),
+        (
+            2 * LARGE,
+            Some(2 * (LARGE - 1)),
+            LARGE * (LARGE - 1) / 2,
+            true
+        )
+    );
+}
+
+/// Compares by the key only, so that the sort has to keep the order of the
+/// labels of equal keys.
+#[derive(Clone, Copy, Debug)]
+struct Keyed(u8, char);
+
+impl PartialEq for Keyed {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Keyed {}
+
+impl PartialOrd for Keyed {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Keyed {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+#[test]
+fn sort_is_stable() {
+    let mut list: List<Keyed> = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (0, 'e')]
+        .iter()
+        .map(|&(key, label)| Keyed(key, label))
+        .collect();
+    list.sort();
+    let labels: String = list.iter().map(|keyed| keyed.1).collect();
+    assert_eq!(labels, "ebdac");
+}
@@ -0,0 +1,62 @@
+use std::ops::Generator;
+
+use super::node;
+use crate::{trampoline, with_stack_size};
+
+type Tree = super::Tree<u64>;
+
+fn leaf(value: u64) -> Tree {
+    node(value, Vec::new())
+}
+
+fn sum(tree: &Tree) -> u64 {
+    trampoline(|tree: &Tree| {
+        move |_: u64| yield_all!(tree.children.iter(), tree.label, |acc, res| acc + res)
+    })(tree)
+}
+
+fn depth(tree: &Tree) -> u64 {
+    trampoline(|tree: &Tree| {
+        move |_: u64| {
+            let depths = yield_all!(tree.children.iter());
+            1 + depths.into_iter().max().unwrap_or(0)
+        }
+    })(tree)
+}
+
+fn sum_children(tree: &Tree) -> impl Generator<u64, Yield = &Tree, Return = u64> + Unpin {
+    move |_: u64| yield_all!(tree.children.iter(), 0, |acc, res| acc + res)
+}
+
+fn sum_delegating(tree: &Tree) -> u64 {
+    trampoline(|tree: &Tree| move |_: u64| yield_from!(sum_children(tree)) + tree.label)(tree)
+}
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn yield_all_collects_and_folds() {
+    let tree = node(1, vec![leaf(2), node(3, vec![leaf(4)])]);
+    assert_eq!(sum(&tree), 10);
+    assert_eq!(depth(&tree), 3);
+
+    let mut tree = leaf(0);
+    for value in 1..LARGE {
+        tree = node(value, vec![tree, leaf(0)]);
+    }
+    let result = with_stack_size(1024, || (sum(&tree), depth(&tree)));
+    assert_eq!(result.unwrap(), (LARGE * (LARGE - 1) / 2, LARGE));
+}
+
+#[test]
+fn yield_from_forwards_calls() {
+    let tree = node(1, vec![leaf(2), node(3, vec![leaf(4)])]);
+    assert_eq!(sum_delegating(&tree), 10);
+
+    let mut tree = leaf(0);
+    for value in 1..LARGE {
+        tree = node(value, vec![tree]);
+    }
+    let result = with_stack_size(1024, || sum_delegating(&tree));
+    assert_eq!(result.unwrap(), LARGE * (LARGE - 1) / 2);
+}
@@ -0,0 +1,85 @@
+use std::ops::Generator;
+
+use crate::{trampoline_many, trampoline_many_scoped, with_stack_size, ManyCall};
+
+enum Exp {
+    Num(u64),
+    Add(Vec<Exp>),
+    Double(Box<Exp>),
+}
+
+impl Drop for Exp {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        match self {
+            Exp::Num(_) => {}
+            Exp::Add(exps) => stack.append(exps),
+            Exp::Double(exp) => stack.push(std::mem::replace(exp.as_mut(), Exp::Num(0))),
+        }
+        while let Some(mut exp) = stack.pop() {
+            match &mut exp {
+                Exp::Num(_) => {}
+                Exp::Add(exps) => stack.append(exps),
+                Exp::Double(exp) => stack.push(std::mem::replace(exp.as_mut(), Exp::Num(0))),
+            }
+        }
+    }
+}
+
+fn eval_frame(exp: &Exp) -> impl Generator<Vec<u64>, Yield = ManyCall<&Exp>, Return = u64> {
+    move |_: Vec<u64>| match exp {
+        Exp::Num(n) => *n,
+        Exp::Add(exps) => {
+            let results = yield ManyCall::many(exps.iter().collect());
+            results.into_iter().sum()
+        }
+        Exp::Double(exp) => {
+            let results = yield ManyCall::normal(exp.as_ref());
+            2 * results[0]
+        }
+    }
+}
+
+fn eval(exp: &Exp) -> u64 {
+    trampoline_many(eval_frame)(exp)
+}
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn batches_are_resumed_with_all_results() {
+    let exp = Exp::Add(vec![
+        Exp::Num(1),
+        Exp::Add(vec![]),
+        Exp::Double(Box::new(Exp::Add(vec![Exp::Num(2), Exp::Num(3)]))),
+    ]);
+    assert_eq!(eval(&exp), 11);
+}
+
+#[test]
+fn deep_batches_are_stack_safe() {
+    let mut exp = Exp::Num(1);
+    for n in 0..LARGE {
+        exp = Exp::Add(vec![Exp::Num(n), exp]);
+    }
+    let result = with_stack_size(1024, || eval(&exp));
+    assert_eq!(result.unwrap(), LARGE * (LARGE - 1) / 2 + 1);
+}
+
+#[test]
+fn scoped_batches_borrow_the_tree() {
+    let mut deep = Exp::Num(1);
+    for n in 0..LARGE {
+        deep = Exp::Add(vec![Exp::Num(n), deep]);
+    }
+    let wide = Exp::Add(
+        (0..100)
+            .map(|n| Exp::Double(Box::new(Exp::Num(n))))
+            .collect(),
+    );
+    let result = with_stack_size(64 * 1024, || {
+        let eval = trampoline_many_scoped(eval_frame, 2);
+        (eval(&deep), eval(&wide))
+    });
+    assert_eq!(result.unwrap(), (LARGE * (LARGE - 1) / 2 + 1, 9900));
+}
@@ -0,0 +1,55 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use crate::{recurse_with_cache, with_stack_size};
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn cache_survives_top_level_calls() {
+    let calls = Cell::new(0);
+    let mut cache = HashMap::new();
+    let mut fib = recurse_with_cache(
+        |n: u64| {
+            calls.set(calls.get() + 1);
+            move |_: u64| {
+                if n < 2 {
+                    n
+                } else {
+                    let a = yield (n - 1);
+                    let b = yield (n - 2);
+                    a.wrapping_add(b)
+                }
+            }
+        },
+        &mut cache,
+    );
+    assert_eq!(fib(50), 12_586_269_025);
+    assert_eq!(calls.get(), 51);
+    assert_eq!(fib(40), 102_334_155);
+    assert_eq!(fib(52), 32_951_280_099);
+    assert_eq!(calls.get(), 53);
+    drop(fib);
+    assert_eq!(cache.len(), 53);
+}
+
+#[test]
+fn cached_recursion_is_stack_safe() {
+    let mut cache = HashMap::new();
+    let result = with_stack_size(1024, || {
+        recurse_with_cache(
+            |n: u64| {
+                move |_: u64| {
+                    if n == 0 {
+                        0
+                    } else {
+                        n + yield (n - 1)
+                    }
+                }
+            },
+            &mut cache,
+        )(LARGE)
+    });
+    assert_eq!(result.unwrap(), LARGE * (LARGE + 1) / 2);
+    assert_eq!(cache.len() as u64, LARGE + 1);
+}
@@ -0,0 +1,89 @@
+use crate::{deep_merge, with_stack_size, MapLike};
+
+#[derive(Debug, PartialEq)]
+enum Tree {
+    Leaf(i32),
+    Map(Vec<(&'static str, Tree)>),
+}
+
+impl Drop for Tree {
+    fn drop(&mut self) {
+        if let Tree::Map(entries) = self {
+            let mut stack = std::mem::take(entries);
+            while let Some((_, mut tree)) = stack.pop() {
+                if let Tree::Map(entries) = &mut tree {
+                    stack.append(entries);
+                }
+            }
+        }
+    }
+}
+
+impl MapLike for Tree {
+    type Key = &'static str;
+
+    fn into_entries(mut self) -> Result<Vec<(Self::Key, Self)>, Self> {
+        match &mut self {
+            Tree::Map(entries) => Ok(std::mem::take(entries)),
+            Tree::Leaf(_) => Err(self),
+        }
+    }
+
+    fn from_entries(entries: Vec<(Self::Key, Self)>) -> Self {
+        Tree::Map(entries)
+    }
+}
+
+fn add(a: Tree, b: Tree) -> Tree {
+    match (&a, &b) {
+        (Tree::Leaf(a), Tree::Leaf(b)) => Tree::Leaf(a + b),
+        _ => b,
+    }
+}
+
+#[test]
+fn merges_common_keys() {
+    use Tree::*;
+    let a = Map(vec![
+        ("x", Leaf(1)),
+        ("y", Map(vec![("z", Leaf(2)), ("w", Leaf(3))])),
+    ]);
+    let b = Map(vec![
+        ("y", Map(vec![("w", Leaf(4)), ("v", Leaf(5))])),
+        ("u", Leaf(6)),
+        ("x", Map(vec![])),
+    ]);
+    let expected = Map(vec![
+        ("x", Map(vec![])),
+        (
+            "y",
+            Map(vec![("z", Leaf(2)), ("w", Leaf(7)), ("v", Leaf(5))]),
+        ),
+        ("u", Leaf(6)),
+    ]);
+    assert_eq!(deep_merge(a, b, add), expected);
+}
+
+#[test]
+fn deep_merge_does_not_overflow() {
+    const LARGE: usize = 10_000;
+    let result = with_stack_size(1024, || {
+        let chain = |leaf| {
+            let mut tree = Tree::Leaf(leaf);
+            for _ in 0..LARGE {
+                tree = Tree::Map(vec![("a", tree)]);
+            }
+            tree
+        };
+        let mut tree = deep_merge(chain(1), chain(2), add);
+        let mut depth = 0;
+        loop {
+            match tree.into_entries() {
+                Ok(mut entries) => tree = entries.pop().unwrap().1,
+                Err(leaf) => break (depth, leaf),
+            }
+            depth += 1;
+        }
+    });
+    assert_eq!(result.unwrap(), (LARGE, Tree::Leaf(3)));
+}
@@ -1,4 +1,123 @@
 mod ackermann;
+mod arena;
+mod auto_traits;
+mod batch;
+mod big_stack;
 mod binomial;
+mod bisim;
+mod boxed;
+mod builder;
+mod children;
+mod clone_into;
+mod control;
+mod dag;
+mod deep_drop;
+mod diff;
+mod disjoint_set;
+mod dom;
+mod dot;
+mod effect;
+mod error;
+mod fallible;
+mod fixed;
+mod flat;
+mod fold;
+mod frame;
+#[cfg(all(feature = "testdata", feature = "arbitrary"))]
+mod fuzz;
+mod gametree;
+mod graph;
+mod hash;
+mod hook;
+#[cfg(feature = "huffman")]
+mod huffman;
+mod incremental;
+mod interner;
+#[cfg(feature = "debug-invariants")]
+mod invariants;
+mod iter;
+#[cfg(feature = "json")]
+mod json;
 mod list;
+mod macros;
+mod many;
+mod memo;
+mod merge;
+mod off_thread;
+mod owned;
+#[cfg(feature = "rayon")]
+mod par;
+mod path;
+mod plan;
+mod probe;
+#[cfg(feature = "profiling")]
+mod profile;
+mod progress;
+mod reclaim;
+mod reduce;
+mod replay;
+mod reuse;
+mod rewrite;
+mod sched;
+mod scoped;
+mod search;
+mod snapshot;
+mod spill;
+mod stable;
+mod stack;
+#[cfg(feature = "stream")]
+mod stream;
+#[cfg(feature = "syn")]
+mod syn_expr;
+mod tail;
+mod tco;
+#[cfg(feature = "testdata")]
+mod testdata;
+mod thunk;
 mod triangular;
+mod visit;
+mod walk;
+mod zipper;
+
+use std::rc::Rc;
+
+/// A rose tree whose `Drop` does not recurse, for tests with deep trees.
+#[derive(Debug, Default, PartialEq)]
+struct Tree<L> {
+    label: L,
+    children: Vec<Tree<L>>,
+}
+
+impl<L> Drop for Tree<L> {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.children);
+        while let Some(mut tree) = stack.pop() {
+            stack.append(&mut tree.children);
+        }
+    }
+}
+
+fn node<L>(label: L, children: Vec<Tree<L>>) -> Tree<L> {
+    Tree { label, children }
+}
+
+/// Like `Tree`, but children can be shared, e.g., to build DAGs.
+struct SharedTree<L> {
+    label: L,
+    children: Vec<Rc<SharedTree<L>>>,
+}
+
+impl<L> Drop for SharedTree<L> {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.children);
+        while let Some(tree) = stack.pop() {
+            if let Ok(mut tree) = Rc::try_unwrap(tree) {
+                stack.append(&mut tree.children);
+            }
+        }
+    }
+}
+
+fn shared_node<L>(label: L, children: Vec<Rc<SharedTree<L>>>) -> Rc<SharedTree<L>> {
+    Rc::new(SharedTree { label, children })
+}
@@ -0,0 +1,37 @@
+use crate::{trampoline, BigStackThread, OffThread, StackSafeError};
+
+fn triangular(n: u64) -> u64 {
+    trampoline(|n: u64| move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) })(n)
+}
+
+#[test]
+fn streams_deep_inputs() {
+    let thread = BigStackThread::new().stack_size(16 * 1024).name("worker");
+    let worker = OffThread::spawn(thread, 2, triangular).unwrap();
+    let mut results = Vec::new();
+    for n in [10_000, 1, 100, 1000] {
+        worker.send(n).unwrap();
+        if let Some(res) = worker.try_recv() {
+            results.push(res);
+        }
+    }
+    results.push(worker.recv().unwrap());
+    results.extend(worker.finish().unwrap());
+    assert_eq!(results, [50_005_000, 1, 5050, 500_500]);
+}
+
+#[test]
+fn panics_stop_the_worker() {
+    let worker = OffThread::spawn(BigStackThread::new(), 1, |n: u64| {
+        assert!(n > 0, "zero");
+        n
+    })
+    .unwrap();
+    worker.send(0).unwrap();
+    assert_eq!(worker.recv(), None);
+    assert!(worker.send(1).is_err());
+    assert!(matches!(
+        worker.finish(),
+        Err(StackSafeError::FramePanicked(payload)) if payload.message() == Some("zero")
+    ));
+}
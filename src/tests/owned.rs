@@ -0,0 +1,119 @@
+use crate::{recurse_owned, try_recurse_owned, with_stack_size};
+
+enum Expr {
+    Num(u64),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Num(u64),
+    Add,
+    Mul,
+}
+
+fn to_rpn(expr: Expr) -> Vec<Token> {
+    recurse_owned(
+        |expr| match expr {
+            Expr::Num(n) => (Token::Num(n), vec![]),
+            Expr::Add(lhs, rhs) => (Token::Add, vec![*lhs, *rhs]),
+            Expr::Mul(lhs, rhs) => (Token::Mul, vec![*lhs, *rhs]),
+        },
+        |token, operands: Vec<Vec<Token>>| {
+            let mut operands = operands.into_iter();
+            let mut rpn = operands.next().unwrap_or_default();
+            for mut operand in operands {
+                rpn.append(&mut operand);
+            }
+            rpn.push(token);
+            rpn
+        },
+    )(expr)
+}
+
+#[test]
+fn simple_rpn() {
+    let expr = Expr::Add(
+        Box::new(Expr::Num(1)),
+        Box::new(Expr::Mul(Box::new(Expr::Num(2)), Box::new(Expr::Num(3)))),
+    );
+    assert_eq!(
+        to_rpn(expr),
+        vec![
+            Token::Num(1),
+            Token::Num(2),
+            Token::Num(3),
+            Token::Mul,
+            Token::Add
+        ]
+    );
+}
+
+#[test]
+fn rpn_is_stack_safe() {
+    const LARGE: u64 = 10_000;
+    let mut expr = Expr::Num(0);
+    for n in 1..LARGE {
+        expr = Expr::Add(Box::new(expr), Box::new(Expr::Num(n)));
+    }
+    let rpn = with_stack_size(1024, move || to_rpn(expr)).unwrap();
+    assert_eq!(rpn.len() as u64, 2 * LARGE - 1);
+    assert_eq!(rpn.last(), Some(&Token::Add));
+}
+
+enum Sexp {
+    Atom(&'static str),
+    List(Vec<Sexp>),
+}
+
+fn to_expr(sexp: Sexp) -> Result<Expr, String> {
+    try_recurse_owned(
+        |sexp| match sexp {
+            Sexp::Atom(atom) => match atom.parse() {
+                Ok(n) => Ok((Some(n), vec![])),
+                Err(_) => Err(format!("not a number: {}", atom)),
+            },
+            Sexp::List(mut sexps) => match sexps.first() {
+                Some(Sexp::Atom("+")) if sexps.len() == 3 => Ok((None, sexps.split_off(1))),
+                _ => Err(String::from("expected (+ _ _)")),
+            },
+        },
+        |num, mut operands: Vec<Expr>| match num {
+            Some(n) => Ok(Expr::Num(n)),
+            None => {
+                let rhs = operands.pop().unwrap();
+                let lhs = operands.pop().unwrap();
+                Ok(Expr::Add(Box::new(lhs), Box::new(rhs)))
+            }
+        },
+    )(sexp)
+}
+
+#[test]
+fn conversion_reports_deep_errors() {
+    const LARGE: usize = 10_000;
+    let make = |leaf| {
+        let mut sexp = Sexp::Atom(leaf);
+        for _ in 1..LARGE {
+            sexp = Sexp::List(vec![Sexp::Atom("+"), sexp, Sexp::Atom("1")]);
+        }
+        sexp
+    };
+    let good = make("0");
+    let bad = make("zero");
+    // The error leaves a deep sibling behind.
+    let worse = Sexp::List(vec![Sexp::Atom("+"), make("zero"), make("0")]);
+    let result = with_stack_size(1024, move || {
+        let rpn = to_expr(good).map(to_rpn).map(|rpn| rpn.len());
+        (rpn, to_expr(bad).err(), to_expr(worse).err())
+    });
+    assert_eq!(
+        result.unwrap(),
+        (
+            Ok(2 * LARGE - 1),
+            Some(String::from("not a number: zero")),
+            Some(String::from("not a number: zero"))
+        )
+    );
+}
@@ -0,0 +1,60 @@
+use rayon::prelude::*;
+
+use crate::{par_nodes, par_reachable, trampoline_many_par, with_stack_size, ManyCall};
+
+fn fib(n: u64) -> u64 {
+    trampoline_many_par(
+        |n: u64| {
+            move |_: Vec<u64>| {
+                if n < 2 {
+                    n
+                } else {
+                    let results = yield ManyCall::many(vec![n - 1, n - 2]);
+                    results[0] + results[1]
+                }
+            }
+        },
+        4,
+    )(n)
+}
+
+fn triangular(n: u64) -> u64 {
+    trampoline_many_par(
+        |n: u64| {
+            move |_: Vec<u64>| {
+                if n == 0 {
+                    0
+                } else {
+                    let results = yield ManyCall::many(vec![n - 1, 0]);
+                    n + results[0]
+                }
+            }
+        },
+        2,
+    )(n)
+}
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn parallel_batches_are_resumed_in_order() {
+    assert_eq!(fib(20), 6765);
+}
+
+#[test]
+fn deep_parallel_batches_are_stack_safe() {
+    let result = with_stack_size(64 * 1024, || triangular(LARGE));
+    assert_eq!(result.unwrap(), LARGE * (LARGE + 1) / 2);
+}
+
+#[test]
+fn nodes_of_deep_structures() {
+    let result = with_stack_size(64 * 1024, || {
+        let chain = par_nodes(0, |&n: &u64| if n < LARGE { Some(n + 1) } else { None });
+        // Every node has two successors, and following them leads in circles.
+        let graph = par_reachable(0, |&n: &u64| [(n + 1) % LARGE, n * 2 % LARGE]);
+        (chain.sum::<u64>(), graph.map(|n| n * n).sum::<u64>())
+    });
+    let squares = (0..LARGE).map(|n| n * n).sum();
+    assert_eq!(result.unwrap(), (LARGE * (LARGE + 1) / 2, squares));
+}
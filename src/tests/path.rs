@@ -0,0 +1,57 @@
+use crate::{trampoline_with_labels, trampoline_with_path, with_stack_size};
+
+enum Value {
+    Num(i64),
+    Obj(Vec<(&'static str, Value)>),
+}
+
+fn find_negative(value: &Value) -> Option<String> {
+    trampoline_with_labels(
+        |(key, _): &(&'static str, &Value)| *key,
+        |path, (_, value)| {
+            move |_: Option<String>| match value {
+                Value::Num(n) if *n < 0 => Some(path.with(|keys| keys[1..].join("."))),
+                Value::Num(_) => None,
+                Value::Obj(fields) => {
+                    for (key, field) in fields {
+                        let res = yield (*key, field);
+                        if res.is_some() {
+                            return res;
+                        }
+                    }
+                    None
+                }
+            }
+        },
+    )(("", value))
+}
+
+#[test]
+fn error_location() {
+    let value = Value::Obj(vec![
+        ("a", Value::Num(1)),
+        (
+            "b",
+            Value::Obj(vec![("c", Value::Num(2)), ("d", Value::Num(-3))]),
+        ),
+    ]);
+    assert_eq!(find_negative(&value), Some("b.d".to_string()));
+    assert_eq!(find_negative(&Value::Num(0)), None);
+}
+
+#[test]
+fn deep_paths() {
+    const LARGE: u64 = 10_000;
+    let result = with_stack_size(1024, || {
+        trampoline_with_path(|path, n: u64| {
+            move |_: (usize, u64)| {
+                if n == 0 {
+                    (path.len(), path.with(|args| args[0]))
+                } else {
+                    yield n - 1
+                }
+            }
+        })(LARGE)
+    });
+    assert_eq!(result.unwrap(), (LARGE as usize + 1, LARGE));
+}
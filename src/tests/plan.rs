@@ -0,0 +1,64 @@
+use std::cell::Cell;
+
+use crate::{recurse_dag, recurse_planned, with_stack_size};
+
+const LARGE: u64 = 10_000;
+
+fn edit_distance(a: &[u8], b: &[u8]) -> usize {
+    recurse_planned(
+        |&(i, j): &(usize, usize)| {
+            if i == 0 || j == 0 {
+                vec![]
+            } else {
+                vec![(i - 1, j - 1), (i - 1, j), (i, j - 1)]
+            }
+        },
+        |&(i, j), deps: &[usize]| match deps {
+            [] => i.max(j),
+            [both, left, right] => {
+                let substitute = both + usize::from(a[i - 1] != b[j - 1]);
+                substitute.min(left + 1).min(right + 1)
+            }
+            _ => unreachable!(),
+        },
+    )((a.len(), b.len()))
+}
+
+#[test]
+fn edit_distances() {
+    assert_eq!(edit_distance(b"kitten", b"sitting"), 3);
+    assert_eq!(edit_distance(b"", b"abc"), 3);
+    assert_eq!(edit_distance(b"stack", b"stack"), 0);
+    assert_eq!(edit_distance(b"flaw", b"lawn"), 2);
+}
+
+#[test]
+fn arguments_are_evaluated_once() {
+    let result = with_stack_size(1024, || {
+        let combined = Cell::new(0);
+        let fib = recurse_planned(
+            |&n: &u64| if n < 2 { vec![] } else { vec![n - 1, n - 2] },
+            |&n, deps: &[u64]| {
+                combined.set(combined.get() + 1);
+                if n < 2 {
+                    n
+                } else {
+                    deps[0].wrapping_add(deps[1])
+                }
+            },
+        );
+        let expected = recurse_dag(|n: u64| {
+            move |_: u64| {
+                if n < 2 {
+                    n
+                } else {
+                    let a = yield (n - 1);
+                    let b = yield (n - 2);
+                    a.wrapping_add(b)
+                }
+            }
+        });
+        (fib(LARGE) == expected(LARGE), combined.get())
+    });
+    assert_eq!(result.unwrap(), (true, LARGE + 1));
+}
@@ -0,0 +1,38 @@
+fn recursive(n: u64, depth: usize) -> u64 {
+    stack_probe!(depth = depth);
+    if n == 0 {
+        0
+    } else {
+        n + recursive(n - 1, depth + 1)
+    }
+}
+
+#[crate::test(stack_size = "256KiB")]
+fn probe_passes_with_enough_stack() {
+    stack_probe!();
+    assert_eq!(recursive(100, 0), 5050);
+}
+
+#[cfg(all(debug_assertions, target_os = "linux"))]
+#[crate::test(stack_size = "256KiB")]
+#[should_panic(expected = "about to overflow the stack at depth")]
+fn probe_panics_before_overflow() {
+    recursive(u64::MAX, 0);
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+#[crate::test(stack_size = "256KiB")]
+fn remaining_stack_shrinks_with_depth() {
+    #[inline(never)]
+    fn nested(levels: usize) -> usize {
+        if levels == 0 {
+            crate::remaining_stack().unwrap()
+        } else {
+            nested(levels - 1)
+        }
+    }
+
+    let top = crate::remaining_stack().unwrap();
+    assert!(0 < top && top < 1024 * 1024);
+    assert!(nested(10) < top);
+}
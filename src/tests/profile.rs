@@ -0,0 +1,51 @@
+use crate::{trampoline_observed, Profiler};
+
+#[test]
+fn folded_stacks() {
+    let triangular = trampoline_observed(|n: u64| {
+        move |_: u64| {
+            if n == 0 {
+                0
+            } else {
+                n + yield (n - 1)
+            }
+        }
+    });
+    let mut profiler = Profiler::new(|n: &u64| format!("t{}", n));
+    assert_eq!(triangular(2, &mut profiler), 3);
+
+    let depths: Vec<_> = profiler.samples().iter().map(|s| s.depth).collect();
+    assert_eq!(depths, vec![0, 1, 2]);
+
+    let mut folded = Vec::new();
+    profiler.write_folded(&mut folded).unwrap();
+    let stacks: Vec<_> = String::from_utf8(folded)
+        .unwrap()
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().0.to_string())
+        .collect();
+    assert_eq!(stacks, vec!["t2;t1;t0", "t2;t1", "t2"]);
+}
+
+#[test]
+fn folded_labels_are_sanitized() {
+    let count = trampoline_observed(|n: u64| {
+        move |_: u64| {
+            if n > 0 {
+                yield n - 1;
+            }
+            n
+        }
+    });
+    let mut profiler = Profiler::new(|n: &u64| format!("call {};\t{}", n, n));
+    count(1, &mut profiler);
+
+    let mut folded = Vec::new();
+    profiler.write_folded(&mut folded).unwrap();
+    let stacks: Vec<_> = String::from_utf8(folded)
+        .unwrap()
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().0.to_string())
+        .collect();
+    assert_eq!(stacks, vec!["call_1:_1;call_0:_0", "call_1:_1"]);
+}
@@ -0,0 +1,31 @@
+use crate::{trampoline_observed, Progress, ProgressUpdate};
+
+#[test]
+fn updates_are_reported() {
+    let mut updates = Vec::new();
+    {
+        let triangular = trampoline_observed(|n: u64| {
+            move |_: u64| {
+                if n == 0 {
+                    0
+                } else {
+                    n + yield (n - 1)
+                }
+            }
+        });
+        let mut progress = Progress::new(|update: &ProgressUpdate| updates.push(*update))
+            .total(8)
+            .interval(4);
+        assert_eq!(triangular(9, &mut progress), 45);
+        assert_eq!(progress.completed(), 10);
+    }
+
+    let summary: Vec<_> = updates
+        .iter()
+        .map(|update| (update.completed, update.depth, update.fraction()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![(4, 6, Some(0.5)), (8, 2, Some(1.0)), (10, 0, Some(1.0))]
+    );
+}
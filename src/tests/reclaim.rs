@@ -0,0 +1,45 @@
+use crate::{trampoline_reclaiming, try_drive, ReclaimingStack, Shrink};
+
+fn triangular(n: u64) -> impl std::ops::Generator<u64, Yield = u64, Return = u64> + Unpin {
+    move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) }
+}
+
+/// Runs a deep call followed by `shallow` shallow ones and returns the
+/// capacity after each of the shallow calls.
+fn capacities(policy: Shrink, shallow: usize) -> Vec<usize> {
+    let mut stack = ReclaimingStack::new(policy);
+    assert_eq!(try_drive(triangular, 1000, &mut stack), Ok(500_500));
+    stack.reclaim();
+    assert!(stack.capacity() >= 1000);
+    (0..shallow)
+        .map(|_| {
+            assert_eq!(try_drive(triangular, 10, &mut stack), Ok(55));
+            stack.reclaim();
+            stack.capacity()
+        })
+        .collect()
+}
+
+#[test]
+fn policies_free_capacity() {
+    assert!(capacities(Shrink::Never, 4).iter().all(|&c| c >= 1000));
+    assert!(capacities(Shrink::ToFit, 1).iter().all(|&c| c < 1000));
+
+    let decaying = capacities(Shrink::Decay(2), 12);
+    assert!(decaying[0] >= 1000);
+    assert!(decaying[1] < 1000);
+    assert!(decaying.windows(2).all(|pair| pair[1] <= pair[0]));
+    assert!(*decaying.last().unwrap() < 100);
+}
+
+#[test]
+fn explicit_shrink_and_trampoline() {
+    let mut stack = ReclaimingStack::new(Shrink::Never);
+    assert_eq!(try_drive(triangular, 1000, &mut stack), Ok(500_500));
+    stack.shrink();
+    assert_eq!(stack.capacity(), 0);
+
+    let mut f = trampoline_reclaiming(Shrink::ToFit, triangular);
+    assert_eq!(f(1000), 500_500);
+    assert_eq!(f(10), 55);
+}
@@ -0,0 +1,58 @@
+use super::node;
+use crate::{recurse_reduce, with_stack_size};
+
+type Tree = super::Tree<u64>;
+
+fn path(n: u64) -> Tree {
+    let mut tree = node(n, Vec::new());
+    for value in (1..n).rev() {
+        tree = node(value, vec![tree]);
+    }
+    tree
+}
+
+fn sum(tree: &Tree) -> u64 {
+    recurse_reduce(
+        |tree: &Tree| {
+            move |()| {
+                for child in &tree.children {
+                    yield child;
+                }
+                tree.label
+            }
+        },
+        |x, y| x + y,
+    )(tree)
+}
+
+fn preorder(tree: &Tree) -> Vec<u64> {
+    recurse_reduce(
+        |tree: &Tree| {
+            move |()| {
+                for child in &tree.children {
+                    yield child;
+                }
+                vec![tree.label]
+            }
+        },
+        |mut xs, mut ys| {
+            xs.append(&mut ys);
+            xs
+        },
+    )(tree)
+}
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn preorder_keeps_order() {
+    let leaf = |value| node(value, Vec::new());
+    let tree = node(1, vec![node(2, vec![leaf(3), leaf(4)]), leaf(5)]);
+    assert_eq!(preorder(&tree), vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn sum_is_stack_safe() {
+    let result = with_stack_size(1024, || sum(&path(LARGE)));
+    assert_eq!(result.unwrap(), LARGE * (LARGE + 1) / 2);
+}
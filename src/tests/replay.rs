@@ -0,0 +1,69 @@
+use std::ops::Generator;
+
+use crate::TraceEvent::{Call, Return};
+use crate::{replay, trampoline_recorded, with_stack_size, Divergence, Trace};
+
+// Fibonacci numbers, except that the base cases are multiplied by `base`.
+fn fib(n: u64, base: u64) -> impl Generator<u64, Yield = u64, Return = u64> {
+    move |_: u64| {
+        if n < 2 {
+            return n * base;
+        }
+        let a = yield n - 1;
+        let b = yield n - 2;
+        a + b
+    }
+}
+
+#[test]
+fn replays_detect_divergences() {
+    let mut trace = Trace::new();
+    assert_eq!(trampoline_recorded(|n| fib(n, 1))(3, &mut trace), 2);
+    let events = [
+        Call(3),
+        Call(2),
+        Call(1),
+        Return(1),
+        Call(0),
+        Return(0),
+        Return(1),
+        Call(1),
+        Return(1),
+        Return(2),
+    ];
+    assert_eq!(trace.events(), events);
+    assert_eq!(replay(|n| fib(n, 1), &trace), Ok(2));
+    assert_eq!(
+        replay(|n| fib(n, 2), &trace),
+        Err(Divergence {
+            step: 3,
+            expected: Some(Return(1)),
+            actual: Some(Return(2)),
+        })
+    );
+}
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn deep_traces() {
+    let result = with_stack_size(1024, || {
+        let triangular = |n: u64| {
+            move |_: u64| {
+                if n == 0 {
+                    0
+                } else {
+                    n + yield (n - 1)
+                }
+            }
+        };
+        let mut trace = Trace::new();
+        let res = trampoline_recorded(triangular)(LARGE, &mut trace);
+        (res, trace.len(), replay(triangular, &trace))
+    });
+    let expected = LARGE * (LARGE + 1) / 2;
+    assert_eq!(
+        result.unwrap(),
+        (expected, 2 * LARGE as usize + 2, Ok(expected))
+    );
+}
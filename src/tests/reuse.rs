@@ -0,0 +1,37 @@
+use crate::{release_retained, retained_bytes, trampoline_reusing, with_stack_size};
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn buffers_are_reused() {
+    let triangular = trampoline_reusing(1 << 20, |n: u64| {
+        move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) }
+    });
+    assert_eq!(triangular(100), 5050);
+    let retained = retained_bytes();
+    assert!(retained > 0);
+    assert_eq!(triangular(50), 1275);
+    assert_eq!(retained_bytes(), retained);
+    release_retained();
+    assert_eq!(retained_bytes(), 0);
+
+    let triangular = trampoline_reusing(0, |n: u64| {
+        move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) }
+    });
+    assert_eq!(triangular(100), 5050);
+    assert_eq!(retained_bytes(), 0);
+}
+
+#[test]
+fn large_inputs() {
+    let result = with_stack_size(1024, || {
+        let triangular = trampoline_reusing(1 << 20, |n: u64| {
+            move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) }
+        });
+        (triangular(LARGE), triangular(LARGE))
+    });
+    assert_eq!(
+        result.unwrap(),
+        (LARGE * (LARGE + 1) / 2, LARGE * (LARGE + 1) / 2)
+    );
+}
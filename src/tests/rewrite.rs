@@ -0,0 +1,94 @@
+use crate::rewrite::{first_of, rewrite, rewrite_until, Strategy, Term};
+use crate::{with_stack_size, Fuel, StackSafeError};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Num(u64),
+    Add,
+    Fst,
+    /// Rewrites to itself forever.
+    Loop,
+}
+
+#[derive(Debug, PartialEq)]
+struct Expr {
+    op: Op,
+    args: Vec<Expr>,
+}
+
+impl Drop for Expr {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.args);
+        while let Some(mut expr) = stack.pop() {
+            stack.append(&mut expr.args);
+        }
+    }
+}
+
+impl Term for Expr {
+    type Head = Op;
+
+    fn into_parts(mut self) -> (Op, Vec<Self>) {
+        (self.op, std::mem::take(&mut self.args))
+    }
+
+    fn from_parts(op: Op, args: Vec<Self>) -> Self {
+        Self { op, args }
+    }
+}
+
+fn expr(op: Op, args: Vec<Expr>) -> Expr {
+    Expr { op, args }
+}
+
+fn num(n: u64) -> Expr {
+    expr(Op::Num(n), vec![])
+}
+
+fn rules() -> impl FnMut(Expr) -> Result<Expr, Expr> {
+    first_of(vec![
+        Box::new(|mut e: Expr| match (e.op, e.args.as_slice()) {
+            (Op::Add, [a, b]) => match (a.op, b.op) {
+                (Op::Num(a), Op::Num(b)) => Ok(num(a + b)),
+                _ => Err(e),
+            },
+            (Op::Fst, [_, _]) => Ok(e.args.swap_remove(0)),
+            _ => Err(e),
+        }),
+        Box::new(|e: Expr| match e.op {
+            Op::Loop => Ok(expr(Op::Loop, vec![])),
+            _ => Err(e),
+        }),
+    ])
+}
+
+#[test]
+fn strategies() {
+    let term = || {
+        let sum = expr(Op::Add, vec![num(1), num(2)]);
+        expr(Op::Fst, vec![sum, expr(Op::Loop, vec![])])
+    };
+    // The outermost strategy discards the loop before it gets to it.
+    assert_eq!(rewrite(term(), Strategy::Outermost, rules()), num(3));
+
+    let result = rewrite_until(term(), Strategy::Innermost, rules(), &mut Fuel(100));
+    let partial = result.unwrap_err();
+    assert!(matches!(partial.reason, StackSafeError::FuelExhausted));
+    let expected = expr(Op::Fst, vec![num(3), expr(Op::Loop, vec![])]);
+    assert_eq!(partial.value, expected);
+}
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn deep_terms() {
+    let result = with_stack_size(1024, || {
+        // 1 + (1 + (... + 0))
+        let term = || (0..LARGE).fold(num(0), |sum, _| expr(Op::Add, vec![num(1), sum]));
+        (
+            rewrite(term(), Strategy::Innermost, rules()),
+            rewrite(term(), Strategy::Outermost, rules()),
+        )
+    });
+    assert_eq!(result.unwrap(), (num(LARGE), num(LARGE)));
+}
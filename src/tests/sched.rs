@@ -0,0 +1,54 @@
+use std::cell::RefCell;
+
+use crate::{trampoline_scheduled, with_stack_size, Bfs, Dfs, ManyCall, Priority, Scheduler, Task};
+
+// Sums up the labels of the complete binary tree with nodes `1..=15`, in
+// which node `n` has children `2n` and `2n + 1`, and logs the visiting order.
+fn sum<S: Scheduler<Task<u64>>>(new_scheduler: impl Fn() -> S) -> (u64, Vec<u64>) {
+    let log = RefCell::new(Vec::new());
+    let sum = trampoline_scheduled(new_scheduler, |n: u64| {
+        log.borrow_mut().push(n);
+        move |_: Vec<u64>| {
+            if n >= 8 {
+                n
+            } else {
+                let results = yield ManyCall::many(vec![2 * n, 2 * n + 1]);
+                n + results[0] + results[1]
+            }
+        }
+    })(1);
+    (sum, log.into_inner())
+}
+
+#[test]
+fn schedules_determine_order() {
+    let (total, dfs) = sum(Dfs::new);
+    assert_eq!(total, 120);
+    assert_eq!(dfs, [1, 2, 4, 8, 9, 5, 10, 11, 3, 6, 12, 13, 7, 14, 15]);
+
+    let (total, bfs) = sum(Bfs::new);
+    assert_eq!(total, 120);
+    assert_eq!(bfs, (1..=15).collect::<Vec<_>>());
+
+    let (total, odd_first) = sum(|| Priority::new(|task: &Task<u64>| task.arg().map(|n| n % 2)));
+    assert_eq!(total, 120);
+    assert_eq!(odd_first[..4], [1, 3, 7, 15]);
+}
+
+#[test]
+fn deep_schedules_are_stack_safe() {
+    const LARGE: u64 = 10_000;
+    let result = with_stack_size(1024, || {
+        let depth = trampoline_scheduled(Dfs::new, |n: u64| {
+            move |_: Vec<u64>| match n {
+                0 => 0,
+                _ => {
+                    let results = yield ManyCall::normal(n - 1);
+                    results[0] + 1
+                }
+            }
+        });
+        depth(LARGE)
+    });
+    assert_eq!(result.unwrap(), LARGE);
+}
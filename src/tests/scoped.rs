@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use crate::{recurse_scoped, with_stack_size};
+
+/// The number of names `name` expands to, where every name without a
+/// definition in `defs` counts as one.
+fn expansion_len<'s>(defs: &'s HashMap<&'s str, Vec<&'s str>>) -> impl Fn(&'s str) -> usize + 's {
+    recurse_scoped(defs, |defs, name: &'s str| {
+        move |_: usize| match defs.get(name) {
+            None => 1,
+            Some(body) => {
+                let mut len = 0;
+                for name in body {
+                    len += yield *name;
+                }
+                len
+            }
+        }
+    })
+}
+
+fn suffix_sum<'s>(input: &'s [u64]) -> impl Fn(usize) -> u64 + 's {
+    recurse_scoped(input, |input, i: usize| {
+        move |_: u64| {
+            if i == input.len() {
+                0
+            } else {
+                input[i] + (yield i + 1)
+            }
+        }
+    })
+}
+
+#[test]
+fn frames_borrow_from_scope() {
+    let defs: HashMap<_, _> = vec![("a", vec!["b", "c", "b"]), ("b", vec!["c", "d"])]
+        .into_iter()
+        .collect();
+    assert_eq!(expansion_len(&defs)("a"), 5);
+
+    const LARGE: u64 = 10_000;
+    let input: Vec<u64> = (0..LARGE).collect();
+    let result = with_stack_size(1024, || suffix_sum(&input)(0));
+    assert_eq!(result.unwrap(), LARGE * (LARGE - 1) / 2);
+}
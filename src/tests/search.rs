@@ -0,0 +1,60 @@
+use crate::search::{solutions, Resume, Step};
+use crate::with_stack_size;
+
+fn ordered_pairs(n: usize, cut: bool) -> Vec<(usize, usize)> {
+    solutions(
+        |first: bool| {
+            move |_: Resume<(usize, usize)>| {
+                if first {
+                    let pair = yield Step::Call(false);
+                    if cut {
+                        yield Step::Cut;
+                    }
+                    pair.into_solution()
+                } else {
+                    let a = (yield Step::Choose(n)).into_choice();
+                    let b = (yield Step::Choose(n)).into_choice();
+                    if a >= b {
+                        yield Step::Fail;
+                    }
+                    (a, b)
+                }
+            }
+        },
+        true,
+    )
+    .collect()
+}
+
+#[test]
+fn choice_points_and_cut() {
+    assert_eq!(ordered_pairs(3, false), [(0, 1), (0, 2), (1, 2)]);
+    assert_eq!(ordered_pairs(3, true), [(0, 1)]);
+    assert_eq!(ordered_pairs(1, false), []);
+}
+
+#[test]
+fn deep_backtracking() {
+    const LARGE: u64 = 10_000;
+    let result = with_stack_size(1024, || {
+        solutions(
+            |n: u64| {
+                move |_: Resume<u64>| {
+                    if n == 0 {
+                        let choice = (yield Step::Choose(3)).into_choice();
+                        if choice == 1 {
+                            yield Step::Fail;
+                        }
+                        choice as u64
+                    } else {
+                        let res = yield Step::Call(n - 1);
+                        res.into_solution() + 1
+                    }
+                }
+            },
+            LARGE,
+        )
+        .collect::<Vec<_>>()
+    });
+    assert_eq!(result.unwrap(), [LARGE, LARGE + 2]);
+}
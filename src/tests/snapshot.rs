@@ -0,0 +1,75 @@
+use std::ops::Generator;
+
+use crate::{with_stack_size, Computation, EvalStatus};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Arg {
+    /// Sums `n` digits, which are chosen by the caller of the computation.
+    Digits(u64),
+    Choice,
+}
+
+fn digits(arg: Arg) -> impl Generator<u64, Yield = Arg, Return = u64> + Clone {
+    move |_: u64| match arg {
+        Arg::Digits(0) | Arg::Choice => 0,
+        Arg::Digits(n) => {
+            let digit = yield Arg::Choice;
+            let rest = yield Arg::Digits(n - 1);
+            10 * rest + digit
+        }
+    }
+}
+
+fn is_choice(arg: &Arg) -> bool {
+    *arg == Arg::Choice
+}
+
+#[test]
+fn alternatives_are_explored_from_snapshots() {
+    // Enumerates all numbers with two digits in `1..=2`, depth first.
+    let mut results = Vec::new();
+    let mut branches = vec![Computation::new(digits, Arg::Digits(2))];
+    while let Some(mut computation) = branches.pop() {
+        match computation.run_until(is_choice) {
+            EvalStatus::Done(res) => results.push(res),
+            EvalStatus::Pending { .. } => {
+                let mut other = computation.snapshot();
+                assert_eq!(other.answer(2), Arg::Choice);
+                branches.push(other);
+                computation.answer(1);
+                branches.push(computation);
+            }
+        }
+    }
+    assert_eq!(results, vec![11, 21, 12, 22]);
+}
+
+#[test]
+fn deep_rollbacks() {
+    const LARGE: u64 = 10_000;
+    let result = with_stack_size(1024, || {
+        // Counts the calls until the innermost one, which makes a choice.
+        let chain = |arg: Option<u64>| {
+            move |_: u64| match arg {
+                Some(0) => yield None,
+                Some(n) => 1 + yield Some(n - 1),
+                None => 0,
+            }
+        };
+        let mut computation = Computation::new(chain, Some(LARGE));
+        let status = computation.run_until(Option::is_none);
+        let snapshot = computation.snapshot();
+        let mut results = Vec::new();
+        for choice in [3, 7] {
+            computation.rollback(&snapshot);
+            computation.answer(choice);
+            results.push(computation.run_until(|_| false));
+        }
+        (status, results, computation.is_done())
+    });
+    let results = vec![EvalStatus::Done(LARGE + 3), EvalStatus::Done(LARGE + 7)];
+    let status = EvalStatus::Pending {
+        depth: LARGE as usize,
+    };
+    assert_eq!(result.unwrap(), (status, results, true));
+}
@@ -0,0 +1,59 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+use crate::{trampoline_with, FrameStack, Plain, SpillStack};
+
+// A hand-written version of the `triangular` frame.
+#[derive(Clone, Copy)]
+struct Triangular {
+    n: u64,
+    state: u64,
+}
+
+unsafe impl Plain for Triangular {}
+
+impl Generator<u64> for Triangular {
+    type Yield = u64;
+    type Return = u64;
+
+    fn resume(mut self: Pin<&mut Self>, res: u64) -> GeneratorState<u64, u64> {
+        match (self.n, self.state) {
+            (0, _) => GeneratorState::Complete(0),
+            (n, 0) => {
+                self.state = 1;
+                GeneratorState::Yielded(n - 1)
+            }
+            (n, _) => GeneratorState::Complete(n + res),
+        }
+    }
+}
+
+const LARGE: u64 = 100_000;
+
+#[test]
+fn spilled_frames_are_restored() {
+    let triangular = trampoline_with(
+        || SpillStack::with_temp_file(1024).unwrap(),
+        |n: u64| Triangular { n, state: 0 },
+    );
+    assert_eq!(triangular(LARGE), LARGE * (LARGE + 1) / 2);
+}
+
+#[test]
+fn stack_keeps_frames_in_order() {
+    let mut stack = SpillStack::with_temp_file(64).unwrap();
+    for n in 0..1000u64 {
+        stack.push(n).unwrap();
+    }
+    assert_eq!(stack.len(), 1000);
+    assert!(stack.spilled() >= 990);
+    for n in (0..1000).rev() {
+        assert_eq!(stack.pop(), Some(n));
+        if n == 500 {
+            *stack.last_mut().unwrap() += 1000;
+            assert_eq!(stack.pop(), Some(1499));
+            stack.push(499).unwrap();
+        }
+    }
+    assert_eq!(stack.pop(), None);
+}
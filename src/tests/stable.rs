@@ -0,0 +1,39 @@
+use crate::{recurse_async, with_stack_size};
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn async_frames_are_stack_safe() {
+    let triangular = recurse_async(|caller, n: u64| async move {
+        if n == 0 {
+            0
+        } else {
+            n + caller.call(n - 1).await
+        }
+    });
+    assert_eq!(triangular(3), 6);
+    let result = with_stack_size(4 * 1024, || triangular(LARGE));
+    assert_eq!(result.unwrap(), LARGE * (LARGE + 1) / 2);
+}
+
+#[test]
+fn async_frames_make_several_calls() {
+    let binomial = recurse_async(|caller, (n, k): (u64, u64)| async move {
+        if k == 0 || k == n {
+            1
+        } else {
+            let a = caller.call((n - 1, k - 1)).await;
+            let b = caller.call((n - 1, k)).await;
+            a + b
+        }
+    });
+    assert_eq!(binomial((10, 5)), 252);
+}
+
+#[test]
+#[should_panic(expected = "frames may only await `Caller::call`")]
+fn foreign_futures_are_rejected() {
+    recurse_async(|_caller, ()| async {
+        std::future::pending::<()>().await;
+    })(());
+}
@@ -0,0 +1,247 @@
+use std::alloc::{AllocError, Allocator, Global, Layout};
+use std::cell::Cell;
+use std::ptr::NonNull;
+
+use crate::{trampoline_with, with_stack_size, FrameStack};
+
+struct Bounded<G> {
+    frames: Vec<G>,
+    capacity: usize,
+}
+
+impl<G> FrameStack<G> for Bounded<G> {
+    fn push(&mut self, frame: G) -> Result<(), G> {
+        if self.frames.len() < self.capacity {
+            self.frames.push(frame);
+            Ok(())
+        } else {
+            Err(frame)
+        }
+    }
+
+    fn pop(&mut self) -> Option<G> {
+        self.frames.pop()
+    }
+
+    fn last_mut(&mut self) -> Option<&mut G> {
+        self.frames.last_mut()
+    }
+
+    fn len(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+fn triangular(capacity: usize) -> impl Fn(u64) -> u64 {
+    trampoline_with(
+        move || Bounded {
+            frames: Vec::with_capacity(capacity),
+            capacity,
+        },
+        |n: u64| {
+            move |_: u64| {
+                if n == 0 {
+                    0
+                } else {
+                    n + yield (n - 1)
+                }
+            }
+        },
+    )
+}
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn custom_stack_is_used() {
+    let result = with_stack_size(1024, || triangular(LARGE as usize)(LARGE));
+    assert_eq!(result.unwrap(), LARGE * (LARGE + 1) / 2);
+}
+
+#[test]
+#[should_panic(expected = "frame stack is full")]
+fn full_stack_panics() {
+    triangular(10)(LARGE);
+}
+
+#[test]
+fn growth_policies_agree() {
+    use crate::{GrowingStack, Growth};
+
+    for growth in [Growth::Doubling, Growth::Fixed(100), Growth::Chunked(64)] {
+        let mut stack = GrowingStack::new(growth);
+        for n in 0..1000 {
+            stack.push(n).unwrap();
+            if n % 3 == 0 {
+                assert_eq!(stack.pop(), Some(n));
+            }
+        }
+        assert_eq!(stack.len(), 666);
+        *stack.last_mut().unwrap() += 1;
+        assert_eq!(stack.pop(), Some(999));
+        while stack.pop().is_some() {}
+        assert!(stack.is_empty());
+    }
+}
+
+#[test]
+fn bounded_trampoline_reports_excess_depth() {
+    use crate::{trampoline_bounded, DepthExceeded};
+
+    let mut triangular = trampoline_bounded(100, |n: u64| {
+        move |_: u64| {
+            if n == 0 {
+                0
+            } else {
+                n + yield (n - 1)
+            }
+        }
+    });
+    assert_eq!(triangular(100), Ok(5050));
+    assert_eq!(triangular(101), Err(DepthExceeded { max_depth: 100 }));
+    assert_eq!(triangular(10), Ok(55));
+}
+
+#[test]
+fn external_stacks_are_reused() {
+    use crate::{drive, try_drive, BoundedStack, DepthExceeded};
+
+    let triangular = |n: u64| move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) };
+    let result = with_stack_size(1024, || {
+        let mut stack = Vec::new();
+        let deep = drive(triangular, LARGE, &mut stack);
+        let capacity = stack.capacity();
+        let shallow = drive(triangular, 10, &mut stack);
+        (
+            deep,
+            shallow,
+            stack.is_empty(),
+            stack.capacity() == capacity,
+        )
+    });
+    assert_eq!(result.unwrap(), (LARGE * (LARGE + 1) / 2, 55, true, true));
+
+    let mut stack = BoundedStack::with_max_depth(5);
+    assert_eq!(try_drive(triangular, 5, &mut stack), Ok(15));
+    assert_eq!(
+        try_drive(triangular, 6, &mut stack),
+        Err(DepthExceeded { max_depth: 5 })
+    );
+}
+
+#[test]
+fn try_drive_leaves_existing_frames_alone() {
+    use crate::{try_drive, BoundedStack, DepthExceeded, FrameStack};
+
+    let triangular = |n: u64| move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) };
+    let mut stack = BoundedStack::with_max_depth(5);
+    stack.push(triangular(100)).ok().unwrap();
+    stack.push(triangular(200)).ok().unwrap();
+    assert_eq!(try_drive(triangular, 3, &mut stack), Ok(6));
+    assert_eq!(stack.len(), 2);
+    assert_eq!(
+        try_drive(triangular, 4, &mut stack),
+        Err(DepthExceeded { max_depth: 5 })
+    );
+    assert_eq!(stack.len(), 2);
+}
+
+#[test]
+fn reused_stacks_recover_from_panics() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use crate::trampoline_bounded;
+
+    let mut triangular = trampoline_bounded(100, |n: u64| {
+        move |_: u64| {
+            assert_ne!(n, 13, "unlucky");
+            if n == 0 {
+                0
+            } else {
+                n + yield (n - 1)
+            }
+        }
+    });
+    let res = panic::catch_unwind(AssertUnwindSafe(|| triangular(20)));
+    assert!(res.is_err());
+    assert_eq!(triangular(10), Ok(55));
+    assert_eq!(triangular(12), Ok(78));
+}
+
+#[test]
+fn bounded_stacks_do_not_depend_on_capacity() {
+    use crate::{BoundedStack, FrameStack};
+
+    // `Vec` reports an unbounded capacity for zero-sized types.
+    let mut stack = BoundedStack::with_max_depth(2);
+    assert_eq!(stack.max_depth(), 2);
+    assert_eq!(stack.push(()), Ok(()));
+    assert_eq!(stack.push(()), Ok(()));
+    assert_eq!(stack.push(()), Err(()));
+    assert_eq!(stack.len(), 2);
+}
+
+/// Forwards to the global allocator and records the peak number of bytes.
+#[derive(Default)]
+struct Tracking {
+    current: Cell<usize>,
+    peak: Cell<usize>,
+}
+
+unsafe impl Allocator for Tracking {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = Global.allocate(layout)?;
+        self.current.set(self.current.get() + layout.size());
+        self.peak.set(self.peak.get().max(self.current.get()));
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.current.set(self.current.get() - layout.size());
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn frame_stack_in_custom_allocator() {
+    use crate::trampoline_in;
+
+    let result = with_stack_size(1024, || {
+        let alloc = Tracking::default();
+        let triangular = trampoline_in(&alloc, |n: u64| {
+            move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) }
+        });
+        let res = triangular(LARGE);
+        (res, alloc.current.get(), alloc.peak.get())
+    });
+    let (res, current, peak) = result.unwrap();
+    assert_eq!(res, LARGE * (LARGE + 1) / 2);
+    assert_eq!(current, 0);
+    assert!(peak >= LARGE as usize);
+}
+
+#[test]
+fn memory_budgets() {
+    use crate::{trampoline_budgeted, BudgetedStack, StackSafeError};
+
+    let mut stack = BudgetedStack::with_heap_size(100, |frame: &Vec<u8>| frame.len());
+    let frame_size = std::mem::size_of::<Vec<u8>>() + 10;
+    while stack.push(vec![0; 10]).is_ok() {}
+    assert_eq!(stack.len(), 100 / frame_size);
+    assert_eq!(stack.used(), stack.len() * frame_size);
+    stack.pop();
+    assert_eq!(stack.used(), stack.len() * frame_size);
+
+    let triangular = |budget| {
+        trampoline_budgeted(budget, |n: u64| {
+            move |_: u64| if n == 0 { 0 } else { n + yield (n - 1) }
+        })
+    };
+    assert_eq!(triangular(1 << 20)(100), Ok(5050));
+    let err = triangular(0)(100).unwrap_err();
+    assert_eq!(err.depth, 0);
+    assert_eq!(
+        StackSafeError::from(err).to_string(),
+        "frames exceed the budget of 0 bytes at depth 0"
+    );
+}
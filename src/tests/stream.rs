@@ -0,0 +1,43 @@
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake};
+
+use futures_core::Stream;
+
+use crate::iter::preorder;
+use crate::{with_stack_size, TraversalStream};
+
+const LARGE: u64 = 10_000;
+
+#[derive(Default)]
+struct CountWakes(AtomicUsize);
+
+impl Wake for CountWakes {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn deep_traversal_yields_to_executor() {
+    let result = with_stack_size(1024, || {
+        let chain = preorder(0, |&n: &u64| if n < LARGE { Some(n + 1) } else { None });
+        let mut stream = TraversalStream::with_budget(chain, 100);
+        let wakes = Arc::new(CountWakes::default());
+        let waker = wakes.clone().into();
+        let mut cx = Context::from_waker(&waker);
+        let (mut sum, mut pending) = (0, 0);
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                Poll::Ready(Some(n)) => sum += n,
+                Poll::Ready(None) => break,
+                Poll::Pending => pending += 1,
+            }
+        }
+        assert_eq!(wakes.0.load(Ordering::Relaxed), pending);
+        (sum, pending)
+    });
+    // There are `LARGE + 2` ready polls, including the one for the end.
+    assert_eq!(result.unwrap(), (LARGE * (LARGE + 1) / 2, 100));
+}
@@ -0,0 +1,55 @@
+use syn::Expr;
+
+use crate::syn_expr::{drop_expr, fold_expr, visit_expr};
+use crate::{with_stack_size, Visitor, Walk};
+
+#[derive(Default)]
+struct Idents {
+    names: Vec<String>,
+}
+
+impl<'a> Visitor<'a, Expr> for Idents {
+    fn enter(&mut self, expr: &'a Expr) -> Walk {
+        if let Expr::Path(path) = expr {
+            self.names.push(path.path.get_ident().unwrap().to_string());
+        }
+        Walk::Continue
+    }
+}
+
+fn rename(expr: Expr) -> Expr {
+    match expr {
+        Expr::Path(path) if path.path.is_ident("a") => syn::parse_str("b").unwrap(),
+        expr => expr,
+    }
+}
+
+const LARGE: usize = 10_000;
+
+#[test]
+fn sub_expressions_are_visited_and_folded() {
+    let expr: Expr = syn::parse_str("f(a, [a; n]).m(x.y as T) + (a, !a)").unwrap();
+    let mut idents = Idents::default();
+    visit_expr(&expr, &mut idents);
+    assert_eq!(idents.names, ["f", "a", "a", "n", "x", "a", "a"]);
+
+    let expr = fold_expr(expr, rename);
+    let mut idents = Idents::default();
+    visit_expr(&expr, &mut idents);
+    assert_eq!(idents.names, ["f", "b", "b", "n", "x", "b", "b"]);
+}
+
+#[test]
+fn deep_expressions_are_stack_safe() {
+    let source = vec!["a"; LARGE].join(" + ");
+    let names = with_stack_size(128 * 1024, || {
+        let expr: Expr = syn::parse_str(&source).unwrap();
+        let expr = fold_expr(expr, rename);
+        let mut idents = Idents::default();
+        visit_expr(&expr, &mut idents);
+        drop_expr(expr);
+        idents.names
+    })
+    .unwrap();
+    assert_eq!(names, vec!["b"; LARGE]);
+}
@@ -0,0 +1,37 @@
+use crate::{trampoline_tail, with_stack_size, Tail, TailCall};
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn ackermann() {
+    let ackermann = trampoline_tail(|(m, n): (u64, u64)| {
+        move |_: u64| {
+            if m == 0 {
+                return Tail::Return(n + 1);
+            }
+            if n == 0 {
+                tail_call!((m - 1, 1));
+            }
+            let k = yield (m, n - 1);
+            TailCall::new((m - 1, k)).into()
+        }
+    });
+    assert_eq!(ackermann((2, 3)), 9);
+}
+
+#[test]
+fn tail_loops_run_in_constant_space() {
+    let result = with_stack_size(1024, || {
+        trampoline_tail(|(n, acc): (u64, u64)| {
+            move |_: u64| {
+                if n == 0 {
+                    return Tail::Return(acc);
+                }
+                // Keeps the frame a generator.
+                let one = yield (0, 1);
+                tail_call!((n - 1, acc + n * one))
+            }
+        })((LARGE, 0))
+    });
+    assert_eq!(result.unwrap(), LARGE * (LARGE + 1) / 2);
+}
@@ -0,0 +1,35 @@
+use crate::{trampoline_tco_checked, Call};
+
+#[test]
+fn correct_tail_calls_pass() {
+    let ackermann = trampoline_tco_checked(|(m, n): (u64, u64)| {
+        move |_: u64| {
+            if m == 0 {
+                n + 1
+            } else if n == 0 {
+                yield Call::tail((m - 1, 1))
+            } else {
+                let k = yield Call::normal((m, n - 1));
+                yield Call::tail((m - 1, k))
+            }
+        }
+    });
+    assert_eq!(ackermann((2, 3)), 9);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "`Call::tail` changes the result for 3: 0 with tail calls, 3 without")]
+fn work_after_tail_calls_is_detected() {
+    let count = trampoline_tco_checked(|n: u64| {
+        move |_: u64| {
+            if n == 0 {
+                0
+            } else {
+                let res = yield Call::tail(n - 1);
+                res + 1
+            }
+        }
+    });
+    count(3);
+}
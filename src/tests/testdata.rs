@@ -0,0 +1,47 @@
+use crate::testdata::{calc, graph, tree};
+use crate::{trampoline, with_stack_size};
+
+#[test]
+fn deep_inputs_can_be_dropped() {
+    let case = calc::one_branch(calc::Ops::Add);
+    assert_eq!(case.size, 1024 * 1024 - 1);
+    let (tree, depth, _) = tree::path(100_000);
+    assert_eq!(depth, 100_000);
+    let graph = graph::path_rev(100_000);
+    assert_eq!(graph.len(), graph::path_rev_sccs(100_000).len());
+    with_stack_size(1024, move || drop((case, tree, graph))).unwrap();
+}
+
+#[test]
+fn generated_expressions_evaluate_to_their_value() {
+    let case = calc::many_trees(calc::Ops::Add);
+    let eval = trampoline(|expr: &calc::Expr| {
+        move |_: calc::Num| match expr {
+            calc::Expr::Num(num) => *num,
+            calc::Expr::Add(lhs, rhs) => {
+                let lhs = yield lhs.as_ref();
+                lhs + yield rhs.as_ref()
+            }
+            calc::Expr::Mul(lhs, rhs) => {
+                let lhs = yield lhs.as_ref();
+                lhs * yield rhs.as_ref()
+            }
+        }
+    });
+    let res = with_stack_size(1024, || eval(&case.expr)).unwrap();
+    assert!((res - case.eval).abs() <= 1e-6 * case.eval.abs());
+}
+
+#[test]
+fn paths_can_be_empty() {
+    assert!(graph::path(0).is_empty());
+    assert!(graph::path_rev(0).is_empty());
+    assert_eq!(graph::path(2), vec![vec![graph::Node::new(1)], vec![]]);
+    assert_eq!(graph::path_rev(2), vec![vec![], vec![graph::Node::new(0)]]);
+}
+
+#[test]
+#[should_panic(expected = "a path needs at least one node")]
+fn empty_tree_paths_are_rejected() {
+    tree::path(0);
+}
@@ -0,0 +1,55 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::{with_stack_size, Thunk};
+
+#[test]
+fn thunks_are_forced_once() {
+    let calls = Cell::new(0);
+    let mut fibs = vec![Thunk::ready(0u64), Thunk::ready(1)];
+    for n in 2..=90 {
+        let (a, b) = (fibs[n - 1].clone(), fibs[n - 2].clone());
+        let calls = &calls;
+        fibs.push(Thunk::new(move |_: u64| {
+            calls.set(calls.get() + 1);
+            let a = yield a;
+            let b = yield b;
+            a + b
+        }));
+    }
+    assert!(!fibs[90].is_forced());
+    assert_eq!(fibs[90].force(), 2_880_067_194_370_816_120);
+    assert_eq!(fibs[90].force(), 2_880_067_194_370_816_120);
+    assert!(fibs[45].is_forced());
+    assert_eq!(calls.get(), 89);
+}
+
+#[test]
+fn deep_chains_do_not_overflow() {
+    const LARGE: u64 = 10_000;
+    let result = with_stack_size(1024, || {
+        let mut thunk = Thunk::ready(0u64);
+        for n in 1..LARGE {
+            let prev = thunk;
+            thunk = Thunk::new(move |_: u64| {
+                let res = yield prev.clone();
+                res + n
+            });
+        }
+        thunk.force()
+    });
+    assert_eq!(result.unwrap(), LARGE * (LARGE - 1) / 2);
+}
+
+#[test]
+#[should_panic(expected = "thunk depends on itself")]
+fn cycles_panic() {
+    let cell = Rc::new(RefCell::new(None::<Thunk<u64>>));
+    let this = cell.clone();
+    let thunk = Thunk::new(move |_: u64| {
+        let this = this.borrow().clone().unwrap();
+        yield this
+    });
+    *cell.borrow_mut() = Some(thunk.clone());
+    thunk.force();
+}
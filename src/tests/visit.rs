@@ -0,0 +1,39 @@
+use std::cell::{Cell, RefCell};
+
+use crate::{visit, with_stack_size};
+
+const LARGE: u64 = 10_000;
+
+#[test]
+fn frames_run_in_preorder() {
+    let order = RefCell::new(Vec::new());
+    visit(|n: u64| {
+        let order = &order;
+        move |_: ()| {
+            order.borrow_mut().push(n);
+            if n < 4 {
+                yield 2 * n;
+                yield 2 * n + 1;
+            }
+        }
+    })(1);
+    assert_eq!(order.into_inner(), vec![1, 2, 4, 5, 3, 6, 7]);
+}
+
+#[test]
+fn deep_visits_do_not_overflow() {
+    let result = with_stack_size(1024, || {
+        let deepest = Cell::new(0);
+        visit(|n: u64| {
+            let deepest = &deepest;
+            move |_: ()| {
+                deepest.set(n);
+                if n < LARGE {
+                    yield n + 1;
+                }
+            }
+        })(0);
+        deepest.get()
+    });
+    assert_eq!(result.unwrap(), LARGE);
+}
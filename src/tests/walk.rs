@@ -0,0 +1,88 @@
+use crate::{walk, with_stack_size, Visitor, Walk};
+
+enum Ast {
+    Var(&'static str),
+    Quote(Box<Ast>),
+    Apply(Vec<Ast>),
+}
+
+fn children(ast: &Ast) -> Vec<&Ast> {
+    match ast {
+        Ast::Var(_) => vec![],
+        Ast::Quote(ast) => vec![ast.as_ref()],
+        Ast::Apply(asts) => asts.iter().collect(),
+    }
+}
+
+#[derive(Default)]
+struct Printer {
+    output: String,
+}
+
+impl<'a> Visitor<'a, Ast> for Printer {
+    fn enter(&mut self, ast: &'a Ast) -> Walk {
+        match ast {
+            Ast::Var(name) => self.output.push_str(name),
+            Ast::Quote(_) => {
+                self.output.push('\'');
+                return Walk::SkipChildren;
+            }
+            Ast::Apply(_) => self.output.push('('),
+        }
+        Walk::Continue
+    }
+
+    fn exit(&mut self, ast: &'a Ast) {
+        match ast {
+            Ast::Apply(_) => self.output.push(')'),
+            _ => self.output.push(' '),
+        }
+    }
+}
+
+#[test]
+fn printer_skips_quotes() {
+    let ast = Ast::Apply(vec![
+        Ast::Var("f"),
+        Ast::Quote(Box::new(Ast::Var("x"))),
+        Ast::Apply(vec![Ast::Var("g")]),
+    ]);
+    let mut printer = Printer::default();
+    walk(&ast, children, &mut printer);
+    assert_eq!(printer.output, "(f ' (g ))");
+}
+
+#[test]
+fn walk_is_stack_safe() {
+    const LARGE: usize = 10_000;
+
+    struct Depth {
+        current: usize,
+        max: usize,
+    }
+
+    impl<'a> Visitor<'a, Ast> for Depth {
+        fn enter(&mut self, _: &'a Ast) -> Walk {
+            self.current += 1;
+            self.max = self.max.max(self.current);
+            Walk::Continue
+        }
+
+        fn exit(&mut self, _: &'a Ast) {
+            self.current -= 1;
+        }
+    }
+
+    let mut ast = Ast::Var("x");
+    for _ in 1..LARGE {
+        ast = Ast::Apply(vec![ast]);
+    }
+    let result = with_stack_size(1024, || {
+        let mut depth = Depth { current: 0, max: 0 };
+        walk(&ast, children, &mut depth);
+        depth.max
+    });
+    assert_eq!(result.unwrap(), LARGE);
+    // Dropping `ast` would recurse as deeply as it is nested.
+    std::mem::forget(ast);
+}
@@ -0,0 +1,51 @@
+use super::node;
+use crate::{with_stack_size, ChildrenMut, Zipper};
+
+type Tree = super::Tree<u32>;
+
+impl ChildrenMut for Tree {
+    type IterMut<'a> = std::slice::IterMut<'a, Tree>;
+
+    fn children_mut(&mut self) -> Self::IterMut<'_> {
+        self.children.iter_mut()
+    }
+}
+
+#[test]
+fn navigation_and_replacement() {
+    let tree = node(1, vec![node(2, vec![]), node(3, vec![node(4, vec![])])]);
+    let mut zipper = Zipper::new(tree);
+    assert!(!zipper.up());
+    assert!(!zipper.down(2));
+    assert!(zipper.down(1));
+    assert!(zipper.down(0));
+    assert_eq!(zipper.depth(), 2);
+    assert_eq!(zipper.replace(node(5, vec![])).label, 4);
+    assert!(zipper.up());
+    zipper.focus_mut().label = 6;
+    assert_eq!(
+        zipper.into_root(),
+        node(1, vec![node(2, vec![]), node(6, vec![node(5, vec![])])])
+    );
+}
+
+#[test]
+fn editing_deep_trees_is_stack_safe() {
+    const LARGE: u32 = 10_000;
+    let mut tree = node(0, vec![]);
+    for label in 1..LARGE {
+        tree = node(label, vec![tree]);
+    }
+    let result = with_stack_size(1024, move || {
+        let mut zipper = Zipper::new(tree);
+        while zipper.down(0) {}
+        zipper.focus_mut().label = LARGE;
+        let depth = zipper.depth();
+        let mut tree = zipper.into_root();
+        while tree.children.len() == 1 {
+            tree = tree.children.pop().unwrap();
+        }
+        (depth, tree.label)
+    });
+    assert_eq!(result.unwrap(), (LARGE as usize - 1, LARGE));
+}
@@ -0,0 +1,115 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::mem;
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use crate::{BoxedFrame, FrameGen};
+
+enum State<'a, T> {
+    Pending(BoxedFrame<'a, Thunk<'a, T>, T>),
+    Forcing,
+    Done(T),
+}
+
+/// A lazily computed value. The computation is a frame that yields the
+/// thunks it depends on and is resumed with their values. Forcing a thunk
+/// runs the frames of all thunks it transitively depends on with an explicit
+/// stack, so long chains of thunks never overflow the native stack.
+///
+/// Dropping a long chain of thunks that have not been forced yet still
+/// recurses on its length.
+pub struct Thunk<'a, T> {
+    state: Rc<RefCell<State<'a, T>>>,
+}
+
+impl<'a, T> Clone for Thunk<'a, T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: Rc::clone(&self.state),
+        }
+    }
+}
+
+impl<'a, T: Clone + Default> Thunk<'a, T> {
+    pub fn new(gen: impl FrameGen<Thunk<'a, T>, T> + 'a) -> Self {
+        Self::from_state(State::Pending(Box::new(gen)))
+    }
+
+    /// A thunk that is already forced.
+    pub fn ready(value: T) -> Self {
+        Self::from_state(State::Done(value))
+    }
+
+    fn from_state(state: State<'a, T>) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    pub fn is_forced(&self) -> bool {
+        matches!(*self.state.borrow(), State::Done(_))
+    }
+
+    /// Computes the value on the first call and returns a copy of the cached
+    /// value on all calls.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value depends on itself or if its computation panicked
+    /// during an earlier call.
+    pub fn force(&self) -> T {
+        let mut stack = Vec::new();
+        let mut current = match self.start() {
+            Ok(value) => return value,
+            Err(gen) => (self.clone(), gen),
+        };
+        let mut res = T::default();
+
+        loop {
+            match Pin::new(&mut current.1).resume(res) {
+                GeneratorState::Yielded(thunk) => match thunk.start() {
+                    Ok(value) => res = value,
+                    Err(gen) => {
+                        stack.push(current);
+                        current = (thunk, gen);
+                        res = T::default();
+                    }
+                },
+                GeneratorState::Complete(value) => {
+                    *current.0.state.borrow_mut() = State::Done(value.clone());
+                    match stack.pop() {
+                        None => return value,
+                        Some(top) => {
+                            current = top;
+                            res = value;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the value if it is cached or takes the frame computing it.
+    fn start(&self) -> Result<T, BoxedFrame<'a, Thunk<'a, T>, T>> {
+        let mut state = self.state.borrow_mut();
+        match mem::replace(&mut *state, State::Forcing) {
+            State::Pending(gen) => Err(gen),
+            State::Forcing => panic!("thunk depends on itself"),
+            State::Done(value) => {
+                *state = State::Done(value.clone());
+                Ok(value)
+            }
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Thunk<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &*self.state.borrow() {
+            State::Done(value) => f.debug_tuple("Thunk").field(value).finish(),
+            _ => f.write_str("Thunk(<unforced>)"),
+        }
+    }
+}
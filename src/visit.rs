@@ -0,0 +1,23 @@
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+/// Like `trampoline`, but for frames that return nothing, such as traversals
+/// that only have side effects. The driver does not pass results around at
+/// all: every frame is resumed with `()` once its callee completes.
+#[must_use = "`visit` returns the stack-safe function without calling it"]
+pub fn visit<Arg, Gen>(f: impl Fn(Arg) -> Gen) -> impl Fn(Arg)
+where
+    Gen: Generator<(), Yield = Arg, Return = ()> + Unpin,
+{
+    move |arg: Arg| {
+        let mut stack = vec![f(arg)];
+        while let Some(current) = stack.last_mut() {
+            match Pin::new(current).resume(()) {
+                GeneratorState::Yielded(arg) => stack.push(f(arg)),
+                GeneratorState::Complete(()) => {
+                    stack.pop();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Walk {
+    Continue,
+    SkipChildren,
+}
+
+/// Callbacks for `walk`. `exit` is called for every node `enter` has been
+/// called for, including nodes whose children have been skipped.
+pub trait Visitor<'a, T: ?Sized> {
+    fn enter(&mut self, _node: &'a T) -> Walk {
+        Walk::Continue
+    }
+
+    fn exit(&mut self, _node: &'a T) {}
+}
+
+pub fn walk<'a, T, I, V>(root: &'a T, children: impl Fn(&'a T) -> I, visitor: &mut V)
+where
+    T: ?Sized,
+    I: IntoIterator<Item = &'a T>,
+    V: Visitor<'a, T> + ?Sized,
+{
+    let mut stack = Vec::new();
+    match visitor.enter(root) {
+        Walk::Continue => stack.push((root, children(root).into_iter())),
+        Walk::SkipChildren => visitor.exit(root),
+    }
+
+    loop {
+        let next = match stack.last_mut() {
+            None => return,
+            Some((_, iter)) => iter.next(),
+        };
+        match next {
+            Some(child) => match visitor.enter(child) {
+                Walk::Continue => stack.push((child, children(child).into_iter())),
+                Walk::SkipChildren => visitor.exit(child),
+            },
+            None => {
+                let (node, _) = stack.pop().unwrap();
+                visitor.exit(node);
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use crate::ChildrenMut;
+
+/// A cursor into an owned tree that keeps the path from the root on the heap.
+///
+/// Moving down takes the child out of its parent, leaving `T::default()` in
+/// its place, and moving up puts it back. Hence, edits at arbitrary depth do
+/// not require any recursive rebuilding of the tree.
+pub struct Zipper<T> {
+    focus: T,
+    path: Vec<(T, usize)>,
+}
+
+impl<T: ChildrenMut + Default> Zipper<T> {
+    pub fn new(root: T) -> Self {
+        Self {
+            focus: root,
+            path: Vec::new(),
+        }
+    }
+
+    pub fn focus(&self) -> &T {
+        &self.focus
+    }
+
+    pub fn focus_mut(&mut self) -> &mut T {
+        &mut self.focus
+    }
+
+    /// The number of steps from the root to the focus.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Moves the focus to the `index`-th child. Returns `false` and leaves the
+    /// focus unchanged if there is no such child.
+    pub fn down(&mut self, index: usize) -> bool {
+        let child = match self.focus.children_mut().nth(index) {
+            Some(child) => std::mem::take(child),
+            None => return false,
+        };
+        let parent = std::mem::replace(&mut self.focus, child);
+        self.path.push((parent, index));
+        true
+    }
+
+    /// Moves the focus to its parent. Returns `false` if the focus is the root.
+    pub fn up(&mut self) -> bool {
+        match self.path.pop() {
+            Some((parent, index)) => {
+                let child = std::mem::replace(&mut self.focus, parent);
+                *self.focus.children_mut().nth(index).unwrap() = child;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the focus by `node` and returns the old focus.
+    pub fn replace(&mut self, node: T) -> T {
+        std::mem::replace(&mut self.focus, node)
+    }
+
+    /// Moves the focus to the root and returns the reconstructed tree.
+    pub fn into_root(mut self) -> T {
+        while self.up() {}
+        self.focus
+    }
+}
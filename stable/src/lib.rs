@@ -0,0 +1,114 @@
+//! A backend for `stack-safe` whose frames are `async` blocks instead of
+//! generators. Awaiting `Caller::call` plays the role of `yield`. Unlike the
+//! rest of `stack-safe`, this crate builds on stable Rust. `stack-safe`
+//! re-exports it.
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+struct Slot<Arg, Res> {
+    arg: Cell<Option<Arg>>,
+    res: Cell<Option<Res>>,
+}
+
+/// The handle through which a frame makes recursive calls.
+pub struct Caller<Arg, Res> {
+    slot: Rc<Slot<Arg, Res>>,
+}
+
+impl<Arg, Res> Clone for Caller<Arg, Res> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+impl<Arg, Res> Caller<Arg, Res> {
+    pub fn call(&self, arg: Arg) -> CallFuture<'_, Arg, Res> {
+        CallFuture {
+            slot: &self.slot,
+            arg: Some(arg),
+        }
+    }
+}
+
+pub struct CallFuture<'a, Arg, Res> {
+    slot: &'a Slot<Arg, Res>,
+    arg: Option<Arg>,
+}
+
+impl<'a, Arg, Res> Future for CallFuture<'a, Arg, Res> {
+    type Output = Res;
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Res> {
+        // SAFETY: We never move out of the pinned future, we only take the
+        // argument, which is not structurally pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        match this.arg.take() {
+            Some(arg) => {
+                this.slot.arg.set(Some(arg));
+                Poll::Pending
+            }
+            None => Poll::Ready(this.slot.res.take().expect("resumed without a result")),
+        }
+    }
+}
+
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    // SAFETY: The vtable functions do nothing and never touch the data.
+    unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+}
+
+/// Like `trampoline`, but with `async` blocks as frames. A frame receives a
+/// `Caller` and makes recursive calls with `caller.call(arg).await`.
+///
+/// # Panics
+///
+/// Panics if a frame awaits anything other than `Caller::call`.
+pub fn recurse_async<Arg, Res, Fut>(f: impl Fn(Caller<Arg, Res>, Arg) -> Fut) -> impl Fn(Arg) -> Res
+where
+    Fut: Future<Output = Res>,
+{
+    move |arg: Arg| {
+        let caller = Caller {
+            slot: Rc::new(Slot {
+                arg: Cell::new(None),
+                res: Cell::new(None),
+            }),
+        };
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut stack = Vec::new();
+        let mut current = Box::pin(f(caller.clone(), arg));
+
+        loop {
+            match current.as_mut().poll(&mut cx) {
+                Poll::Pending => {
+                    let arg = caller
+                        .slot
+                        .arg
+                        .take()
+                        .expect("frames may only await `Caller::call`");
+                    stack.push(current);
+                    current = Box::pin(f(caller.clone(), arg));
+                }
+                Poll::Ready(res) => match stack.pop() {
+                    None => return res,
+                    Some(top) => {
+                        current = top;
+                        caller.slot.res.set(Some(res));
+                    }
+                },
+            }
+        }
+    }
+}